@@ -0,0 +1,61 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Benchmarks for getting the hostname.
+//!
+//! On Windows `try_gethostname` measures the two calls of `GetComputerNameExW`
+//! and the conversion of the wide string buffer.
+
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use std::ffi::OsString;
+
+fn raw(c: &mut Criterion) {
+    c.bench_function("try_gethostname", |b| {
+        b.iter(|| black_box(gethostname::try_gethostname()))
+    });
+    c.bench_function("try_gethostname_into", |b| {
+        let mut buffer = [0; 256];
+        b.iter(|| black_box(gethostname::try_gethostname_into(&mut buffer).map(|name| name.len())))
+    });
+    c.bench_function("max_hostname_len", |b| {
+        b.iter(|| black_box(gethostname::max_hostname_len()))
+    });
+}
+
+fn cached(c: &mut Criterion) {
+    c.bench_function("gethostname_tls", |b| {
+        b.iter(|| black_box(gethostname::gethostname_tls(|hostname| hostname.len())))
+    });
+    c.bench_function("gethostname_tls_into", |b| {
+        let mut hostname = OsString::new();
+        b.iter(|| black_box(gethostname::gethostname_tls_into(&mut hostname)))
+    });
+}
+
+fn validated(c: &mut Criterion) {
+    c.bench_function("Hostname::current", |b| {
+        b.iter(|| black_box(gethostname::Hostname::current()))
+    });
+}
+
+fn fqdn(c: &mut Criterion) {
+    // Resolving involves DNS lookups, so take fewer samples
+    let mut group = c.benchmark_group("fqdn");
+    group.sample_size(10);
+    group.bench_function("getfqdn", |b| b.iter(|| black_box(gethostname::getfqdn())));
+    group.finish();
+}
+
+criterion_group!(benches, raw, cached, validated, fqdn);
+criterion_main!(benches);
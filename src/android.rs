@@ -0,0 +1,312 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Fallbacks for the hostname on Android.
+//!
+//! Android leaves the kernel hostname at `localhost` on most devices.  Older
+//! releases set the `net.hostname` system property to a name like
+//! `android-0123456789abcdef` instead, so use this property if it exists, and
+//! otherwise derive a name from the serial number or the model of the device.
+//!
+//! [Termux] users often set `HOSTNAME` in their shell for the prompt; in
+//! Termux prefer this variable over the system properties.
+//!
+//! With the `android` feature also get the name the user gave the device, from
+//! the Android settings through JNI.
+//!
+//! [Termux]: https://termux.dev
+
+use std::ffi::OsString;
+
+#[cfg(all(target_os = "android", feature = "android"))]
+use crate::error::HostnameError;
+
+/// The maximum size of the value of a system property, including the trailing
+/// NUL byte.
+const PROP_VALUE_MAX: usize = 92;
+
+/// The maximum length of a label in a hostname.
+const LABEL_MAX: usize = 63;
+
+/// Replace the hostname in `buffer` if it is `localhost`.
+///
+/// `buffer` holds the result of a successful call to `gethostname`; write the
+/// fallback hostname into `buffer` like `gethostname` would.  Leave `buffer`
+/// alone if the hostname isn't `localhost` or if there's no fallback.
+#[cfg(target_os = "android")]
+pub(crate) fn replace_localhost(buffer: &mut [u8]) {
+    use std::os::unix::ffi::OsStrExt;
+
+    if !buffer.starts_with(b"localhost\0") {
+        return;
+    }
+    if let Some(hostname) = termux_hostname(
+        std::env::var_os("TERMUX_VERSION"),
+        std::env::var_os("PREFIX"),
+        std::env::var_os("HOSTNAME"),
+    ) {
+        warn!(
+            "Kernel hostname is localhost, using $HOSTNAME {:?}",
+            hostname
+        );
+        crate::sys::copy_hostname(buffer, hostname.as_bytes());
+        return;
+    }
+    let mut net_hostname = [0; PROP_VALUE_MAX];
+    let mut serial = [0; PROP_VALUE_MAX];
+    let mut model = [0; PROP_VALUE_MAX];
+    let mut name = [0; PROP_VALUE_MAX];
+    let len = fallback_name(
+        system_property(b"net.hostname\0", &mut net_hostname),
+        system_property(b"ro.serialno\0", &mut serial),
+        system_property(b"ro.product.model\0", &mut model),
+        &mut name,
+    );
+    if 0 < len {
+        warn!(
+            "Kernel hostname is localhost, using {}",
+            String::from_utf8_lossy(&name[..len])
+        );
+        crate::sys::copy_hostname(buffer, &name[..len]);
+    }
+}
+
+/// Get the hostname from `$HOSTNAME` if we're running in Termux.
+///
+/// Termux sets `$TERMUX_VERSION` in its shells, and older releases only set
+/// `$PREFIX` to a directory in the data directory of the Termux app.  Ignore
+/// `$HOSTNAME` if it's empty or `localhost`.
+fn termux_hostname(
+    termux_version: Option<OsString>,
+    prefix: Option<OsString>,
+    hostname: Option<OsString>,
+) -> Option<OsString> {
+    let in_termux = termux_version.is_some()
+        || prefix.is_some_and(|prefix| {
+            prefix
+                .to_string_lossy()
+                .starts_with("/data/data/com.termux/")
+        });
+    hostname.filter(|hostname| in_termux && !hostname.is_empty() && hostname != "localhost")
+}
+
+/// Get the value of the system property `name`, which ends with a NUL byte.
+///
+/// Return an empty value if the property doesn't exist, or if we may not read
+/// it; apps may not read the serial number on Android 8 and newer.
+#[cfg(target_os = "android")]
+fn system_property<'a>(name: &[u8], value: &'a mut [u8; PROP_VALUE_MAX]) -> &'a [u8] {
+    use libc::c_char;
+
+    let len = unsafe {
+        libc::__system_property_get(
+            name.as_ptr() as *const c_char,
+            value.as_mut_ptr() as *mut c_char,
+        )
+    };
+    value.get(..len as usize).unwrap_or_default()
+}
+
+/// Choose a fallback hostname and write it to `name`.
+///
+/// Use `net_hostname` if not empty.  Otherwise derive a name from `serial`,
+/// or from `model` if the serial number isn't known, by prefixing `android-` and
+/// replacing everything but ASCII letters and digits with hyphens.
+///
+/// Return the length of the name, or zero if there's no fallback.
+fn fallback_name(
+    net_hostname: &[u8],
+    serial: &[u8],
+    model: &[u8],
+    name: &mut [u8; PROP_VALUE_MAX],
+) -> usize {
+    if !net_hostname.is_empty() {
+        let len = net_hostname.len().min(name.len());
+        name[..len].copy_from_slice(&net_hostname[..len]);
+        return len;
+    }
+    let suffix = if serial.is_empty() || serial == b"unknown" {
+        model
+    } else {
+        serial
+    };
+    if suffix.is_empty() {
+        return 0;
+    }
+    const PREFIX: &[u8] = b"android-";
+    name[..PREFIX.len()].copy_from_slice(PREFIX);
+    let mut len = PREFIX.len();
+    for &b in suffix {
+        if len == LABEL_MAX {
+            break;
+        }
+        if b.is_ascii_alphanumeric() {
+            name[len] = b.to_ascii_lowercase();
+            len += 1;
+        } else if name[len - 1] != b'-' {
+            name[len] = b'-';
+            len += 1;
+        }
+    }
+    while name[len - 1] == b'-' {
+        len -= 1;
+    }
+    len
+}
+
+/// Get the name the user gave the current device, e.g. `Anna's Pixel 8`.
+///
+/// Return the `device_name` global setting, or if that isn't set the name of
+/// the device for Bluetooth, from the `bluetooth_name` secure setting.  This is
+/// the name Android shows in its settings, and to other devices.
+///
+/// Call Android through JNI with the Java VM and the context of the current
+/// app from [ndk-context], which frameworks like `android-activity` set up.
+/// Fail with [`HostnameError::ServiceFailed`] if JNI fails, and with
+/// [`HostnameError::NotFound`] if the device has no name.
+///
+/// [ndk-context]: https://docs.rs/ndk-context
+#[cfg(all(target_os = "android", feature = "android"))]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn device_name() -> Result<String, HostnameError> {
+    use jni::objects::{JObject, JString};
+    use jni::JavaVM;
+
+    let context = ndk_context::android_context();
+    let vm = unsafe { JavaVM::from_raw(context.vm().cast()) }.map_err(jni_failed)?;
+    let mut env = vm.attach_current_thread().map_err(jni_failed)?;
+    let result = (|| {
+        let context = unsafe { JObject::from_raw(context.context().cast()) };
+        let resolver = env
+            .call_method(
+                &context,
+                "getContentResolver",
+                "()Landroid/content/ContentResolver;",
+                &[],
+            )?
+            .l()?;
+        for (class, key) in &[
+            ("android/provider/Settings$Global", "device_name"),
+            ("android/provider/Settings$Secure", "bluetooth_name"),
+        ] {
+            let key = env.new_string(key)?;
+            let value = env
+                .call_static_method(
+                    *class,
+                    "getString",
+                    "(Landroid/content/ContentResolver;Ljava/lang/String;)Ljava/lang/String;",
+                    &[(&resolver).into(), (&key).into()],
+                )?
+                .l()?;
+            if !value.is_null() {
+                let value: String = env.get_string(&JString::from(value))?.into();
+                if !value.trim().is_empty() {
+                    return Ok(Some(value));
+                }
+            }
+        }
+        Ok(None)
+    })();
+    if env.exception_check().unwrap_or(false) {
+        // Don't leave a pending Java exception behind for the app
+        let _ = env.exception_clear();
+    }
+    result.map_err(jni_failed)?.ok_or(HostnameError::NotFound {
+        what: "device name",
+    })
+}
+
+#[cfg(all(target_os = "android", feature = "android"))]
+fn jni_failed(error: jni::errors::Error) -> HostnameError {
+    HostnameError::ServiceFailed {
+        service: "JNI",
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{fallback_name, termux_hostname, PROP_VALUE_MAX};
+    use pretty_assertions::assert_eq;
+
+    fn fallback(net_hostname: &str, serial: &str, model: &str) -> String {
+        let mut name = [0; PROP_VALUE_MAX];
+        let len = fallback_name(
+            net_hostname.as_bytes(),
+            serial.as_bytes(),
+            model.as_bytes(),
+            &mut name,
+        );
+        String::from_utf8(name[..len].to_vec()).unwrap()
+    }
+
+    #[test]
+    fn hostname_variable_in_termux() {
+        let hostname = || Some("phone".into());
+        assert_eq!(
+            termux_hostname(Some("0.118.0".into()), None, hostname()),
+            hostname()
+        );
+        assert_eq!(
+            termux_hostname(
+                None,
+                Some("/data/data/com.termux/files/usr".into()),
+                hostname()
+            ),
+            hostname()
+        );
+        assert_eq!(termux_hostname(None, None, hostname()), None);
+        assert_eq!(termux_hostname(None, Some("/usr".into()), hostname()), None);
+        assert_eq!(termux_hostname(Some("0.118.0".into()), None, None), None);
+        assert_eq!(
+            termux_hostname(Some("0.118.0".into()), None, Some("localhost".into())),
+            None
+        );
+    }
+
+    #[test]
+    fn net_hostname_property() {
+        assert_eq!(
+            fallback("android-0123456789abcdef", "R58M12ABCDE", "Pixel 7"),
+            "android-0123456789abcdef"
+        );
+    }
+
+    #[test]
+    fn derived_from_serial() {
+        assert_eq!(
+            fallback("", "R58M12ABCDE", "Pixel 7"),
+            "android-r58m12abcde"
+        );
+    }
+
+    #[test]
+    fn derived_from_model() {
+        assert_eq!(fallback("", "", "Pixel 7 Pro"), "android-pixel-7-pro");
+        assert_eq!(fallback("", "unknown", "SM-G991B"), "android-sm-g991b");
+        assert_eq!(fallback("", "", "Galaxy (5G) "), "android-galaxy-5g");
+        assert_eq!(fallback("", "", "???"), "android");
+    }
+
+    #[test]
+    fn derived_name_fits_into_label() {
+        let model = "x".repeat(100);
+        assert_eq!(fallback("", "", &model).len(), 63);
+    }
+
+    #[test]
+    fn no_fallback() {
+        assert_eq!(fallback("", "", ""), "");
+    }
+}
@@ -0,0 +1,252 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Anonymized identifiers derived from the hostname.
+
+use std::fmt;
+use std::hash::{Hash, Hasher};
+
+use hmac::{Hmac, Mac};
+use sha2::{Digest, Sha256};
+
+use crate::error::HostnameError;
+
+/// An identifier derived from the hostname, which doesn't reveal the hostname.
+///
+/// Display the identifier as lowercase hex digits, or use
+/// [`to_base32()`](AnonymizedId::to_base32) for a shorter representation.
+#[derive(Clone, Copy)]
+pub struct AnonymizedId {
+    digest: [u8; 32],
+    len: usize,
+}
+
+impl AnonymizedId {
+    fn new(digest: [u8; 32]) -> AnonymizedId {
+        AnonymizedId {
+            digest,
+            len: digest.len(),
+        }
+    }
+
+    /// Keep only the first `len` bytes of this identifier.
+    ///
+    /// Shorter identifiers leak less, but collide more easily; eight bytes are
+    /// plenty to tell apart the machines of a fleet.  Clamp `len` to between
+    /// 1 and 32 bytes.
+    pub fn truncate(self, len: usize) -> AnonymizedId {
+        AnonymizedId {
+            len: len.clamp(1, self.len),
+            ..self
+        }
+    }
+
+    /// The bytes of this identifier.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.digest[..self.len]
+    }
+
+    /// This identifier as lowercase hex digits.
+    pub fn to_hex(&self) -> String {
+        self.as_bytes()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
+    }
+
+    /// This identifier in lowercase [base32], without padding.
+    ///
+    /// [base32]: https://tools.ietf.org/html/rfc4648#section-6
+    pub fn to_base32(&self) -> String {
+        const ALPHABET: &[u8; 32] = b"abcdefghijklmnopqrstuvwxyz234567";
+        let mut encoded = String::with_capacity((self.len * 8 + 4) / 5);
+        let mut buffer: u16 = 0;
+        let mut bits = 0;
+        for &byte in self.as_bytes() {
+            buffer = (buffer << 8) | u16::from(byte);
+            bits += 8;
+            while 5 <= bits {
+                bits -= 5;
+                encoded.push(char::from(ALPHABET[usize::from((buffer >> bits) & 0x1f)]));
+            }
+        }
+        if 0 < bits {
+            encoded.push(char::from(
+                ALPHABET[usize::from((buffer << (5 - bits)) & 0x1f)],
+            ));
+        }
+        encoded
+    }
+}
+
+// Compare, hash and debug only the bytes which are left after truncating, so
+// that the rest of the digest doesn't leak.
+
+impl PartialEq for AnonymizedId {
+    fn eq(&self, other: &Self) -> bool {
+        self.as_bytes() == other.as_bytes()
+    }
+}
+
+impl Eq for AnonymizedId {}
+
+impl Hash for AnonymizedId {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.as_bytes().hash(state);
+    }
+}
+
+impl fmt::Debug for AnonymizedId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("AnonymizedId").field(&self.to_hex()).finish()
+    }
+}
+
+impl fmt::Display for AnonymizedId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.to_hex())
+    }
+}
+
+/// Derive a stable anonymized identifier from the hostname.
+///
+/// Return the SHA-256 digest of the hostname of the current machine with its
+/// case folded with [`fold_case()`](crate::fold_case), so that the
+/// identifier doesn't change if only the case of the hostname does.
+/// Telemetry can then correlate events of the same machine without sending
+/// the hostname itself.
+///
+/// Note that anyone who can guess the hostname can compute its identifier and
+/// thus tell whether a machine has this hostname.  Use [`keyed_id()`] to
+/// prevent this, and to derive identifiers which others cannot link to yours.
+///
+/// ```
+/// let id = gethostname::anonymized_id().unwrap();
+/// println!("Machine: {}", id.truncate(8));
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn anonymized_id() -> Result<AnonymizedId, HostnameError> {
+    let hostname = crate::try_gethostname()?;
+    let id = anonymize(&hostname.to_string_lossy());
+    crate::zeroize::wipe_os_string(hostname);
+    Ok(id)
+}
+
+fn anonymize(hostname: &str) -> AnonymizedId {
+    let name = crate::fold_case(hostname);
+    let id = AnonymizedId::new(Sha256::digest(name.as_bytes()).into());
+    crate::zeroize::wipe(name);
+    id
+}
+
+/// Derive a stable anonymized identifier from the hostname with a `secret`
+/// key.
+///
+/// Like [`anonymized_id()`], but return the HMAC-SHA-256 of the case-folded
+/// hostname, keyed with `secret`.  Without the secret nobody can compute the
+/// identifier of a given hostname, and different secrets derive unlinkable
+/// identifiers from the same hostname.  Give each product or vendor its own
+/// secret, and keep it stable to keep the identifiers stable.
+///
+/// ```
+/// let id = gethostname::keyed_id(b"my-analytics-secret").unwrap();
+/// println!("Machine: {}", id.truncate(8));
+/// ```
+#[cfg_attr(
+    feature = "tracing",
+    tracing::instrument(level = "debug", err, skip(secret))
+)]
+pub fn keyed_id(secret: &[u8]) -> Result<AnonymizedId, HostnameError> {
+    let hostname = crate::try_gethostname()?;
+    let id = anonymize_keyed(secret, &hostname.to_string_lossy());
+    crate::zeroize::wipe_os_string(hostname);
+    Ok(id)
+}
+
+fn anonymize_keyed(secret: &[u8], hostname: &str) -> AnonymizedId {
+    let name = crate::fold_case(hostname);
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC takes keys of any size");
+    mac.update(name.as_bytes());
+    crate::zeroize::wipe(name);
+    AnonymizedId::new(mac.finalize().into_bytes().into())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{anonymize, anonymize_keyed, AnonymizedId};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn anonymized_id_is_sha256_of_lowercase_hostname() {
+        let id = anonymize("Foo.Example");
+        assert_eq!(id, anonymize("foo.example"));
+        assert_eq!(
+            id.to_hex(),
+            "0611803e517c3adcc1ecdd70678a03a83dfd5aa80464d8b302fcc8a404e7200d"
+        );
+        assert_eq!(
+            id.to_base32(),
+            "ayiyapsrpq5nzqpm3vygpcqdva672wviarsnrmyc7tekibhheagq"
+        );
+    }
+
+    #[test]
+    fn truncated_anonymized_id() {
+        let id = anonymize("foo.example");
+        assert_eq!(id.truncate(4).to_string(), "0611803e");
+        assert_eq!(id.truncate(5).to_base32(), "ayiyapsr");
+        assert_eq!(id.truncate(0).as_bytes().len(), 1);
+        assert_eq!(id.truncate(64).as_bytes().len(), 32);
+    }
+
+    #[test]
+    fn truncated_ids_only_consider_remaining_bytes() {
+        let mut digest = [0; 32];
+        let one = AnonymizedId::new(digest);
+        digest[31] = 1;
+        let other = AnonymizedId::new(digest);
+        assert_ne!(one, other);
+        assert_eq!(one.truncate(4), other.truncate(4));
+        assert_eq!(
+            format!("{:?}", other.truncate(4)),
+            "AnonymizedId(\"00000000\")"
+        );
+        let mut ids = std::collections::HashSet::new();
+        ids.insert(one.truncate(4));
+        assert!(ids.contains(&other.truncate(4)));
+    }
+
+    #[test]
+    fn keyed_id_is_hmac_sha256_of_lowercase_hostname() {
+        let id = anonymize_keyed(b"vendor-a", "FOO.example");
+        assert_eq!(id, anonymize_keyed(b"vendor-a", "foo.example"));
+        assert_eq!(
+            id.to_hex(),
+            "b0525e369259e8f91bd09c8231d369d1d3e9619832f43c62373c11d72f162211"
+        );
+        assert_eq!(
+            anonymize_keyed(b"vendor-b", "foo.example").to_hex(),
+            "1e2fff708a169e1c3f0c38d8fa56d57b125475feb2f9e23301ff28cd57c6bb2b"
+        );
+    }
+
+    #[test]
+    fn anonymized_id_of_current_machine() {
+        let hostname = crate::gethostname();
+        assert_eq!(
+            super::anonymized_id().unwrap(),
+            anonymize(&hostname.to_string_lossy())
+        );
+    }
+}
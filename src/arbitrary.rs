@@ -0,0 +1,143 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Arbitrary hostnames and FQDNs for fuzzing with the [arbitrary] crate.
+//!
+//! [arbitrary]: https://docs.rs/arbitrary
+
+use ::arbitrary::{Arbitrary, Result, Unstructured};
+
+use crate::fqdn::Fqdn;
+use crate::hostname::{is_numeric, Hostname, MAX_HOSTNAME_LEN, MAX_LABEL_LEN};
+
+const ALPHANUMERIC: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789";
+const LABEL_CHARACTERS: &[u8] = b"abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ0123456789-";
+
+/// Generate an arbitrary label of `len` bytes.
+fn arbitrary_label(u: &mut Unstructured<'_>, len: usize) -> Result<String> {
+    let mut label = String::with_capacity(len);
+    for i in 0..len {
+        // Labels must neither start nor end with a hyphen
+        let characters = if i == 0 || i == len - 1 {
+            ALPHANUMERIC
+        } else {
+            LABEL_CHARACTERS
+        };
+        label.push(char::from(*u.choose(characters)?));
+    }
+    Ok(label)
+}
+
+/// Generate arbitrary valid ASCII hostnames.
+///
+/// Prefer labels of one and of 63 bytes, and names which fill up the maximum
+/// length of 253 bytes, to exercise the boundaries of hostname handling.
+impl<'a> Arbitrary<'a> for Hostname {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        let mut name = String::new();
+        loop {
+            let remaining = if name.is_empty() {
+                MAX_HOSTNAME_LEN
+            } else {
+                // Leave room for the dot
+                MAX_HOSTNAME_LEN - name.len() - 1
+            };
+            let max = remaining.min(MAX_LABEL_LEN);
+            let len = match u.int_in_range(0..=3)? {
+                0 => 1,
+                1 => max,
+                _ => u.int_in_range(1..=max)?,
+            };
+            if !name.is_empty() {
+                name.push('.');
+            }
+            name.push_str(&arbitrary_label(u, len)?);
+            if MAX_HOSTNAME_LEN < name.len() + 2 || !bool::arbitrary(u)? {
+                break;
+            }
+        }
+        let top_label = name.rfind('.').map_or(0, |dot| dot + 1);
+        if is_numeric(&name[top_label..]) {
+            // The last label must not look like part of an IPv4 address
+            name.replace_range(top_label..=top_label, "x");
+        }
+        Ok(Hostname::new_unchecked(name))
+    }
+
+    fn size_hint(_depth: usize) -> (usize, Option<usize>) {
+        (1, None)
+    }
+}
+
+/// Generate arbitrary valid FQDNs from [arbitrary hostnames](Hostname).
+impl<'a> Arbitrary<'a> for Fqdn {
+    fn arbitrary(u: &mut Unstructured<'a>) -> Result<Self> {
+        Hostname::arbitrary(u).map(Fqdn::from)
+    }
+
+    fn size_hint(depth: usize) -> (usize, Option<usize>) {
+        Hostname::size_hint(depth)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Fqdn, Hostname};
+    use ::arbitrary::{Arbitrary, Unstructured};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn arbitrary_hostnames_are_valid() {
+        let mut state: u32 = 42;
+        let mut max_labels = 0;
+        let mut max_names = 0;
+        for _ in 0..500 {
+            let data = (0..1024)
+                .map(|_| {
+                    state = state.wrapping_mul(1_103_515_245).wrapping_add(12345);
+                    (state >> 16) as u8
+                })
+                .collect::<Vec<_>>();
+            let hostname = Hostname::arbitrary(&mut Unstructured::new(&data)).unwrap();
+            assert_eq!(hostname.as_str().parse::<Hostname>().unwrap(), hostname);
+            if hostname.as_str().split('.').any(|label| label.len() == 63) {
+                max_labels += 1;
+            }
+            if hostname.as_str().len() == 253 {
+                max_names += 1;
+            }
+        }
+        assert!(0 < max_labels);
+        assert!(0 < max_names);
+    }
+
+    #[test]
+    fn arbitrary_fqdns_are_valid() {
+        let data = (0..=255).cycle().take(4096).collect::<Vec<u8>>();
+        let mut u = Unstructured::new(&data);
+        for _ in 0..10 {
+            let fqdn = Fqdn::arbitrary(&mut u).unwrap();
+            assert_eq!(fqdn.as_absolute().parse::<Fqdn>().unwrap(), fqdn);
+            assert!(fqdn.as_absolute().ends_with('.'));
+        }
+        let fqdn = Fqdn::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(fqdn.as_absolute(), "a.");
+    }
+
+    #[test]
+    fn arbitrary_hostname_from_empty_data() {
+        let hostname = Hostname::arbitrary(&mut Unstructured::new(&[])).unwrap();
+        assert_eq!(hostname.as_str(), "a");
+    }
+}
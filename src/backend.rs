@@ -0,0 +1,246 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Pluggable DNS backends for the resolver and diagnostics.
+
+use std::fmt;
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// The DNS operations behind [`Resolver`](crate::Resolver) and
+/// [`diagnose_with()`](crate::diagnose_with).
+///
+/// By default this crate asks the resolver of the operating system, with
+/// [`SystemBackend`].  Implement this trait to route lookups through another
+/// resolver instead, e.g. `hickory-resolver` to query a specific DNS server
+/// on a split-horizon network, or a DNS-over-HTTPS client where policy
+/// forbids plain DNS.
+///
+/// ```
+/// use std::net::{IpAddr, Ipv4Addr};
+/// use gethostname::{HostnameError, Resolver, ResolverBackend};
+///
+/// /// Resolve every name to the same address.
+/// #[derive(Debug)]
+/// struct Fixed;
+///
+/// impl ResolverBackend for Fixed {
+///     fn addresses(&self, _name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+///         Ok(vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))])
+///     }
+///
+///     fn name_of(&self, _address: IpAddr) -> Result<String, HostnameError> {
+///         Ok("host.example.com".to_string())
+///     }
+/// }
+///
+/// let hostname = gethostname::gethostname().into_string().unwrap();
+/// let resolver = Resolver::new().backend(Fixed);
+/// assert_eq!(resolver.fqdn().unwrap(), hostname.as_str());
+/// ```
+pub trait ResolverBackend: fmt::Debug + Send + Sync {
+    /// Resolve `name` to its addresses.
+    ///
+    /// Return an empty list, or fail with [`HostnameError::NotFound`], if
+    /// `name` has no addresses.
+    fn addresses(&self, name: &str) -> Result<Vec<IpAddr>, HostnameError>;
+
+    /// Look up the name of `address` in reverse DNS.
+    fn name_of(&self, address: IpAddr) -> Result<String, HostnameError>;
+
+    /// Get the canonical name of `name`, i.e. follow `CNAME` records.
+    ///
+    /// By default return `name` itself if it has any address, for backends
+    /// which can't follow `CNAME` records.
+    fn canonical_name(&self, name: &str) -> Result<String, HostnameError> {
+        if self.addresses(name)?.is_empty() {
+            Err(HostnameError::NotFound { what: "address" })
+        } else {
+            Ok(name.to_string())
+        }
+    }
+}
+
+/// The resolver of the operating system.
+///
+/// Resolve names with `getaddrinfo` and addresses with `getnameinfo`, which
+/// consult `/etc/hosts`, DNS, and whatever else the system is configured
+/// for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub struct SystemBackend;
+
+impl ResolverBackend for SystemBackend {
+    fn addresses(&self, name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+        crate::resolver::address_info(name)
+    }
+
+    fn name_of(&self, address: IpAddr) -> Result<String, HostnameError> {
+        let name = crate::resolver::name_info(&SocketAddr::new(address, 0))?;
+        Ok(name.to_string_lossy().into_owned())
+    }
+
+    #[cfg(not(windows))]
+    fn canonical_name(&self, name: &str) -> Result<String, HostnameError> {
+        let name = crate::fqdn::canonical_name(std::ffi::OsStr::new(name))?;
+        Ok(name.to_string_lossy().into_owned())
+    }
+}
+
+/// A backend which gives up on another backend after a timeout.
+///
+/// Run every lookup of the inner backend on a helper thread, and fail with a
+/// [transient](HostnameError::is_transient) error if it doesn't answer within
+/// the timeout, e.g. to bound [`diagnose_with()`](crate::diagnose_with).  The
+/// helper thread keeps running after a timeout until the inner backend
+/// returns.
+///
+/// ```
+/// use std::time::Duration;
+/// use gethostname::{SystemBackend, TimeoutBackend};
+///
+/// let backend = TimeoutBackend::new(SystemBackend, Duration::from_secs(2));
+/// println!("Findings: {:?}", gethostname::diagnose_with(&backend));
+/// ```
+#[derive(Debug, Clone)]
+pub struct TimeoutBackend {
+    backend: Arc<dyn ResolverBackend>,
+    timeout: Duration,
+}
+
+impl TimeoutBackend {
+    /// Give up on `backend` after `timeout`.
+    pub fn new(backend: impl ResolverBackend + 'static, timeout: Duration) -> TimeoutBackend {
+        TimeoutBackend {
+            backend: Arc::new(backend),
+            timeout,
+        }
+    }
+
+    /// Run `lookup` with the inner backend within the timeout.
+    fn within<T, F>(&self, lookup: F) -> Result<T, HostnameError>
+    where
+        T: Send + 'static,
+        F: FnOnce(&dyn ResolverBackend) -> Result<T, HostnameError> + Send + 'static,
+    {
+        let backend = self.backend.clone();
+        crate::resolver::within(Some(self.timeout), move || lookup(&*backend))
+    }
+}
+
+impl ResolverBackend for TimeoutBackend {
+    fn addresses(&self, name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+        let name = name.to_string();
+        self.within(move |backend| backend.addresses(&name))
+    }
+
+    fn name_of(&self, address: IpAddr) -> Result<String, HostnameError> {
+        self.within(move |backend| backend.name_of(address))
+    }
+
+    fn canonical_name(&self, name: &str) -> Result<String, HostnameError> {
+        let name = name.to_string();
+        self.within(move |backend| backend.canonical_name(&name))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{ResolverBackend, SystemBackend, TimeoutBackend};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::net::{IpAddr, Ipv4Addr};
+    use std::time::Duration;
+
+    #[test]
+    fn system_backend_resolves_localhost() {
+        let addresses = SystemBackend.addresses("localhost").unwrap();
+        assert!(addresses.iter().all(IpAddr::is_loopback));
+        let name = SystemBackend
+            .name_of(IpAddr::V4(Ipv4Addr::LOCALHOST))
+            .unwrap();
+        assert!(name.starts_with("localhost"));
+    }
+
+    #[test]
+    fn system_backend_keeps_resolver_error_codes() {
+        match SystemBackend.addresses("nonexistent.invalid") {
+            Err(HostnameError::ResolutionFailed { code, .. }) => assert_ne!(code, 0),
+            result => panic!("Unexpected result: {:?}", result),
+        }
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn temporary_resolver_failures_are_transient() {
+        assert!(crate::resolver::gai_error(libc::EAI_AGAIN).is_transient());
+        assert!(!crate::resolver::gai_error(libc::EAI_NONAME).is_transient());
+    }
+
+    #[test]
+    fn timeout_backend() {
+        #[derive(Debug)]
+        struct Slow;
+
+        impl ResolverBackend for Slow {
+            fn addresses(&self, name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+                if name == "slow" {
+                    std::thread::sleep(Duration::from_secs(1));
+                }
+                Ok(vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))])
+            }
+
+            fn name_of(&self, _address: IpAddr) -> Result<String, HostnameError> {
+                Ok("foo".to_string())
+            }
+        }
+
+        let backend = TimeoutBackend::new(Slow, Duration::from_millis(10));
+        assert_eq!(backend.addresses("slow"), Err(HostnameError::timed_out()));
+        let backend = TimeoutBackend::new(Slow, Duration::from_secs(30));
+        assert_eq!(backend.canonical_name("fast").unwrap(), "fast");
+        assert_eq!(
+            backend.name_of(IpAddr::V4(Ipv4Addr::LOCALHOST)).unwrap(),
+            "foo"
+        );
+    }
+
+    #[test]
+    fn default_canonical_name() {
+        #[derive(Debug)]
+        struct Backend;
+
+        impl ResolverBackend for Backend {
+            fn addresses(&self, name: &str) -> Result<Vec<IpAddr>, crate::HostnameError> {
+                Ok(if name == "foo" {
+                    vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))]
+                } else {
+                    Vec::new()
+                })
+            }
+
+            fn name_of(&self, _address: IpAddr) -> Result<String, crate::HostnameError> {
+                Err(crate::HostnameError::Unsupported)
+            }
+        }
+
+        assert_eq!(Backend.canonical_name("foo"), Ok("foo".to_string()));
+        assert_eq!(
+            Backend.canonical_name("bar"),
+            Err(crate::HostnameError::NotFound { what: "address" })
+        );
+    }
+}
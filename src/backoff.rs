@@ -0,0 +1,196 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Retry transient failures with exponential backoff.
+
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// How often and how long to retry transient failures.
+///
+/// Wait [`initial_delay`](Backoff::initial_delay) after the first failed
+/// attempt, and multiply the delay by [`multiplier`](Backoff::multiplier)
+/// after every further attempt, up to [`max_delay`](Backoff::max_delay).
+/// Give up after [`attempts`](Backoff::attempts) attempts, or as soon as an
+/// attempt fails with an error which isn't
+/// [transient](HostnameError::is_transient).
+///
+/// By default make 5 attempts, and wait 100ms, 200ms, 400ms and 800ms in
+/// between.
+///
+/// ```
+/// use std::time::Duration;
+/// use gethostname::{Backoff, Resolver};
+///
+/// let backoff = Backoff::new()
+///     .attempts(10)
+///     .max_delay(Duration::from_secs(2));
+/// println!("FQDN: {:?}", Resolver::new().retry(backoff).fqdn());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Backoff {
+    attempts: u32,
+    initial_delay: Duration,
+    max_delay: Duration,
+    multiplier: u32,
+}
+
+impl Default for Backoff {
+    fn default() -> Self {
+        Backoff {
+            attempts: 5,
+            initial_delay: Duration::from_millis(100),
+            max_delay: Duration::from_secs(5),
+            multiplier: 2,
+        }
+    }
+}
+
+impl Backoff {
+    /// Create the default backoff.
+    pub fn new() -> Backoff {
+        Backoff::default()
+    }
+
+    /// Set the maximum number of attempts, including the first one.
+    ///
+    /// Zero attempts are treated like one.
+    pub fn attempts(mut self, attempts: u32) -> Backoff {
+        self.attempts = attempts;
+        self
+    }
+
+    /// Set the delay after the first failed attempt.
+    pub fn initial_delay(mut self, delay: Duration) -> Backoff {
+        self.initial_delay = delay;
+        self
+    }
+
+    /// Set the maximum delay between two attempts.
+    pub fn max_delay(mut self, delay: Duration) -> Backoff {
+        self.max_delay = delay;
+        self
+    }
+
+    /// Set the factor to multiply the delay with after each failed attempt.
+    pub fn multiplier(mut self, multiplier: u32) -> Backoff {
+        self.multiplier = multiplier;
+        self
+    }
+
+    /// Call `f` until it succeeds, fails with a permanent error, or we run out
+    /// of attempts.
+    pub(crate) fn retry<T>(
+        &self,
+        f: impl FnMut() -> Result<T, HostnameError>,
+    ) -> Result<T, HostnameError> {
+        self.retry_with(f, std::thread::sleep)
+    }
+
+    /// Like [`Backoff::retry()`], but `sleep` between attempts.
+    pub(crate) fn retry_with<T>(
+        &self,
+        mut f: impl FnMut() -> Result<T, HostnameError>,
+        mut sleep: impl FnMut(Duration),
+    ) -> Result<T, HostnameError> {
+        let mut delay = self.initial_delay.min(self.max_delay);
+        let mut attempt = 1;
+        loop {
+            match f() {
+                Err(error) if error.is_transient() && attempt < self.attempts => {
+                    warn!(
+                        "Attempt {} failed with {}, retrying in {:?}",
+                        attempt, error, delay
+                    );
+                    sleep(delay);
+                    delay = delay.saturating_mul(self.multiplier).min(self.max_delay);
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Backoff;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    /// Run `backoff` over `results`, and return the result and the delays.
+    fn run(
+        backoff: Backoff,
+        results: Vec<Result<u32, HostnameError>>,
+    ) -> (Result<u32, HostnameError>, Vec<Duration>) {
+        let mut results = results.into_iter();
+        let mut delays = Vec::new();
+        let result = backoff.retry_with(|| results.next().unwrap(), |delay| delays.push(delay));
+        (result, delays)
+    }
+
+    fn ms(millis: u64) -> Duration {
+        Duration::from_millis(millis)
+    }
+
+    #[test]
+    fn retries_transient_errors_with_exponential_delays() {
+        let results = vec![
+            Err(HostnameError::timed_out()),
+            Err(HostnameError::timed_out()),
+            Err(HostnameError::timed_out()),
+            Ok(42),
+        ];
+        assert_eq!(
+            run(Backoff::new(), results),
+            (Ok(42), vec![ms(100), ms(200), ms(400)])
+        );
+    }
+
+    #[test]
+    fn gives_up_after_attempts() {
+        let results = vec![Err(HostnameError::timed_out()); 3];
+        assert_eq!(
+            run(Backoff::new().attempts(3), results),
+            (Err(HostnameError::timed_out()), vec![ms(100), ms(200)])
+        );
+        let results = vec![Err(HostnameError::timed_out())];
+        assert_eq!(
+            run(Backoff::new().attempts(0), results),
+            (Err(HostnameError::timed_out()), Vec::new())
+        );
+    }
+
+    #[test]
+    fn caps_delay() {
+        let backoff = Backoff::new()
+            .initial_delay(ms(300))
+            .max_delay(ms(1000))
+            .multiplier(3)
+            .attempts(4);
+        let results = vec![Err(HostnameError::timed_out()); 4];
+        assert_eq!(run(backoff, results).1, vec![ms(300), ms(900), ms(1000)]);
+    }
+
+    #[test]
+    fn does_not_retry_permanent_errors() {
+        let results = vec![Err(HostnameError::Unsupported), Ok(42)];
+        assert_eq!(
+            run(Backoff::new(), results),
+            (Err(HostnameError::Unsupported), Vec::new())
+        );
+    }
+}
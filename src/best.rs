@@ -0,0 +1,323 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Pick the best name of the current machine among all its names.
+
+use std::fmt;
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// Where a [`HostnameCandidate`] came from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum CandidateSource {
+    /// The hostname of the kernel, from [`try_gethostname()`](crate::try_gethostname).
+    Kernel,
+    /// The fully qualified domain name, from [`getfqdn()`](crate::getfqdn).
+    Fqdn,
+    /// The hostname in the cloud metadata, from cloud-init or, with the `cloud`
+    /// feature, the metadata service of the cloud provider.
+    Cloud,
+    /// The multicast DNS name, with the `mdns` feature.
+    Mdns,
+    /// The pretty name the user gave the machine.
+    PrettyName,
+}
+
+impl fmt::Display for CandidateSource {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            CandidateSource::Kernel => "kernel",
+            CandidateSource::Fqdn => "fqdn",
+            CandidateSource::Cloud => "cloud",
+            CandidateSource::Mdns => "mdns",
+            CandidateSource::PrettyName => "pretty name",
+        })
+    }
+}
+
+/// A name of the current machine, with its source and score.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostnameCandidate {
+    name: String,
+    source: CandidateSource,
+    score: u8,
+}
+
+impl HostnameCandidate {
+    /// The name.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Where the name came from.
+    pub fn source(&self) -> CandidateSource {
+        self.source
+    }
+
+    /// The score of the name; higher is better.
+    ///
+    /// A name which resolves to an address always beats a name which
+    /// doesn't; among these a dotted name beats a single label, a name other
+    /// than `localhost` beats `localhost`, and any name beats an empty name.
+    pub fn score(&self) -> u8 {
+        self.score
+    }
+}
+
+/// The best name of the current machine, among all candidates.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BestHostname {
+    // Never empty, best first
+    ranked: Vec<HostnameCandidate>,
+}
+
+impl BestHostname {
+    /// The best candidate.
+    pub fn winner(&self) -> &HostnameCandidate {
+        &self.ranked[0]
+    }
+
+    /// All candidates, best first.
+    ///
+    /// Candidates with equal score keep the order of [`CandidateSource`].
+    pub fn ranked(&self) -> &[HostnameCandidate] {
+        &self.ranked
+    }
+}
+
+const RESOLVABLE: u8 = 8;
+const DOTTED: u8 = 4;
+const NOT_LOCALHOST: u8 = 2;
+const NOT_EMPTY: u8 = 1;
+
+/// Find the best name of the current machine.
+///
+/// Gather the kernel hostname, the FQDN, the hostname in the cloud metadata,
+/// the mDNS name and the pretty name of the current machine, ignore those
+/// which are not available, and rank them by [score](HostnameCandidate::score).
+/// Each candidate which doesn't contain whitespace is resolved, so this may
+/// block for a while if the resolver is slow; use [`best_hostname_timeout()`]
+/// to bound each lookup.  With the `cloud` feature this also asks the metadata
+/// services of EC2, GCE and Azure in turn, which takes a few seconds outside
+/// of these clouds.
+///
+/// Fail with [`HostnameError::NotFound`] if no candidate is available at all.
+///
+/// ```
+/// if let Ok(best) = gethostname::best_hostname() {
+///     for candidate in best.ranked() {
+///         println!("{} ({}): {}", candidate.name(), candidate.source(), candidate.score());
+///     }
+/// }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn best_hostname() -> Result<BestHostname, HostnameError> {
+    rank(candidates(None), |name| resolves(name, None))
+}
+
+/// Find the best name of the current machine, giving up on each lookup after
+/// `timeout`.
+///
+/// Like [`best_hostname()`], but give up resolving the FQDN or a candidate
+/// after `timeout`, and treat a candidate which doesn't resolve in time as
+/// not resolvable.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn best_hostname_timeout(timeout: Duration) -> Result<BestHostname, HostnameError> {
+    rank(candidates(Some(timeout)), |name| {
+        resolves(name, Some(timeout))
+    })
+}
+
+/// Gather all available names of the current machine, in the order of
+/// [`CandidateSource`].
+fn candidates(timeout: Option<Duration>) -> Vec<(String, CandidateSource)> {
+    let mut candidates = Vec::new();
+    let mut push = |name: Option<String>, source| {
+        if let Some(name) = name {
+            candidates.push((name, source));
+        }
+    };
+    push(
+        crate::try_gethostname()
+            .ok()
+            .and_then(|name| name.into_string().ok()),
+        CandidateSource::Kernel,
+    );
+    push(
+        crate::fqdn::getfqdn_within(timeout)
+            .ok()
+            .and_then(|name| name.into_string().ok()),
+        CandidateSource::Fqdn,
+    );
+    push(cloud_hostname(), CandidateSource::Cloud);
+    #[cfg(feature = "mdns")]
+    push(crate::mdns::local_name().ok(), CandidateSource::Mdns);
+    push(crate::info::pretty_name(), CandidateSource::PrettyName);
+    candidates
+}
+
+fn cloud_hostname() -> Option<String> {
+    #[cfg(target_os = "linux")]
+    {
+        let cloud_init = crate::cloud_init_hostname().ok();
+        let name = cloud_init.and_then(|c| c.fqdn().or_else(|| c.hostname()).map(str::to_string));
+        if name.is_some() {
+            return name;
+        }
+    }
+    #[cfg(feature = "cloud")]
+    {
+        use crate::cloud::{azure::AzureMetadata, ec2::Ec2Metadata, gce::GceMetadata};
+        let name = Ec2Metadata::connect()
+            .and_then(|ec2| ec2.local_hostname())
+            .or_else(|_| GceMetadata::new().hostname())
+            .or_else(|_| AzureMetadata::new().fqdn())
+            .ok();
+        if name.is_some() {
+            return name;
+        }
+    }
+    None
+}
+
+/// Whether `name` resolves to at least one address within `timeout`.
+fn resolves(name: &str, timeout: Option<Duration>) -> bool {
+    let name = name.to_string();
+    !name.contains(char::is_whitespace)
+        && crate::resolver::within(timeout, move || crate::resolver::address_info(&name))
+            .is_ok_and(|addresses| !addresses.is_empty())
+}
+
+fn score(name: &str, resolves: &mut impl FnMut(&str) -> bool) -> u8 {
+    let relative = name.trim_end_matches('.');
+    let mut score = 0;
+    if !name.is_empty() {
+        score |= NOT_EMPTY;
+    }
+    if !crate::case::eq_folded(relative, "localhost") && !relative.is_empty() {
+        score |= NOT_LOCALHOST;
+    }
+    if relative.contains('.') {
+        score |= DOTTED;
+    }
+    if !relative.is_empty() && resolves(relative) {
+        score |= RESOLVABLE;
+    }
+    score
+}
+
+/// Score and rank `candidates`, best first.
+fn rank(
+    candidates: Vec<(String, CandidateSource)>,
+    mut resolves: impl FnMut(&str) -> bool,
+) -> Result<BestHostname, HostnameError> {
+    let mut ranked = candidates
+        .into_iter()
+        .map(|(name, source)| HostnameCandidate {
+            score: score(&name, &mut resolves),
+            name,
+            source,
+        })
+        .collect::<Vec<_>>();
+    if ranked.is_empty() {
+        return Err(HostnameError::NotFound {
+            what: "hostname candidate",
+        });
+    }
+    // The sort is stable, so equal scores keep the order of sources
+    ranked.sort_by_key(|candidate| std::cmp::Reverse(candidate.score));
+    Ok(BestHostname { ranked })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{rank, CandidateSource};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+
+    fn ranked_names(candidates: &[(&str, CandidateSource)]) -> Vec<(String, CandidateSource, u8)> {
+        let candidates = candidates
+            .iter()
+            .map(|(name, source)| (name.to_string(), *source))
+            .collect();
+        let best = rank(candidates, |name| name == "foo.example.com").unwrap();
+        best.ranked()
+            .iter()
+            .map(|c| (c.name().to_string(), c.source(), c.score()))
+            .collect()
+    }
+
+    #[test]
+    fn resolvable_beats_dotted_beats_non_localhost_beats_empty() {
+        let ranked = ranked_names(&[
+            ("", CandidateSource::Kernel),
+            ("localhost", CandidateSource::Kernel),
+            ("bar", CandidateSource::Mdns),
+            ("bar.example.com", CandidateSource::Cloud),
+            ("foo.example.com", CandidateSource::Fqdn),
+        ]);
+        assert_eq!(
+            ranked,
+            vec![
+                ("foo.example.com".to_string(), CandidateSource::Fqdn, 15),
+                ("bar.example.com".to_string(), CandidateSource::Cloud, 7),
+                ("bar".to_string(), CandidateSource::Mdns, 3),
+                ("localhost".to_string(), CandidateSource::Kernel, 1),
+                ("".to_string(), CandidateSource::Kernel, 0),
+            ]
+        );
+    }
+
+    #[test]
+    fn equal_scores_keep_source_order() {
+        let ranked = ranked_names(&[
+            ("foo", CandidateSource::Kernel),
+            ("Foo's Laptop", CandidateSource::PrettyName),
+            ("foo.local", CandidateSource::Mdns),
+            ("foo.example.com.", CandidateSource::Fqdn),
+        ]);
+        let sources = ranked.iter().map(|c| c.1).collect::<Vec<_>>();
+        assert_eq!(
+            sources,
+            vec![
+                CandidateSource::Fqdn,
+                CandidateSource::Mdns,
+                CandidateSource::Kernel,
+                CandidateSource::PrettyName
+            ]
+        );
+    }
+
+    #[test]
+    fn no_candidates() {
+        assert_eq!(
+            rank(Vec::new(), |_| true),
+            Err(HostnameError::NotFound {
+                what: "hostname candidate"
+            })
+        );
+    }
+
+    #[test]
+    fn best_hostname_of_current_machine() {
+        let best = super::best_hostname().unwrap();
+        assert_eq!(best.winner(), &best.ranked()[0]);
+        assert!(best
+            .ranked()
+            .iter()
+            .any(|c| c.source() == CandidateSource::Kernel));
+    }
+}
@@ -0,0 +1,129 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A process-wide cache of the hostname.
+//!
+//! Integrations which need the hostname over and over again, e.g. for every
+//! log record, read it from this cache instead of asking the operating system
+//! every time.
+
+use std::ffi::{OsStr, OsString};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+
+use crate::error::HostnameError;
+use crate::watch::{CloseHandle, HostnameChanges};
+
+static HOSTNAME: RwLock<Option<Arc<OsStr>>> = RwLock::new(None);
+
+/// Get the hostname from a process-wide cache.
+///
+/// Fill the cache on first use.  If getting the hostname fails leave the
+/// cache empty and try again next time.
+///
+/// By default the cache keeps the hostname forever; use
+/// [`spawn_cache_refresher()`] to keep it up to date.
+///
+/// ```
+/// let hostname = gethostname::cached_hostname().unwrap();
+/// assert_eq!(&*hostname, gethostname::gethostname());
+/// ```
+pub fn cached_hostname() -> Result<Arc<OsStr>, HostnameError> {
+    let cached = HOSTNAME
+        .read()
+        .unwrap_or_else(|error| error.into_inner())
+        .clone();
+    match cached {
+        Some(hostname) => Ok(hostname),
+        None => {
+            let hostname: Arc<OsStr> = Arc::from(crate::try_gethostname()?.as_os_str());
+            let mut cache = HOSTNAME.write().unwrap_or_else(|error| error.into_inner());
+            Ok(cache.get_or_insert(hostname).clone())
+        }
+    }
+}
+
+/// Spawn a background thread which keeps the cache up to date.
+///
+/// Fill the cache with the current hostname, and then check the hostname
+/// every `poll_interval` in a background thread, with [`HostnameChanges`].
+/// Update the cache whenever the hostname changes, so that
+/// [`cached_hostname()`] returns a recent hostname without ever waiting for
+/// the operating system.  On Linux lower the priority of the thread to the
+/// lowest priority.
+///
+/// Return a handle to stop the thread.  Fail if getting the hostname fails,
+/// or if the thread can't be spawned.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// let refresher = gethostname::spawn_cache_refresher(Duration::from_secs(30)).unwrap();
+/// // Later
+/// refresher.close();
+/// ```
+pub fn spawn_cache_refresher(poll_interval: Duration) -> Result<CloseHandle, HostnameError> {
+    store(crate::try_gethostname()?);
+    let changes = HostnameChanges::new().poll_interval(poll_interval);
+    let handle = changes.close_handle();
+    std::thread::Builder::new()
+        .name("gethostname-cache".to_string())
+        .spawn(move || {
+            lower_thread_priority();
+            for hostname in changes {
+                store(hostname);
+            }
+        })
+        .map_err(|error| HostnameError::from_io(&error))?;
+    Ok(handle)
+}
+
+fn store(hostname: OsString) {
+    let hostname: Arc<OsStr> = Arc::from(hostname.as_os_str());
+    *HOSTNAME.write().unwrap_or_else(|error| error.into_inner()) = Some(hostname);
+}
+
+#[cfg(target_os = "linux")]
+fn lower_thread_priority() {
+    // On Linux the priority is a property of the thread, not the process
+    if unsafe { libc::setpriority(libc::PRIO_PROCESS, 0, 19) } != 0 {
+        warn!(
+            "Failed to lower priority of cache refresher: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(not(target_os = "linux"))]
+fn lower_thread_priority() {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn cached_hostname() {
+        assert_eq!(&*super::cached_hostname().unwrap(), crate::gethostname());
+        assert_eq!(&*super::cached_hostname().unwrap(), crate::gethostname());
+    }
+
+    #[test]
+    fn refresh_cache() {
+        let refresher = super::spawn_cache_refresher(Duration::from_millis(1)).unwrap();
+        std::thread::sleep(Duration::from_millis(10));
+        assert_eq!(&*super::cached_hostname().unwrap(), crate::gethostname());
+        refresher.close();
+    }
+}
@@ -0,0 +1,209 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Cancel lookups which run on helper threads.
+
+use std::io;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{Arc, Condvar, Mutex, MutexGuard};
+use std::time::{Duration, Instant};
+
+/// A token to cancel a lookup from another thread.
+///
+/// Clones share their state: cancelling one clone cancels all of them, and
+/// every lookup which got any clone.  A token stays cancelled once cancelled;
+/// use a new token for every lookup you may want to cancel.
+///
+/// ```
+/// use gethostname::{CancellationToken, HostnameError, Resolver};
+///
+/// let token = CancellationToken::new();
+/// token.cancel();
+/// assert_eq!(
+///     Resolver::new().fqdn_cancellable(&token),
+///     Err(HostnameError::Cancelled)
+/// );
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct CancellationToken(Arc<(Mutex<bool>, Condvar)>);
+
+/// How a helper thread ended for the thread which waited for it.
+#[derive(Debug, PartialEq, Eq)]
+pub(crate) enum Outcome<T> {
+    /// The helper thread returned a value.
+    Done(T),
+    /// The helper thread panicked.
+    Panicked,
+    /// The helper thread didn't return in time.
+    TimedOut,
+    /// The token got cancelled before the helper thread returned.
+    Cancelled,
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> MutexGuard<'_, T> {
+    mutex.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+impl CancellationToken {
+    /// Create a new token.
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    /// Cancel all lookups which got this token.
+    ///
+    /// Wake up all threads which wait for a lookup with this token, and make
+    /// these lookups fail with
+    /// [`HostnameError::Cancelled`](crate::HostnameError::Cancelled).
+    pub fn cancel(&self) {
+        let (cancelled, condvar) = &*self.0;
+        *lock(cancelled) = true;
+        condvar.notify_all();
+    }
+
+    /// Whether this token got cancelled.
+    pub fn is_cancelled(&self) -> bool {
+        *lock(&self.0 .0)
+    }
+
+    /// Sleep for `duration`, and return whether this token got cancelled.
+    ///
+    /// Return early if the token gets cancelled while sleeping.
+    pub(crate) fn sleep(&self, duration: Duration) -> bool {
+        let (cancelled, condvar) = &*self.0;
+        let (cancelled, _) = condvar
+            .wait_timeout_while(lock(cancelled), duration, |cancelled| !*cancelled)
+            .unwrap_or_else(|error| error.into_inner());
+        *cancelled
+    }
+
+    /// Run `f` on a helper thread, and wait until it returns, `timeout`
+    /// elapses, or this token gets cancelled.
+    ///
+    /// The helper thread keeps running until `f` returns; nobody waits for its
+    /// result anymore if it timed out or got cancelled.
+    pub(crate) fn run<T, F>(&self, timeout: Option<Duration>, f: F) -> io::Result<Outcome<T>>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let result = Arc::new(Mutex::new(None));
+        let slot = result.clone();
+        let token = self.clone();
+        std::thread::Builder::new()
+            .name("gethostname-resolver".to_string())
+            .spawn(move || {
+                let value = panic::catch_unwind(AssertUnwindSafe(f));
+                *lock(&slot) = Some(value);
+                // Notify under the lock of the token, so that we can't notify
+                // between the check and the wait of the waiting thread.
+                let (cancelled, condvar) = &*token.0;
+                let _cancelled = lock(cancelled);
+                condvar.notify_all();
+            })?;
+
+        let deadline = timeout.and_then(|timeout| Instant::now().checked_add(timeout));
+        let (cancelled, condvar) = &*self.0;
+        let mut cancelled = lock(cancelled);
+        loop {
+            if *cancelled {
+                return Ok(Outcome::Cancelled);
+            }
+            if let Some(value) = lock(&result).take() {
+                return Ok(value.map_or(Outcome::Panicked, Outcome::Done));
+            }
+            cancelled = match deadline {
+                Some(deadline) => {
+                    let now = Instant::now();
+                    if deadline <= now {
+                        return Ok(Outcome::TimedOut);
+                    }
+                    condvar
+                        .wait_timeout(cancelled, deadline - now)
+                        .unwrap_or_else(|error| error.into_inner())
+                        .0
+                }
+                None => condvar
+                    .wait(cancelled)
+                    .unwrap_or_else(|error| error.into_inner()),
+            };
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{CancellationToken, Outcome};
+    use pretty_assertions::assert_eq;
+    use std::time::{Duration, Instant};
+
+    fn sleep_and_return(value: u32) -> impl FnOnce() -> u32 {
+        move || {
+            std::thread::sleep(Duration::from_secs(1));
+            value
+        }
+    }
+
+    #[test]
+    fn run_returns_value() {
+        let token = CancellationToken::new();
+        assert_eq!(token.run(None, || 42).unwrap(), Outcome::Done(42));
+        let timeout = Some(Duration::from_secs(10));
+        assert_eq!(token.run(timeout, || 42).unwrap(), Outcome::Done(42));
+    }
+
+    #[test]
+    fn run_times_out() {
+        let token = CancellationToken::new();
+        let timeout = Some(Duration::from_millis(10));
+        assert_eq!(
+            token.run(timeout, sleep_and_return(42)).unwrap(),
+            Outcome::TimedOut
+        );
+    }
+
+    #[test]
+    fn run_reports_panics() {
+        let token = CancellationToken::new();
+        let outcome = token.run(None, || -> u32 { panic!("lookup failed") });
+        assert_eq!(outcome.unwrap(), Outcome::Panicked);
+    }
+
+    #[test]
+    fn cancel_wakes_up_waiting_thread() {
+        let token = CancellationToken::new();
+        let canceller = token.clone();
+        let start = Instant::now();
+        std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            canceller.cancel();
+        });
+        assert_eq!(
+            token.run(None, sleep_and_return(42)).unwrap(),
+            Outcome::Cancelled
+        );
+        assert!(start.elapsed() < Duration::from_secs(1));
+        assert!(token.is_cancelled());
+    }
+
+    #[test]
+    fn sleep_until_cancelled() {
+        let token = CancellationToken::new();
+        assert!(!token.sleep(Duration::from_millis(1)));
+        token.cancel();
+        let start = Instant::now();
+        assert!(token.sleep(Duration::from_secs(10)));
+        assert!(start.elapsed() < Duration::from_secs(1));
+    }
+}
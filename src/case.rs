@@ -0,0 +1,133 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Fold the case of hostnames independently of the locale.
+
+/// Fold the case of `name` for case-insensitive comparisons.
+///
+/// Apply unicode [simple case folding], which maps every character to a
+/// single character and doesn't depend on the locale: ASCII letters map to
+/// lowercase, and so do most other letters, but e.g. `İ`, the capital I with
+/// dot of Turkish, remains as it is instead of becoming `i` followed by a
+/// combining dot, and `ı`, the dotless i, doesn't become `i`.  Two names
+/// which only differ in case have the same folded form.
+///
+/// All case-insensitive operations of this crate fold case with this
+/// function.
+///
+/// ```
+/// use gethostname::fold_case;
+///
+/// assert_eq!(fold_case("Web-01.EXAMPLE.com"), "web-01.example.com");
+/// assert_eq!(fold_case("İSTANBUL.example"), "İstanbul.example");
+/// assert_eq!(fold_case("ΣΊΣΥΦΟΣ"), fold_case("σίσυφος"));
+/// ```
+///
+/// [simple case folding]: https://www.unicode.org/Public/UCD/latest/ucd/CaseFolding.txt
+pub fn fold_case(name: &str) -> String {
+    name.chars().map(fold_char).collect()
+}
+
+/// Whether `a` and `b` are equal after [folding their case](fold_case).
+pub(crate) fn eq_folded(a: &str, b: &str) -> bool {
+    a.chars().map(fold_char).eq(b.chars().map(fold_char))
+}
+
+/// Fold the case of `c`.
+fn fold_char(c: char) -> char {
+    if c.is_ascii() {
+        return c.to_ascii_lowercase();
+    }
+    // Characters whose simple case folding differs from their lowercase form
+    match c {
+        '\u{B5}' => '\u{3BC}',
+        '\u{17F}' => 's',
+        '\u{345}' | '\u{1FBE}' => '\u{3B9}',
+        '\u{3C2}' => '\u{3C3}',
+        '\u{3D0}' => '\u{3B2}',
+        '\u{3D1}' => '\u{3B8}',
+        '\u{3D5}' => '\u{3C6}',
+        '\u{3D6}' => '\u{3C0}',
+        '\u{3F0}' => '\u{3BA}',
+        '\u{3F1}' => '\u{3C1}',
+        '\u{3F5}' => '\u{3B5}',
+        '\u{1C80}' => '\u{432}',
+        '\u{1C81}' => '\u{434}',
+        '\u{1C82}' => '\u{43E}',
+        '\u{1C83}' => '\u{441}',
+        '\u{1C84}' | '\u{1C85}' => '\u{442}',
+        '\u{1C86}' => '\u{44A}',
+        '\u{1C87}' => '\u{463}',
+        '\u{1C88}' => '\u{A64B}',
+        '\u{1E9B}' => '\u{1E61}',
+        // Cherokee folds to uppercase, for compatibility with older versions
+        // of unicode which had uppercase Cherokee letters only
+        '\u{13A0}'..='\u{13F5}' => c,
+        '\u{13F8}'..='\u{13FD}' => char::from_u32(c as u32 - 8).unwrap_or(c),
+        '\u{AB70}'..='\u{ABBF}' => char::from_u32(c as u32 - 0xAB70 + 0x13A0).unwrap_or(c),
+        _ => {
+            // Simple case folding never maps to more than one character; keep
+            // characters like İ whose lowercase form has more
+            let mut lowercase = c.to_lowercase();
+            match (lowercase.next(), lowercase.next()) {
+                (Some(lowercase), None) => lowercase,
+                _ => c,
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{eq_folded, fold_case};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn ascii() {
+        assert_eq!(fold_case("Foo-01.EXAMPLE.com"), "foo-01.example.com");
+    }
+
+    #[test]
+    fn turkish_i() {
+        assert_eq!(fold_case("İ"), "İ");
+        assert_eq!(fold_case("ı"), "ı");
+        assert_eq!(fold_case("I"), "i");
+        assert_ne!(fold_case("DİZİN"), fold_case("dizin"));
+    }
+
+    #[test]
+    fn folding_differs_from_lowercase() {
+        assert_eq!(fold_case("ς"), "σ");
+        assert_eq!(fold_case("ſ"), "s");
+        assert_eq!(fold_case("µ"), "μ");
+        assert_eq!(fold_case("\u{212A}"), "k");
+        assert_eq!(fold_case("ẞ"), "ß");
+    }
+
+    #[test]
+    fn cherokee_folds_to_uppercase() {
+        assert_eq!(
+            fold_case("\u{AB70}\u{13A0}\u{13F8}"),
+            "\u{13A0}\u{13A0}\u{13F0}"
+        );
+    }
+
+    #[test]
+    fn equal_when_folded() {
+        assert!(eq_folded("Web-01.EXAMPLE.com", "web-01.example.COM"));
+        assert!(eq_folded("ΣΊΣΥΦΟΣ", "σίσυφος"));
+        assert!(!eq_folded("DİZİN", "dizin"));
+        assert!(!eq_folded("foo", "foo."));
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The chassis type of the current machine.
+
+use std::fmt;
+
+use crate::error::HostnameError;
+
+/// The chassis type of a machine.
+///
+/// These are the chassis types of [systemd-hostnamed].
+///
+/// [systemd-hostnamed]: https://www.freedesktop.org/software/systemd/man/machine-info.html#CHASSIS=
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum Chassis {
+    /// A desktop computer, including all-in-one and mini PCs.
+    Desktop,
+    /// A laptop or notebook.
+    Laptop,
+    /// A laptop which converts into a tablet.
+    Convertible,
+    /// A server, including rack mounted and blade systems.
+    Server,
+    /// A tablet, including detachables.
+    Tablet,
+    /// A phone.
+    Handset,
+    /// A smart watch.
+    Watch,
+    /// An embedded device, e.g. an IoT gateway.
+    Embedded,
+    /// A virtual machine.
+    Vm,
+    /// A container.
+    Container,
+}
+
+impl Chassis {
+    /// The name of this chassis type, as used by systemd-hostnamed.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Chassis::Desktop => "desktop",
+            Chassis::Laptop => "laptop",
+            Chassis::Convertible => "convertible",
+            Chassis::Server => "server",
+            Chassis::Tablet => "tablet",
+            Chassis::Handset => "handset",
+            Chassis::Watch => "watch",
+            Chassis::Embedded => "embedded",
+            Chassis::Vm => "vm",
+            Chassis::Container => "container",
+        }
+    }
+
+    /// Get the chassis type with the systemd-hostnamed `name`.
+    #[cfg(any(target_os = "linux", target_os = "android", test))]
+    fn from_name(name: &str) -> Option<Chassis> {
+        let chassis = match name.trim() {
+            "desktop" => Chassis::Desktop,
+            "laptop" => Chassis::Laptop,
+            "convertible" => Chassis::Convertible,
+            "server" => Chassis::Server,
+            "tablet" => Chassis::Tablet,
+            "handset" => Chassis::Handset,
+            "watch" => Chassis::Watch,
+            "embedded" => Chassis::Embedded,
+            "vm" => Chassis::Vm,
+            "container" => Chassis::Container,
+            _ => return None,
+        };
+        Some(chassis)
+    }
+
+    /// Map an SMBIOS chassis type to a chassis type.
+    ///
+    /// See section 7.4.1 of the [SMBIOS specification][smbios]; the mapping
+    /// follows systemd-hostnamed.
+    ///
+    /// [smbios]: https://www.dmtf.org/standards/smbios
+    #[cfg(any(target_os = "linux", target_os = "android", windows, test))]
+    fn from_smbios(chassis_type: u8) -> Option<Chassis> {
+        // The high bit indicates a chassis lock
+        let chassis = match chassis_type & 0x7f {
+            0x03 | 0x04 | 0x06 | 0x07 | 0x0d | 0x18 | 0x23 | 0x24 => Chassis::Desktop,
+            0x08 | 0x09 | 0x0a | 0x0e => Chassis::Laptop,
+            0x0b => Chassis::Handset,
+            0x11 | 0x17 | 0x1c | 0x1d => Chassis::Server,
+            0x1e | 0x20 => Chassis::Tablet,
+            0x1f => Chassis::Convertible,
+            0x21 | 0x22 => Chassis::Embedded,
+            _ => return None,
+        };
+        Some(chassis)
+    }
+
+    /// Derive the chassis type from an Apple hardware `model`, e.g.
+    /// `MacBookPro18,3`.
+    #[cfg(any(target_vendor = "apple", test))]
+    fn from_apple_model(model: &str) -> Option<Chassis> {
+        let chassis = if model.starts_with("MacBook") {
+            Chassis::Laptop
+        } else if model.starts_with("VirtualMac") {
+            Chassis::Vm
+        } else if model.starts_with("iPhone") || model.starts_with("iPod") {
+            Chassis::Handset
+        } else if model.starts_with("iPad") {
+            Chassis::Tablet
+        } else if model.starts_with("Watch") {
+            Chassis::Watch
+        } else if model.starts_with("AppleTV") {
+            // Like systemd, treat set-top boxes as embedded devices
+            Chassis::Embedded
+        } else if model.starts_with("Xserve") {
+            Chassis::Server
+        } else if model.starts_with("Mac") || model.starts_with("iMac") {
+            Chassis::Desktop
+        } else {
+            return None;
+        };
+        Some(chassis)
+    }
+}
+
+impl fmt::Display for Chassis {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// Get the chassis type of the current machine.
+///
+/// * On Linux ask systemd-hostnamed for its `Chassis` property with the `dbus`
+///   feature.  Otherwise, or if systemd-hostnamed doesn't know the chassis,
+///   detect containers and virtual machines, and then use the SMBIOS chassis
+///   type from `/sys/class/dmi/id/chassis_type` or the chassis type from the
+///   device tree.
+/// * On Apple systems derive the chassis type from the hardware model, e.g.
+///   [`Chassis::Laptop`] for `MacBookPro18,3`, or [`Chassis::Handset`] for
+///   `iPhone15,2`.
+/// * On Windows use the SMBIOS chassis type from the raw SMBIOS tables.  Most
+///   hypervisors report a desktop chassis to Windows guests.
+///
+/// Fail with [`HostnameError::NotFound`] if the chassis type is unknown, and
+/// with [`HostnameError::Unsupported`] on other systems.
+///
+/// ```
+/// match gethostname::get_chassis() {
+///     Ok(chassis) => println!("Chassis: {}", chassis),
+///     Err(error) => eprintln!("Unknown chassis: {}", error),
+/// }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn get_chassis() -> Result<Chassis, HostnameError> {
+    chassis_impl()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn chassis_impl() -> Result<Chassis, HostnameError> {
+    #[cfg(all(target_os = "linux", feature = "dbus"))]
+    match crate::hostnamed::property("Chassis") {
+        Ok(name) => {
+            if let Some(chassis) = Chassis::from_name(&name) {
+                return Ok(chassis);
+            }
+        }
+        Err(error) => warn!("Failed to get chassis from systemd-hostnamed: {}", error),
+    }
+    detect_chassis_in(std::path::Path::new("/")).ok_or(HostnameError::NotFound { what: "chassis" })
+}
+
+/// Detect the chassis type from the files under `root`.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn detect_chassis_in(root: &std::path::Path) -> Option<Chassis> {
+    use std::fs;
+
+    let container_markers = ["run/systemd/container", ".dockerenv", "run/.containerenv"];
+    if container_markers.iter().any(|m| root.join(m).exists()) {
+        return Some(Chassis::Container);
+    }
+    // The kernel sets the hypervisor CPU flag in x86 virtual machines
+    let is_vm = fs::read_to_string(root.join("proc/cpuinfo")).is_ok_and(|cpuinfo| {
+        cpuinfo
+            .lines()
+            .filter(|line| line.starts_with("flags"))
+            .any(|line| line.split_whitespace().any(|flag| flag == "hypervisor"))
+    });
+    if is_vm {
+        return Some(Chassis::Vm);
+    }
+    let smbios = fs::read_to_string(root.join("sys/class/dmi/id/chassis_type"))
+        .ok()
+        .and_then(|chassis_type| chassis_type.trim().parse().ok())
+        .and_then(Chassis::from_smbios);
+    smbios.or_else(|| {
+        let chassis_type = fs::read(root.join("sys/firmware/devicetree/base/chassis-type")).ok()?;
+        // The device tree terminates strings with NUL
+        Chassis::from_name(String::from_utf8_lossy(&chassis_type).trim_end_matches('\0'))
+    })
+}
+
+#[cfg(target_vendor = "apple")]
+fn chassis_impl() -> Result<Chassis, HostnameError> {
+    Chassis::from_apple_model(&crate::get_hardware_model()?)
+        .ok_or(HostnameError::NotFound { what: "chassis" })
+}
+
+#[cfg(windows)]
+fn chassis_impl() -> Result<Chassis, HostnameError> {
+    crate::hardware::smbios_chassis_type()?
+        .and_then(Chassis::from_smbios)
+        .ok_or(HostnameError::NotFound { what: "chassis" })
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    windows
+)))]
+fn chassis_impl() -> Result<Chassis, HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{detect_chassis_in, Chassis};
+    use pretty_assertions::assert_eq;
+    use std::fs;
+
+    #[test]
+    fn names_roundtrip() {
+        for chassis in &[Chassis::Desktop, Chassis::Vm, Chassis::Container] {
+            assert_eq!(Chassis::from_name(chassis.as_str()), Some(*chassis));
+        }
+        assert_eq!(Chassis::from_name("spaceship"), None);
+    }
+
+    #[test]
+    fn smbios_chassis_types() {
+        assert_eq!(Chassis::from_smbios(0x03), Some(Chassis::Desktop));
+        assert_eq!(Chassis::from_smbios(0x0a), Some(Chassis::Laptop));
+        // Chassis lock bit
+        assert_eq!(Chassis::from_smbios(0x97), Some(Chassis::Server));
+        assert_eq!(Chassis::from_smbios(0x1f), Some(Chassis::Convertible));
+        assert_eq!(Chassis::from_smbios(0x02), None);
+    }
+
+    #[test]
+    fn apple_models() {
+        assert_eq!(
+            Chassis::from_apple_model("MacBookPro18,3"),
+            Some(Chassis::Laptop)
+        );
+        assert_eq!(Chassis::from_apple_model("Mac14,3"), Some(Chassis::Desktop));
+        assert_eq!(
+            Chassis::from_apple_model("iMac21,1"),
+            Some(Chassis::Desktop)
+        );
+        assert_eq!(
+            Chassis::from_apple_model("VirtualMac2,1"),
+            Some(Chassis::Vm)
+        );
+        assert_eq!(
+            Chassis::from_apple_model("iPhone15,2"),
+            Some(Chassis::Handset)
+        );
+        assert_eq!(
+            Chassis::from_apple_model("AppleTV14,1"),
+            Some(Chassis::Embedded)
+        );
+        assert_eq!(Chassis::from_apple_model("Foo1,1"), None);
+    }
+
+    #[test]
+    fn detect_chassis() {
+        let root = std::env::temp_dir().join(format!("gethostname-chassis-{}", std::process::id()));
+        let dmi = root.join("sys/class/dmi/id");
+        fs::create_dir_all(&dmi).unwrap();
+        assert_eq!(detect_chassis_in(&root), None);
+        fs::write(dmi.join("chassis_type"), "10\n").unwrap();
+        assert_eq!(detect_chassis_in(&root), Some(Chassis::Laptop));
+        fs::create_dir_all(root.join("proc")).unwrap();
+        fs::write(
+            root.join("proc/cpuinfo"),
+            "processor\t: 0\nflags\t\t: fpu vme hypervisor lahf_lm\n",
+        )
+        .unwrap();
+        assert_eq!(detect_chassis_in(&root), Some(Chassis::Vm));
+        fs::write(root.join(".dockerenv"), "").unwrap();
+        assert_eq!(detect_chassis_in(&root), Some(Chassis::Container));
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn devicetree_chassis() {
+        let root =
+            std::env::temp_dir().join(format!("gethostname-chassis-dt-{}", std::process::id()));
+        let base = root.join("sys/firmware/devicetree/base");
+        fs::create_dir_all(&base).unwrap();
+        fs::write(base.join("chassis-type"), "handset\0").unwrap();
+        assert_eq!(detect_chassis_in(&root), Some(Chassis::Handset));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Integration with the [clap] crate.
+//!
+//! [`Hostname`] implements clap's `ValueParserFactory`, so `clap` derive
+//! validates hostname arguments without further ado:
+//!
+//! ```
+//! use clap::{Arg, Command};
+//! use gethostname::Hostname;
+//!
+//! let command = Command::new("ping").arg(
+//!     Arg::new("host").value_parser(clap::value_parser!(Hostname)),
+//! );
+//! let matches = command.try_get_matches_from(["ping", "foo.example"]).unwrap();
+//! assert_eq!(matches.get_one::<Hostname>("host").unwrap().as_str(), "foo.example");
+//! ```
+//!
+//! Use [`HostnameValueParser::with_options()`] to parse hostnames with
+//! different [`ParseOptions`].  [`Fqdn`] works the same way, with
+//! [`FqdnValueParser`].
+//!
+//! [clap]: https://docs.rs/clap
+
+use std::ffi::OsStr;
+
+use ::clap::builder::{TypedValueParser, ValueParserFactory};
+use ::clap::error::ErrorKind;
+use ::clap::{Arg, Command, Error};
+
+use crate::fqdn::Fqdn;
+use crate::hostname::Hostname;
+use crate::options::ParseOptions;
+
+/// Parse `value` as hostname with `options`, for `arg` of `cmd`.
+fn parse_hostname(
+    cmd: &Command,
+    arg: Option<&Arg>,
+    value: &OsStr,
+    options: &ParseOptions,
+) -> Result<Hostname, Error> {
+    let value = value
+        .to_str()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidUtf8).with_cmd(cmd))?;
+    Hostname::parse_with(value, options).map_err(|error| {
+        let arg = arg.map_or_else(|| "...".to_string(), |arg| arg.to_string());
+        Error::raw(
+            ErrorKind::ValueValidation,
+            format!("invalid value '{}' for '{}': {}\n", value, arg, error),
+        )
+        .with_cmd(cmd)
+    })
+}
+
+/// A clap value parser for [`Hostname`].
+///
+/// Reject invalid hostnames with an error which tells why the hostname is
+/// invalid.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HostnameValueParser {
+    options: ParseOptions,
+}
+
+impl HostnameValueParser {
+    /// Create a value parser with default options.
+    pub fn new() -> HostnameValueParser {
+        HostnameValueParser::default()
+    }
+
+    /// Create a value parser which parses hostnames with `options`.
+    pub fn with_options(options: ParseOptions) -> HostnameValueParser {
+        HostnameValueParser { options }
+    }
+}
+
+impl TypedValueParser for HostnameValueParser {
+    type Value = Hostname;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        parse_hostname(cmd, arg, value, &self.options)
+    }
+}
+
+impl ValueParserFactory for Hostname {
+    type Parser = HostnameValueParser;
+
+    fn value_parser() -> Self::Parser {
+        HostnameValueParser::new()
+    }
+}
+
+/// A clap value parser for [`Fqdn`].
+///
+/// Accept fully qualified domain names with or without trailing dot, and
+/// reject invalid names like [`HostnameValueParser`].
+#[derive(Debug, Clone, Copy, Default)]
+pub struct FqdnValueParser {
+    options: ParseOptions,
+}
+
+impl FqdnValueParser {
+    /// Create a value parser with default options.
+    pub fn new() -> FqdnValueParser {
+        FqdnValueParser::default()
+    }
+
+    /// Create a value parser which parses names with `options`.
+    pub fn with_options(options: ParseOptions) -> FqdnValueParser {
+        FqdnValueParser { options }
+    }
+}
+
+impl TypedValueParser for FqdnValueParser {
+    type Value = Fqdn;
+
+    fn parse_ref(
+        &self,
+        cmd: &Command,
+        arg: Option<&Arg>,
+        value: &OsStr,
+    ) -> Result<Self::Value, Error> {
+        let relative = value
+            .to_str()
+            .and_then(|value| value.strip_suffix('.'))
+            .map_or(value, OsStr::new);
+        parse_hostname(cmd, arg, relative, &self.options).map(Fqdn::from)
+    }
+}
+
+impl ValueParserFactory for Fqdn {
+    type Parser = FqdnValueParser;
+
+    fn value_parser() -> Self::Parser {
+        FqdnValueParser::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{FqdnValueParser, HostnameValueParser};
+    use crate::{Fqdn, Hostname, ParseOptions, Strictness};
+    use ::clap::error::ErrorKind;
+    use ::clap::{Arg, Command};
+    use pretty_assertions::assert_eq;
+
+    fn command(parser: HostnameValueParser) -> Command {
+        Command::new("test").arg(Arg::new("host").long("host").value_parser(parser))
+    }
+
+    #[test]
+    fn parse_valid_hostname() {
+        let matches = command(HostnameValueParser::new())
+            .try_get_matches_from(["test", "--host", "foo.example"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<Hostname>("host").unwrap().as_str(),
+            "foo.example"
+        );
+    }
+
+    #[test]
+    fn reject_invalid_hostname() {
+        let error = command(HostnameValueParser::new())
+            .try_get_matches_from(["test", "--host", "foo_bar"])
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+        assert_eq!(
+            error.to_string(),
+            "error: invalid value 'foo_bar' for '--host <host>': invalid hostname at 3: invalid character '_'\n"
+        );
+    }
+
+    #[test]
+    fn parse_with_options() {
+        let options = ParseOptions::new().strictness(Strictness::Liberal);
+        let matches = command(HostnameValueParser::with_options(options))
+            .try_get_matches_from(["test", "--host", "foo_bar"])
+            .unwrap();
+        assert_eq!(
+            matches.get_one::<Hostname>("host").unwrap().as_str(),
+            "foo_bar"
+        );
+    }
+
+    #[test]
+    fn parse_fqdn() {
+        let command = Command::new("test").arg(
+            Arg::new("fqdn")
+                .long("fqdn")
+                .value_parser(::clap::value_parser!(Fqdn)),
+        );
+        for name in ["foo.example", "foo.example."] {
+            let matches = command
+                .clone()
+                .try_get_matches_from(["test", "--fqdn", name])
+                .unwrap();
+            assert_eq!(
+                matches.get_one::<Fqdn>("fqdn").unwrap().as_absolute(),
+                "foo.example."
+            );
+        }
+        let error = command
+            .try_get_matches_from(["test", "--fqdn", "foo..example"])
+            .unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::ValueValidation);
+    }
+
+    #[test]
+    fn parse_fqdn_with_options() {
+        let options = ParseOptions::new().strictness(Strictness::Liberal);
+        let command = Command::new("test")
+            .arg(Arg::new("fqdn").value_parser(FqdnValueParser::with_options(options)));
+        let matches = command.try_get_matches_from(["test", "foo_bar."]).unwrap();
+        assert_eq!(
+            matches.get_one::<Fqdn>("fqdn").unwrap().as_relative(),
+            "foo_bar"
+        );
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hostnames from the [Azure instance metadata service][imds].
+//!
+//! [imds]: https://learn.microsoft.com/en-us/azure/virtual-machines/instance-metadata-service
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use super::client::{request, Response};
+use super::DEFAULT_TIMEOUT;
+use crate::error::HostnameError;
+
+/// The address of the instance metadata service.
+pub const ENDPOINT: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)), 80);
+
+/// The API version we request by default.
+pub const DEFAULT_API_VERSION: &str = "2021-02-01";
+
+/// A client for the Azure instance metadata service.
+#[derive(Debug, Clone)]
+pub struct AzureMetadata {
+    endpoint: SocketAddr,
+    timeout: Duration,
+    api_version: String,
+}
+
+impl Default for AzureMetadata {
+    fn default() -> Self {
+        AzureMetadata::with_endpoint(ENDPOINT, DEFAULT_TIMEOUT)
+    }
+}
+
+impl AzureMetadata {
+    /// Create a client for the metadata service at [`ENDPOINT`], with
+    /// [`DEFAULT_TIMEOUT`].
+    pub fn new() -> AzureMetadata {
+        AzureMetadata::default()
+    }
+
+    /// Create a client for the metadata service at `endpoint`, with the given
+    /// `timeout` for every request.
+    pub fn with_endpoint(endpoint: SocketAddr, timeout: Duration) -> AzureMetadata {
+        AzureMetadata {
+            endpoint,
+            timeout,
+            api_version: DEFAULT_API_VERSION.to_string(),
+        }
+    }
+
+    /// Set the API version to request, instead of [`DEFAULT_API_VERSION`].
+    ///
+    /// If the metadata service doesn't support the API version we retry once
+    /// with the newest version the service reports.
+    pub fn api_version(mut self, version: &str) -> AzureMetadata {
+        self.api_version = version.to_string();
+        self
+    }
+
+    /// Get the computer name of this virtual machine.
+    ///
+    /// This is the hostname the operating system was provisioned with, which
+    /// may differ from the name of the virtual machine resource.
+    ///
+    /// Fail if the metadata service doesn't respond within the timeout, e.g.
+    /// because the current machine is no Azure virtual machine.
+    pub fn computer_name(&self) -> Result<String, HostnameError> {
+        self.get("instance/compute/osProfile/computerName")
+    }
+
+    /// Get the fully qualified domain name of this virtual machine in the
+    /// internal DNS of Azure, e.g. `vm-1.abcdef.bx.internal.cloudapp.net`.
+    ///
+    /// The metadata service has no item for the FQDN, so resolve the
+    /// [computer name](AzureMetadata::computer_name) with the DNS resolver of
+    /// the platform, and return its canonical name.  Give up resolving after
+    /// the timeout of this client.  Fail with
+    /// [`HostnameError::Unsupported`] on Windows; use
+    /// [`getfqdn()`](crate::getfqdn) there instead.
+    pub fn fqdn(&self) -> Result<String, HostnameError> {
+        self.fqdn_impl()
+    }
+
+    #[cfg(not(windows))]
+    fn fqdn_impl(&self) -> Result<String, HostnameError> {
+        let computer_name = self.computer_name()?;
+        let fqdn = crate::resolver::within(Some(self.timeout), move || {
+            crate::fqdn::canonical_name(computer_name.as_ref())
+        })?;
+        Ok(fqdn.to_string_lossy().into_owned())
+    }
+
+    #[cfg(windows)]
+    fn fqdn_impl(&self) -> Result<String, HostnameError> {
+        Err(HostnameError::Unsupported)
+    }
+
+    fn get(&self, item: &'static str) -> Result<String, HostnameError> {
+        let response = self.request(item, &self.api_version)?;
+        match newest_api_version(&response) {
+            Some(version) if version != self.api_version => {
+                warn!(
+                    "Azure IMDS does not support API version {}, retrying with {}",
+                    self.api_version, version
+                );
+                self.request(item, &version)?.into_body(item)
+            }
+            _ => response.into_body(item),
+        }
+    }
+
+    fn request(&self, item: &str, api_version: &str) -> Result<Response, HostnameError> {
+        request(
+            self.endpoint,
+            self.timeout,
+            "GET",
+            &format!("/metadata/{}?api-version={}&format=text", item, api_version),
+            // The metadata service rejects requests without this header
+            &[("Metadata", "true")],
+        )
+    }
+}
+
+/// Extract the newest API version from a response to a bad API version.
+///
+/// The metadata service responds with a JSON object which lists the newest
+/// supported versions in `newest-versions`, newest first.  Return `None` for
+/// any other response; the body is untrusted, so never panic on it.
+fn newest_api_version(response: &Response) -> Option<String> {
+    if response.status != 400 {
+        return None;
+    }
+    let body = &response.body;
+    let versions = body.get(body.find("\"newest-versions\"")?..)?;
+    let start = versions.find('[')? + 1;
+    let end = start + versions.get(start..)?.find(']')?;
+    let versions = versions.get(start..end)?;
+    let start = versions.find('"')? + 1;
+    let end = start + versions.get(start..)?.find('"')?;
+    versions.get(start..end).map(str::to_string)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{newest_api_version, AzureMetadata};
+    use crate::cloud::client::{tests::serve, Response};
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn computer_name_with_metadata_header() {
+        let (address, server) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nvm-1"]);
+        let metadata = AzureMetadata::with_endpoint(address, Duration::from_secs(5));
+        assert_eq!(metadata.computer_name().unwrap(), "vm-1");
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with(
+            "GET /metadata/instance/compute/osProfile/computerName?api-version=2021-02-01&format=text HTTP/1.1\r\n"
+        ));
+        assert!(requests[0].contains("Metadata: true\r\n"));
+    }
+
+    #[test]
+    fn retry_with_newest_api_version() {
+        let (address, server) = serve(vec![
+            concat!(
+                "HTTP/1.1 400 Bad Request\r\nContent-Length: 91\r\n\r\n",
+                r#"{"error": "Bad request. api-version is invalid", "newest-versions": ["2023-07-01", "2021"]}"#
+            ),
+            "HTTP/1.1 200 OK\r\nContent-Length: 4\r\n\r\nvm-1",
+        ]);
+        let metadata =
+            AzureMetadata::with_endpoint(address, Duration::from_secs(5)).api_version("1999-01-01");
+        assert_eq!(metadata.computer_name().unwrap(), "vm-1");
+        let requests = server.join().unwrap();
+        assert!(requests[0].contains("api-version=1999-01-01&"));
+        assert!(requests[1].contains("api-version=2023-07-01&"));
+    }
+
+    #[test]
+    fn newest_api_version_of_malformed_responses() {
+        let response = |status, body: &str| Response {
+            status,
+            body: body.to_string(),
+        };
+        let body = r#"{"newest-versions": ["2023-07-01"]}"#;
+        assert_eq!(
+            newest_api_version(&response(400, body)).as_deref(),
+            Some("2023-07-01")
+        );
+        assert_eq!(newest_api_version(&response(404, body)), None);
+        let body = r#"{"newest-versions": ] ["2023-07-01"]}"#;
+        assert_eq!(
+            newest_api_version(&response(400, body)).as_deref(),
+            Some("2023-07-01")
+        );
+        for body in &[
+            r#"{"newest-versions": ["2023-07-01}"#,
+            r#"{"newest-versions": [2023]}"#,
+            r#"{"newest-versions": ["#,
+            r#""newest-versions""#,
+        ] {
+            assert_eq!(newest_api_version(&response(400, body)), None, "{}", body);
+        }
+    }
+}
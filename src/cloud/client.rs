@@ -0,0 +1,228 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A minimal HTTP client for metadata services.
+//!
+//! Metadata services are plain HTTP servers on link-local addresses, so we
+//! don't need TLS, redirects or connection reuse, and a tiny HTTP/1.1 client
+//! on top of `TcpStream` saves us a dependency on a full HTTP stack.
+
+use std::io::{self, Read, Write};
+use std::net::{SocketAddr, TcpStream};
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// The maximum size of a response we accept.
+const MAX_RESPONSE_SIZE: u64 = 64 * 1024;
+
+/// A response from a metadata service.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub(crate) struct Response {
+    pub(crate) status: u16,
+    pub(crate) body: String,
+}
+
+impl Response {
+    /// Get the body of a successful response, or fail.
+    ///
+    /// Fail with [`HostnameError::NotFound`] for 404 responses, and with
+    /// [`HostnameError::ResolutionFailed`] for other unsuccessful responses.
+    pub(crate) fn into_body(self, what: &'static str) -> Result<String, HostnameError> {
+        match self.status {
+            200 => Ok(self.body.trim().to_string()),
+            404 => Err(HostnameError::NotFound { what }),
+            status => Err(HostnameError::ResolutionFailed {
+                code: i32::from(status),
+                message: format!("metadata service responded with HTTP {}", status),
+            }),
+        }
+    }
+}
+
+/// Send a request to a metadata service at `endpoint`.
+///
+/// Apply `timeout` to connecting, and to every read and write.
+pub(crate) fn request(
+    endpoint: SocketAddr,
+    timeout: Duration,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> Result<Response, HostnameError> {
+    send(endpoint, timeout, method, path, headers).map_err(|error| {
+        if error.kind() == io::ErrorKind::TimedOut || error.kind() == io::ErrorKind::WouldBlock {
+            HostnameError::timed_out()
+        } else {
+            HostnameError::from_io(&error)
+        }
+    })
+}
+
+fn send(
+    endpoint: SocketAddr,
+    timeout: Duration,
+    method: &str,
+    path: &str,
+    headers: &[(&str, &str)],
+) -> io::Result<Response> {
+    let mut stream = TcpStream::connect_timeout(&endpoint, timeout)?;
+    stream.set_read_timeout(Some(timeout))?;
+    stream.set_write_timeout(Some(timeout))?;
+    let mut request = format!(
+        "{} {} HTTP/1.1\r\nHost: {}\r\nConnection: close\r\n",
+        method, path, endpoint
+    );
+    for (name, value) in headers {
+        request.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if method != "GET" {
+        request.push_str("Content-Length: 0\r\n");
+    }
+    request.push_str("\r\n");
+    stream.write_all(request.as_bytes())?;
+    let mut response = Vec::new();
+    stream.take(MAX_RESPONSE_SIZE).read_to_end(&mut response)?;
+    parse_response(&response)
+        .ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "invalid HTTP response"))
+}
+
+/// Parse an HTTP response.
+fn parse_response(response: &[u8]) -> Option<Response> {
+    let end_of_head = response.windows(4).position(|w| w == b"\r\n\r\n")?;
+    let head = std::str::from_utf8(&response[..end_of_head]).ok()?;
+    let body = &response[end_of_head + 4..];
+    let mut lines = head.split("\r\n");
+    let status = lines.next()?.split(' ').nth(1)?.parse().ok()?;
+    let chunked = lines.any(|line| {
+        let mut parts = line.splitn(2, ':');
+        let name = parts.next().unwrap_or("").trim();
+        let value = parts.next().unwrap_or("").trim();
+        name.eq_ignore_ascii_case("transfer-encoding") && value.eq_ignore_ascii_case("chunked")
+    });
+    let body = if chunked {
+        decode_chunked(body)?
+    } else {
+        body.to_vec()
+    };
+    Some(Response {
+        status,
+        body: String::from_utf8(body).ok()?,
+    })
+}
+
+/// Decode a body with chunked transfer encoding.
+fn decode_chunked(mut body: &[u8]) -> Option<Vec<u8>> {
+    let mut decoded = Vec::new();
+    loop {
+        let end_of_size = body.windows(2).position(|w| w == b"\r\n")?;
+        let size = std::str::from_utf8(&body[..end_of_size]).ok()?;
+        // Ignore chunk extensions
+        let size = size.split(';').next()?.trim();
+        let size = usize::from_str_radix(size, 16).ok()?;
+        if size == 0 {
+            return Some(decoded);
+        }
+        // The size comes from the server, so guard against overflows
+        let start = end_of_size + 2;
+        let end = start.checked_add(size)?;
+        decoded.extend_from_slice(body.get(start..end)?);
+        body = body.get(end.checked_add(2)?..)?;
+    }
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::{parse_response, Response};
+    use pretty_assertions::assert_eq;
+    use std::io::{Read, Write};
+    use std::net::{SocketAddr, TcpListener};
+    use std::thread::JoinHandle;
+
+    /// Serve one canned `response` per request on a local port.
+    ///
+    /// Return the address of the server, and a handle which joins with the
+    /// requests the server received.
+    pub(crate) fn serve(responses: Vec<&'static str>) -> (SocketAddr, JoinHandle<Vec<String>>) {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+        let handle = std::thread::spawn(move || {
+            responses
+                .into_iter()
+                .map(|response| {
+                    let (mut stream, _) = listener.accept().unwrap();
+                    let mut request = Vec::new();
+                    let mut buffer = [0; 1024];
+                    while !request.ends_with(b"\r\n\r\n") {
+                        let read = stream.read(&mut buffer).unwrap();
+                        request.extend_from_slice(&buffer[..read]);
+                    }
+                    stream.write_all(response.as_bytes()).unwrap();
+                    String::from_utf8(request).unwrap()
+                })
+                .collect()
+        });
+        (address, handle)
+    }
+
+    #[test]
+    fn parse_plain_response() {
+        let response = parse_response(b"HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nfoo").unwrap();
+        assert_eq!(
+            response,
+            Response {
+                status: 200,
+                body: "foo".to_string()
+            }
+        );
+    }
+
+    #[test]
+    fn parse_chunked_response() {
+        let response = parse_response(
+            b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nfoo\r\n4;x=y\r\n.bar\r\n0\r\n\r\n",
+        )
+        .unwrap();
+        assert_eq!(response.body, "foo.bar");
+    }
+
+    #[test]
+    fn parse_invalid_responses() {
+        assert_eq!(parse_response(b"HTTP/1.1 200 OK\r\n"), None);
+        assert_eq!(parse_response(b"garbage\r\n\r\n"), None);
+        assert_eq!(
+            parse_response(b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\n3\r\nf"),
+            None
+        );
+        assert_eq!(
+            parse_response(
+                b"HTTP/1.1 200 OK\r\nTransfer-Encoding: chunked\r\n\r\nffffffffffffffff\r\nfoo\r\n0\r\n\r\n"
+            ),
+            None
+        );
+    }
+
+    #[test]
+    fn error_responses() {
+        let response = |status| Response {
+            status,
+            body: String::new(),
+        };
+        assert_eq!(
+            response(404).into_body("thing").unwrap_err(),
+            crate::HostnameError::NotFound { what: "thing" }
+        );
+        assert!(response(500).into_body("thing").is_err());
+    }
+}
@@ -0,0 +1,217 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hostnames from the [EC2 instance metadata service][imds].
+//!
+//! [imds]: https://docs.aws.amazon.com/AWSEC2/latest/UserGuide/configuring-instance-metadata-service.html
+
+use std::fmt;
+use std::net::{Ipv4Addr, SocketAddr};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use super::client::{request, Response};
+use super::DEFAULT_TIMEOUT;
+use crate::error::HostnameError;
+
+/// The address of the instance metadata service.
+pub const ENDPOINT: SocketAddr =
+    SocketAddr::new(std::net::IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)), 80);
+
+/// How long session tokens remain valid, in seconds.
+const TOKEN_TTL: u64 = 21600;
+
+/// How long before it expires to replace a session token.
+const TOKEN_MARGIN: Duration = Duration::from_secs(60);
+
+/// An IMDSv2 session token.
+struct Token {
+    value: String,
+    expires: Instant,
+}
+
+/// A session with the EC2 instance metadata service, version 2.
+///
+/// IMDSv2 requires a session token for all requests; this type requests a
+/// token when connecting, and then uses it for all requests.  Session tokens
+/// remain valid for six hours; request a new token shortly before the
+/// current one expires, or if the metadata service rejects it nonetheless.
+/// Clones share the token.
+///
+/// The `Debug` output leaves out the token.
+#[derive(Clone)]
+pub struct Ec2Metadata {
+    endpoint: SocketAddr,
+    timeout: Duration,
+    token: Arc<Mutex<Token>>,
+}
+
+impl fmt::Debug for Ec2Metadata {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Ec2Metadata")
+            .field("endpoint", &self.endpoint)
+            .field("timeout", &self.timeout)
+            .field("token", &"***")
+            .finish()
+    }
+}
+
+/// Request a new session token from the metadata service at `endpoint`.
+fn request_token(endpoint: SocketAddr, timeout: Duration) -> Result<Token, HostnameError> {
+    let requested = Instant::now();
+    let value = request(
+        endpoint,
+        timeout,
+        "PUT",
+        "/latest/api/token",
+        &[(
+            "X-aws-ec2-metadata-token-ttl-seconds",
+            &TOKEN_TTL.to_string(),
+        )],
+    )?
+    .into_body("IMDSv2 token")?;
+    Ok(Token {
+        value,
+        expires: requested + Duration::from_secs(TOKEN_TTL) - TOKEN_MARGIN,
+    })
+}
+
+impl Ec2Metadata {
+    /// Connect to the instance metadata service at [`ENDPOINT`].
+    ///
+    /// Fail if the metadata service doesn't respond within
+    /// [`DEFAULT_TIMEOUT`], e.g. because the current machine is no EC2
+    /// instance.
+    pub fn connect() -> Result<Ec2Metadata, HostnameError> {
+        Ec2Metadata::connect_to(ENDPOINT, DEFAULT_TIMEOUT)
+    }
+
+    /// Connect to the instance metadata service at `endpoint`, with the given
+    /// `timeout` for every request.
+    pub fn connect_to(
+        endpoint: SocketAddr,
+        timeout: Duration,
+    ) -> Result<Ec2Metadata, HostnameError> {
+        let token = request_token(endpoint, timeout)?;
+        Ok(Ec2Metadata {
+            endpoint,
+            timeout,
+            token: Arc::new(Mutex::new(token)),
+        })
+    }
+
+    /// Get the private DNS name of this instance, e.g.
+    /// `ip-10-0-0-1.ec2.internal`.
+    pub fn local_hostname(&self) -> Result<String, HostnameError> {
+        self.get("local-hostname")
+    }
+
+    /// Get the public DNS name of this instance.
+    ///
+    /// Fail with [`HostnameError::NotFound`] if the instance has no public
+    /// DNS name.
+    pub fn public_hostname(&self) -> Result<String, HostnameError> {
+        self.get("public-hostname")
+    }
+
+    fn get(&self, item: &'static str) -> Result<String, HostnameError> {
+        let response = self.request(item, false)?;
+        if response.status == 401 {
+            warn!("IMDSv2 rejected session token, requesting a new one");
+            self.request(item, true)?.into_body(item)
+        } else {
+            response.into_body(item)
+        }
+    }
+
+    /// Request `item` with the current session token, or with a new one if
+    /// the current one expired or if `refresh` is set.
+    fn request(&self, item: &'static str, refresh: bool) -> Result<Response, HostnameError> {
+        let token = {
+            let mut token = self.token.lock().unwrap_or_else(|error| error.into_inner());
+            if refresh || token.expires <= Instant::now() {
+                *token = request_token(self.endpoint, self.timeout)?;
+            }
+            token.value.clone()
+        };
+        request(
+            self.endpoint,
+            self.timeout,
+            "GET",
+            &format!("/latest/meta-data/{}", item),
+            &[("X-aws-ec2-metadata-token", &token)],
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Ec2Metadata;
+    use crate::cloud::client::tests::serve;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn hostnames_with_token() {
+        let (address, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 5\r\n\r\ntoken",
+            "HTTP/1.1 200 OK\r\nContent-Length: 24\r\n\r\nip-10-0-0-1.ec2.internal",
+            "HTTP/1.1 404 Not Found\r\nContent-Length: 0\r\n\r\n",
+        ]);
+        let metadata = Ec2Metadata::connect_to(address, Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            metadata.local_hostname().unwrap(),
+            "ip-10-0-0-1.ec2.internal"
+        );
+        assert_eq!(
+            metadata.public_hostname().unwrap_err(),
+            HostnameError::NotFound {
+                what: "public-hostname"
+            }
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("PUT /latest/api/token HTTP/1.1\r\n"));
+        assert!(requests[0].contains("X-aws-ec2-metadata-token-ttl-seconds: 21600\r\n"));
+        assert!(requests[1].starts_with("GET /latest/meta-data/local-hostname HTTP/1.1\r\n"));
+        assert!(requests[1].contains("X-aws-ec2-metadata-token: token\r\n"));
+    }
+
+    #[test]
+    fn refresh_rejected_token() {
+        let (address, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nold",
+            "HTTP/1.1 401 Unauthorized\r\nContent-Length: 0\r\n\r\n",
+            "HTTP/1.1 200 OK\r\nContent-Length: 3\r\n\r\nnew",
+            "HTTP/1.1 200 OK\r\nContent-Length: 24\r\n\r\nip-10-0-0-1.ec2.internal",
+        ]);
+        let metadata = Ec2Metadata::connect_to(address, Duration::from_secs(5)).unwrap();
+        assert_eq!(
+            metadata.local_hostname().unwrap(),
+            "ip-10-0-0-1.ec2.internal"
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[1].contains("X-aws-ec2-metadata-token: old\r\n"));
+        assert!(requests[2].starts_with("PUT /latest/api/token HTTP/1.1\r\n"));
+        assert!(requests[3].contains("X-aws-ec2-metadata-token: new\r\n"));
+    }
+
+    #[test]
+    fn debug_redacts_token() {
+        let (address, server) = serve(vec!["HTTP/1.1 200 OK\r\nContent-Length: 6\r\n\r\nsecret"]);
+        let metadata = Ec2Metadata::connect_to(address, Duration::from_secs(5)).unwrap();
+        server.join().unwrap();
+        assert!(!format!("{:?}", metadata).contains("secret"));
+    }
+}
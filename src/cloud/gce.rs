@@ -0,0 +1,98 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hostnames from the [GCE metadata server][metadata].
+//!
+//! [metadata]: https://cloud.google.com/compute/docs/metadata/overview
+
+use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+use std::time::Duration;
+
+use super::client::request;
+use super::DEFAULT_TIMEOUT;
+use crate::error::HostnameError;
+
+/// The address of the metadata server, i.e. `metadata.google.internal`.
+pub const ENDPOINT: SocketAddr = SocketAddr::new(IpAddr::V4(Ipv4Addr::new(169, 254, 169, 254)), 80);
+
+/// A client for the GCE metadata server.
+#[derive(Debug, Clone)]
+pub struct GceMetadata {
+    endpoint: SocketAddr,
+    timeout: Duration,
+}
+
+impl Default for GceMetadata {
+    fn default() -> Self {
+        GceMetadata::with_endpoint(ENDPOINT, DEFAULT_TIMEOUT)
+    }
+}
+
+impl GceMetadata {
+    /// Create a client for the metadata server at [`ENDPOINT`], with
+    /// [`DEFAULT_TIMEOUT`].
+    pub fn new() -> GceMetadata {
+        GceMetadata::default()
+    }
+
+    /// Create a client for the metadata server at `endpoint`, with the given
+    /// `timeout` for every request.
+    pub fn with_endpoint(endpoint: SocketAddr, timeout: Duration) -> GceMetadata {
+        GceMetadata { endpoint, timeout }
+    }
+
+    /// Get the fully qualified internal DNS name of this instance, e.g.
+    /// `instance-1.us-central1-a.c.my-project.internal`.
+    ///
+    /// Fail if the metadata server doesn't respond within the timeout, e.g.
+    /// because the current machine is no GCE instance.
+    pub fn hostname(&self) -> Result<String, HostnameError> {
+        self.get("instance/hostname")
+    }
+
+    fn get(&self, item: &'static str) -> Result<String, HostnameError> {
+        request(
+            self.endpoint,
+            self.timeout,
+            "GET",
+            &format!("/computeMetadata/v1/{}", item),
+            // The metadata server rejects requests without this header
+            &[("Metadata-Flavor", "Google")],
+        )?
+        .into_body(item)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::GceMetadata;
+    use crate::cloud::client::tests::serve;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn hostname_with_metadata_flavor() {
+        let (address, server) = serve(vec![
+            "HTTP/1.1 200 OK\r\nMetadata-Flavor: Google\r\nContent-Length: 46\r\n\r\ninstance-1.us-central1-a.c.my-project.internal",
+        ]);
+        let metadata = GceMetadata::with_endpoint(address, Duration::from_secs(5));
+        assert_eq!(
+            metadata.hostname().unwrap(),
+            "instance-1.us-central1-a.c.my-project.internal"
+        );
+        let requests = server.join().unwrap();
+        assert!(requests[0].starts_with("GET /computeMetadata/v1/instance/hostname HTTP/1.1\r\n"));
+        assert!(requests[0].contains("Metadata-Flavor: Google\r\n"));
+    }
+}
@@ -0,0 +1,38 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hostnames from the metadata services of cloud providers.
+//!
+//! On cloud instances the hostname of the kernel often differs from the DNS
+//! names the cloud provider assigned to the instance.  The functions in this
+//! module query the metadata service of the instance for these names.
+//!
+//! Metadata services are only reachable from within instances of the
+//! respective provider; elsewhere requests fail after a short timeout of one
+//! second per request.
+//!
+//! ```no_run
+//! let metadata = gethostname::cloud::ec2::Ec2Metadata::connect().unwrap();
+//! println!("Local hostname: {}", metadata.local_hostname().unwrap());
+//! ```
+
+use std::time::Duration;
+
+pub mod azure;
+mod client;
+pub mod ec2;
+pub mod gce;
+
+/// The default timeout for requests to metadata services.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(1);
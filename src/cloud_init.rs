@@ -0,0 +1,286 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The hostname cloud-init configured.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::Path;
+
+use crate::error::HostnameError;
+
+/// The hostname cloud-init set or intends to set.
+///
+/// See [`cloud_init_hostname()`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct CloudInitHostname {
+    hostname: Option<String>,
+    fqdn: Option<String>,
+    preserve_hostname: bool,
+    applied: bool,
+}
+
+impl CloudInitHostname {
+    /// The hostname, if any.
+    pub fn hostname(&self) -> Option<&str> {
+        self.hostname.as_deref()
+    }
+
+    /// The fully qualified domain name, if any.
+    pub fn fqdn(&self) -> Option<&str> {
+        self.fqdn.as_deref()
+    }
+
+    /// Whether cloud-init is configured to leave the hostname alone.
+    pub fn preserve_hostname(&self) -> bool {
+        self.preserve_hostname
+    }
+
+    /// Whether cloud-init already set this hostname.
+    ///
+    /// If `false` cloud-init hasn't run its `set_hostname` module yet, and
+    /// will likely rename the machine soon, unless
+    /// [`preserve_hostname()`](CloudInitHostname::preserve_hostname) is set.
+    pub fn applied(&self) -> bool {
+        self.applied
+    }
+}
+
+/// Get the hostname cloud-init set, or intends to set.
+///
+/// Look at the following sources, and use the first which has a hostname:
+///
+/// 1. `/var/lib/cloud/data/set-hostname`, which cloud-init writes after it set
+///    the hostname.
+/// 2. The `hostname` and `fqdn` settings in `/etc/cloud/cloud.cfg` and
+///    `/etc/cloud/cloud.cfg.d/*.cfg`, which override the hostname of the data
+///    source.
+/// 3. The `local_hostname` of the data source in
+///    `/run/cloud-init/instance-data.json`.
+///
+/// Agents which start early during boot can compare the result with
+/// [`gethostname()`](crate::gethostname) to detect that cloud-init is about to
+/// rename the machine.
+///
+/// Fail with [`HostnameError::NotFound`] if cloud-init isn't installed or has
+/// no hostname.
+///
+/// ```
+/// match gethostname::cloud_init_hostname() {
+///     Ok(hostname) => println!("cloud-init hostname: {:?}", hostname.hostname()),
+///     Err(error) => eprintln!("No cloud-init hostname: {}", error),
+/// }
+/// ```
+pub fn cloud_init_hostname() -> Result<CloudInitHostname, HostnameError> {
+    cloud_init_hostname_in(Path::new("/"))
+}
+
+fn cloud_init_hostname_in(root: &Path) -> Result<CloudInitHostname, HostnameError> {
+    let mut result = CloudInitHostname::default();
+    let mut config_files = vec![root.join("etc/cloud/cloud.cfg")];
+    match fs::read_dir(root.join("etc/cloud/cloud.cfg.d")) {
+        Ok(entries) => {
+            let mut files = entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.extension().is_some_and(|ext| ext == "cfg"))
+                .collect::<Vec<_>>();
+            // cloud-init reads configuration files in lexical order, and later
+            // files override earlier ones.
+            files.sort();
+            config_files.extend(files);
+        }
+        Err(error) if error.kind() != ErrorKind::NotFound => {
+            return Err(HostnameError::from_io(&error))
+        }
+        Err(_) => {}
+    }
+    for file in config_files {
+        if let Some(config) = read_optional(&file)? {
+            if let Some(hostname) = yaml_value(&config, "hostname") {
+                result.hostname = Some(hostname);
+            }
+            if let Some(fqdn) = yaml_value(&config, "fqdn") {
+                result.fqdn = Some(fqdn);
+            }
+            if let Some(preserve) = yaml_value(&config, "preserve_hostname") {
+                result.preserve_hostname = preserve == "true";
+            }
+        }
+    }
+
+    if let Some(state) = read_optional(&root.join("var/lib/cloud/data/set-hostname"))? {
+        result.hostname = json_string(&state, "hostname");
+        result.fqdn = json_string(&state, "fqdn");
+        result.applied = true;
+    } else if result.hostname.is_none() && result.fqdn.is_none() {
+        if let Some(data) = read_optional(&root.join("run/cloud-init/instance-data.json"))? {
+            result.hostname = json_string(&data, "local_hostname");
+        }
+    }
+
+    if result.hostname.is_none() && result.fqdn.is_none() {
+        Err(HostnameError::NotFound {
+            what: "cloud-init hostname",
+        })
+    } else {
+        Ok(result)
+    }
+}
+
+/// Read `path`, or return `None` if it doesn't exist.
+fn read_optional(path: &Path) -> Result<Option<String>, HostnameError> {
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(HostnameError::from_io(&error)),
+    }
+}
+
+/// Get the value of the top-level `key` from a simple YAML document.
+///
+/// We don't parse YAML; we just look for an unindented line `key: value`, and
+/// strip quotes and comments from the value.
+fn yaml_value(yaml: &str, key: &str) -> Option<String> {
+    yaml.lines().rev().find_map(|line| {
+        let value = line.strip_prefix(key)?.trim_start().strip_prefix(':')?;
+        let value = value.split(" #").next()?.trim();
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    })
+}
+
+/// Get the first string value of `key` from a JSON document.
+///
+/// We don't parse JSON; we just look for the first `"key": "value"`, which
+/// suffices for the simple documents cloud-init writes.  Hostnames don't need
+/// escapes, so we don't handle them.
+fn json_string(json: &str, key: &str) -> Option<String> {
+    let needle = format!("\"{}\"", key);
+    let mut rest = json;
+    while let Some(start) = rest.find(&needle) {
+        rest = &rest[start + needle.len()..];
+        if let Some(value) = rest.trim_start().strip_prefix(':') {
+            let value = value.trim_start().strip_prefix('"')?;
+            let value = &value[..value.find('"')?];
+            return Some(value.to_string()).filter(|v| !v.is_empty());
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{cloud_init_hostname_in, json_string, yaml_value};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    fn root(name: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!(
+            "gethostname-cloud-init-{}-{}",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_dir_all(&root);
+        for (path, contents) in files {
+            let path = root.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn yaml_values() {
+        let yaml =
+            "# hostname: foo\nhostname: bar # comment\n  fqdn: nested\nfqdn: 'baz.example.com'\n";
+        assert_eq!(yaml_value(yaml, "hostname").unwrap(), "bar");
+        assert_eq!(yaml_value(yaml, "fqdn").unwrap(), "baz.example.com");
+        assert_eq!(yaml_value(yaml, "preserve_hostname"), None);
+    }
+
+    #[test]
+    fn json_strings() {
+        let json = r#"{"v1": {"local_hostname": "ip-10-0-0-1", "region": "eu-central-1"}}"#;
+        assert_eq!(json_string(json, "local_hostname").unwrap(), "ip-10-0-0-1");
+        assert_eq!(json_string(json, "hostname"), None);
+    }
+
+    #[test]
+    fn applied_hostname() {
+        let root = root(
+            "applied",
+            &[
+                ("etc/cloud/cloud.cfg", "preserve_hostname: false\n"),
+                (
+                    "var/lib/cloud/data/set-hostname",
+                    r#"{"fqdn": "web-1.example.com", "hostname": "web-1"}"#,
+                ),
+            ],
+        );
+        let hostname = cloud_init_hostname_in(&root).unwrap();
+        assert_eq!(hostname.hostname(), Some("web-1"));
+        assert_eq!(hostname.fqdn(), Some("web-1.example.com"));
+        assert!(hostname.applied());
+        assert!(!hostname.preserve_hostname());
+    }
+
+    #[test]
+    fn configured_hostname() {
+        let root = root(
+            "configured",
+            &[
+                (
+                    "etc/cloud/cloud.cfg",
+                    "hostname: foo\npreserve_hostname: true\n",
+                ),
+                ("etc/cloud/cloud.cfg.d/90_override.cfg", "hostname: bar\n"),
+                ("etc/cloud/cloud.cfg.d/99_ignored.txt", "hostname: baz\n"),
+                (
+                    "run/cloud-init/instance-data.json",
+                    r#"{"v1": {"local_hostname": "ds"}}"#,
+                ),
+            ],
+        );
+        let hostname = cloud_init_hostname_in(&root).unwrap();
+        assert_eq!(hostname.hostname(), Some("bar"));
+        assert!(hostname.preserve_hostname());
+        assert!(!hostname.applied());
+    }
+
+    #[test]
+    fn data_source_hostname() {
+        let root = root(
+            "data-source",
+            &[(
+                "run/cloud-init/instance-data.json",
+                r#"{"v1": {"local_hostname": "ip-10-0-0-1"}}"#,
+            )],
+        );
+        let hostname = cloud_init_hostname_in(&root).unwrap();
+        assert_eq!(hostname.hostname(), Some("ip-10-0-0-1"));
+        assert!(!hostname.applied());
+    }
+
+    #[test]
+    fn no_cloud_init() {
+        assert_eq!(
+            cloud_init_hostname_in(Path::new("/nonexistent")).unwrap_err(),
+            HostnameError::NotFound {
+                what: "cloud-init hostname"
+            }
+        );
+    }
+}
@@ -0,0 +1,318 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Process-wide configuration of the hostname.
+//!
+//! Applications call [`configure()`] once at startup to set a policy for the
+//! hostname, which then applies to every call of [`gethostname()`](crate::gethostname),
+//! [`try_gethostname()`](crate::try_gethostname) and
+//! [`try_gethostname_into()`](crate::try_gethostname_into) in the process,
+//! including calls from libraries like loggers and tracers.
+//!
+//! ```
+//! use gethostname::config::{configure, Config, HostnameSource};
+//!
+//! let config = Config::new()
+//!     .source_chain([HostnameSource::Env("NODE_NAME".into()), HostnameSource::System])
+//!     .normalize(true)
+//!     .fallback("unknown")
+//!     .cache(true);
+//! configure(config).unwrap();
+//! println!("Hostname: {:?}", gethostname::gethostname());
+//! ```
+
+use std::ffi::{OsStr, OsString};
+use std::path::PathBuf;
+use std::sync::OnceLock;
+
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+
+static CONFIGURED: OnceLock<Configured> = OnceLock::new();
+
+/// Where to get the hostname from.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum HostnameSource {
+    /// Ask the operating system, as without configuration.
+    System,
+    /// Read the environment variable of the given name, e.g. `HOSTNAME`.
+    Env(String),
+    /// Read the file at the given path, e.g. `/etc/hostname`, and trim
+    /// whitespace.
+    File(PathBuf),
+}
+
+impl HostnameSource {
+    fn get(&self) -> Result<OsString, HostnameError> {
+        match self {
+            HostnameSource::System => crate::gethostname_impl(),
+            HostnameSource::Env(name) => std::env::var_os(name).ok_or(HostnameError::NotFound {
+                what: "hostname variable",
+            }),
+            HostnameSource::File(path) => std::fs::read_to_string(path)
+                .map(|contents| OsString::from(contents.trim()))
+                .map_err(|error| HostnameError::from_io(&error)),
+        }
+    }
+}
+
+/// The configuration of the hostname.
+///
+/// By default ask the operating system for the hostname, without any
+/// normalization, fallback or caching, i.e. behave as without configuration.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    normalize: bool,
+    fallback: Option<String>,
+    source_chain: Vec<HostnameSource>,
+    cache: bool,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Config {
+            normalize: false,
+            fallback: None,
+            source_chain: vec![HostnameSource::System],
+            cache: false,
+        }
+    }
+}
+
+impl Config {
+    /// Create the default configuration.
+    pub fn new() -> Config {
+        Config::default()
+    }
+
+    /// Whether to convert ASCII letters to lowercase and strip a trailing dot.
+    pub fn normalize(mut self, normalize: bool) -> Config {
+        self.normalize = normalize;
+        self
+    }
+
+    /// Return `fallback` if no source has a hostname.
+    pub fn fallback<S: Into<String>>(mut self, fallback: S) -> Config {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Try the given `sources` in order, and use the first non-empty hostname.
+    pub fn source_chain<I: IntoIterator<Item = HostnameSource>>(mut self, sources: I) -> Config {
+        self.source_chain = sources.into_iter().collect();
+        self
+    }
+
+    /// Whether to get the hostname only once, and return the same hostname
+    /// for the rest of the process.
+    ///
+    /// Don't cache failures, and try again next time instead.
+    pub fn cache(mut self, cache: bool) -> Config {
+        self.cache = cache;
+        self
+    }
+}
+
+/// Apply `config` to all calls of the hostname functions of this crate in this
+/// process.
+///
+/// Configure only once; return `config` as error if the process already has a
+/// configuration.  The `fixed-hostname` feature takes precedence over the
+/// configuration.
+pub fn configure(config: Config) -> Result<(), Config> {
+    CONFIGURED
+        .set(Configured::new(config))
+        .map_err(|configured| configured.config)
+}
+
+/// Get the hostname according to the process-wide configuration, if any.
+pub(crate) fn configured_hostname() -> Option<Result<OsString, HostnameError>> {
+    CONFIGURED.get().map(Configured::gethostname)
+}
+
+/// Copy a configured `hostname` into `buffer`.
+pub(crate) fn copy_into<'a>(
+    hostname: &OsStr,
+    buffer: &'a mut [u8],
+) -> Result<&'a str, HostnameError> {
+    let hostname = hostname.to_str().ok_or_else(|| {
+        HostnameError::invalid_name(
+            InvalidNameReason::NotUnicode,
+            hostname.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+        )
+    })?;
+    let target = buffer
+        .get_mut(..hostname.len())
+        .ok_or(HostnameError::BufferTooSmall)?;
+    target.copy_from_slice(hostname.as_bytes());
+    std::str::from_utf8(target).map_err(|_| HostnameError::BufferTooSmall)
+}
+
+/// A configuration with its cached hostname.
+#[derive(Debug)]
+struct Configured {
+    config: Config,
+    cached: OnceLock<OsString>,
+}
+
+impl Configured {
+    fn new(config: Config) -> Configured {
+        Configured {
+            config,
+            cached: OnceLock::new(),
+        }
+    }
+
+    fn gethostname(&self) -> Result<OsString, HostnameError> {
+        if let Some(hostname) = self.cached.get() {
+            return Ok(hostname.clone());
+        }
+        let hostname = self.lookup()?;
+        if self.config.cache {
+            return Ok(self.cached.get_or_init(|| hostname).clone());
+        }
+        Ok(hostname)
+    }
+
+    fn lookup(&self) -> Result<OsString, HostnameError> {
+        let mut result = Err(HostnameError::NotFound { what: "hostname" });
+        for source in &self.config.source_chain {
+            match source.get() {
+                Ok(hostname) if !hostname.is_empty() => {
+                    result = Ok(hostname);
+                    break;
+                }
+                Ok(_) => warn!("Empty hostname from {:?}", source),
+                Err(error) => {
+                    warn!("No hostname from {:?}: {}", source, error);
+                    result = Err(error);
+                }
+            }
+        }
+        let hostname = match (result, &self.config.fallback) {
+            (Ok(hostname), _) => hostname,
+            (Err(error), Some(fallback)) => {
+                warn!("No hostname, using fallback {}: {}", fallback, error);
+                OsString::from(fallback)
+            }
+            (Err(error), None) => return Err(error),
+        };
+        Ok(if self.config.normalize {
+            normalize(hostname)
+        } else {
+            hostname
+        })
+    }
+}
+
+fn normalize(mut hostname: OsString) -> OsString {
+    hostname.make_ascii_lowercase();
+    match hostname.into_string() {
+        Ok(mut hostname) => {
+            if hostname.ends_with('.') {
+                hostname.pop();
+            }
+            OsString::from(hostname)
+        }
+        Err(hostname) => hostname,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Config, Configured, HostnameSource};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+
+    fn env(name: &str) -> HostnameSource {
+        HostnameSource::Env(name.to_string())
+    }
+
+    #[test]
+    fn default_config_uses_system_hostname() {
+        let configured = Configured::new(Config::new());
+        assert_eq!(configured.gethostname().unwrap(), crate::gethostname());
+    }
+
+    #[test]
+    fn source_chain_uses_first_non_empty_hostname() {
+        std::env::set_var("GETHOSTNAME_TEST_CONFIG_EMPTY", "");
+        std::env::set_var("GETHOSTNAME_TEST_CONFIG_NAME", "Node-1.Example.");
+        let config = Config::new().source_chain([
+            env("GETHOSTNAME_TEST_CONFIG_UNSET"),
+            env("GETHOSTNAME_TEST_CONFIG_EMPTY"),
+            env("GETHOSTNAME_TEST_CONFIG_NAME"),
+            HostnameSource::System,
+        ]);
+        let configured = Configured::new(config.clone());
+        assert_eq!(configured.gethostname().unwrap(), "Node-1.Example.");
+        let configured = Configured::new(config.normalize(true));
+        assert_eq!(configured.gethostname().unwrap(), "node-1.example");
+    }
+
+    #[test]
+    fn hostname_from_file() {
+        let path = std::env::temp_dir().join("gethostname-test-config-hostname");
+        std::fs::write(&path, "  file-host\n").unwrap();
+        let configured =
+            Configured::new(Config::new().source_chain([HostnameSource::File(path.clone())]));
+        let hostname = configured.gethostname();
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(hostname.unwrap(), "file-host");
+    }
+
+    #[test]
+    fn fallback_if_no_source_has_hostname() {
+        let config = Config::new().source_chain([env("GETHOSTNAME_TEST_CONFIG_UNSET")]);
+        assert_eq!(
+            Configured::new(config.clone()).gethostname(),
+            Err(HostnameError::NotFound {
+                what: "hostname variable"
+            })
+        );
+        let configured = Configured::new(config.fallback("unknown"));
+        assert_eq!(configured.gethostname().unwrap(), "unknown");
+        let configured = Configured::new(Config::new().source_chain([]));
+        assert_eq!(
+            configured.gethostname(),
+            Err(HostnameError::NotFound { what: "hostname" })
+        );
+    }
+
+    #[test]
+    fn cache_hostname() {
+        std::env::set_var("GETHOSTNAME_TEST_CONFIG_CACHE", "first");
+        let source = || [env("GETHOSTNAME_TEST_CONFIG_CACHE")];
+        let cached = Configured::new(Config::new().source_chain(source()).cache(true));
+        let uncached = Configured::new(Config::new().source_chain(source()));
+        assert_eq!(cached.gethostname().unwrap(), "first");
+        std::env::set_var("GETHOSTNAME_TEST_CONFIG_CACHE", "second");
+        assert_eq!(cached.gethostname().unwrap(), "first");
+        assert_eq!(uncached.gethostname().unwrap(), "second");
+    }
+
+    #[test]
+    fn copy_into_buffer() {
+        let mut buffer = [0; 4];
+        assert_eq!(
+            super::copy_into("foo".as_ref(), &mut buffer).unwrap(),
+            "foo"
+        );
+        assert_eq!(
+            super::copy_into("hostname".as_ref(), &mut buffer),
+            Err(HostnameError::BufferTooSmall)
+        );
+    }
+}
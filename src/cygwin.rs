@@ -0,0 +1,203 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The hostname under Cygwin and MSYS2.
+//!
+//! Cygwin and MSYS2 emulate POSIX on Windows.  Their `gethostname` derives the
+//! hostname from the Windows computer name, but the two names need not be
+//! identical: They may differ in case, and either may carry a suffix the other
+//! lacks.  Toolchains which mix POSIX and native Windows tools often need both.
+
+use std::ffi::OsString;
+
+use crate::error::HostnameError;
+
+/// A POSIX emulation layer on Windows.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum PosixLayer {
+    /// [Cygwin](https://cygwin.com).
+    Cygwin,
+    /// [MSYS2](https://www.msys2.org), including Git for Windows.
+    Msys2,
+}
+
+impl PosixLayer {
+    /// Identify the layer from the system name of `uname`.
+    ///
+    /// Cygwin reports e.g. `CYGWIN_NT-10.0-19045`, and MSYS2 `MSYS_NT-10.0` or,
+    /// in its MinGW environments, `MINGW64_NT-10.0`.
+    fn from_sysname(sysname: &str) -> Option<PosixLayer> {
+        if sysname.starts_with("CYGWIN") {
+            Some(PosixLayer::Cygwin)
+        } else if sysname.starts_with("MSYS") || sysname.starts_with("MINGW") {
+            Some(PosixLayer::Msys2)
+        } else {
+            None
+        }
+    }
+}
+
+/// The POSIX and the Windows name of the current machine.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CygwinNames {
+    layer: PosixLayer,
+    posix: OsString,
+    windows: OsString,
+}
+
+impl CygwinNames {
+    /// The POSIX emulation layer.
+    pub fn layer(&self) -> PosixLayer {
+        self.layer
+    }
+
+    /// The hostname the POSIX emulation layer reports, i.e. what `uname -n`
+    /// and `gethostname` return in Cygwin or MSYS2 programs.
+    pub fn posix(&self) -> &OsString {
+        &self.posix
+    }
+
+    /// The DNS host name of Windows, as returned by `GetComputerNameExW`
+    /// with `ComputerNamePhysicalDnsHostname`.
+    pub fn windows(&self) -> &OsString {
+        &self.windows
+    }
+
+    /// Whether both names are identical.
+    pub fn is_consistent(&self) -> bool {
+        self.posix == self.windows
+    }
+}
+
+/// Detect the POSIX emulation layer we're built for, or running under.
+///
+/// In programs built for Cygwin ask `uname` directly; MSYS2 programs are
+/// Cygwin programs as well.  Native Windows programs are under a POSIX layer
+/// if they can run its `uname` from `PATH`, e.g. when started from a Cygwin or
+/// MSYS2 shell.  Return `None` otherwise.
+pub fn posix_layer() -> Option<PosixLayer> {
+    PosixLayer::from_sysname(&sysname()?)
+}
+
+/// Get the POSIX and the Windows name of the current machine.
+///
+/// Fail with [`HostnameError::NotFound`] if we're not in a Cygwin or MSYS2
+/// environment, see [`posix_layer()`].
+///
+/// ```no_run
+/// let names = gethostname::cygwin_names().unwrap();
+/// if !names.is_consistent() {
+///     println!("POSIX {:?}, Windows {:?}", names.posix(), names.windows());
+/// }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn cygwin_names() -> Result<CygwinNames, HostnameError> {
+    let layer = posix_layer().ok_or(HostnameError::NotFound {
+        what: "POSIX emulation layer",
+    })?;
+    let (posix, windows) = names()?;
+    Ok(CygwinNames {
+        layer,
+        posix,
+        windows,
+    })
+}
+
+#[cfg(target_os = "cygwin")]
+fn sysname() -> Option<String> {
+    let mut name: libc::utsname = unsafe { std::mem::zeroed() };
+    if unsafe { libc::uname(&mut name) } != 0 {
+        return None;
+    }
+    let sysname = unsafe { std::ffi::CStr::from_ptr(name.sysname.as_ptr()) };
+    Some(sysname.to_string_lossy().into_owned())
+}
+
+#[cfg(target_os = "cygwin")]
+fn names() -> Result<(OsString, OsString), HostnameError> {
+    Ok((crate::try_gethostname()?, windows_name()?))
+}
+
+/// Get the DNS host name of Windows with `GetComputerNameExW`.
+#[cfg(target_os = "cygwin")]
+fn windows_name() -> Result<OsString, HostnameError> {
+    // ComputerNamePhysicalDnsHostname
+    const NAME_TYPE: i32 = 5;
+
+    #[link(name = "kernel32")]
+    extern "system" {
+        fn GetComputerNameExW(name_type: i32, buffer: *mut u16, size: *mut u32) -> i32;
+        fn GetLastError() -> u32;
+    }
+
+    // DNS hostnames have at most 255 characters
+    let mut buffer = [0_u16; 256];
+    let mut size = buffer.len() as u32;
+    if unsafe { GetComputerNameExW(NAME_TYPE, buffer.as_mut_ptr(), &mut size) } == 0 {
+        return Err(HostnameError::from_errno(unsafe { GetLastError() } as i32)
+            .in_call("GetComputerNameExW", Some(buffer.len())));
+    }
+    let name = buffer.get(..size as usize).unwrap_or(&buffer);
+    Ok(String::from_utf16_lossy(name).into())
+}
+
+#[cfg(windows)]
+fn sysname() -> Option<String> {
+    uname("-s").ok()
+}
+
+#[cfg(windows)]
+fn names() -> Result<(OsString, OsString), HostnameError> {
+    Ok((uname("-n")?.into(), crate::try_gethostname()?))
+}
+
+/// Run `uname` of the POSIX layer with `flag`, and return its output.
+#[cfg(windows)]
+fn uname(flag: &str) -> Result<String, HostnameError> {
+    let output = std::process::Command::new("uname")
+        .arg(flag)
+        .output()
+        .map_err(|error| HostnameError::from_io(&error))?;
+    if !output.status.success() {
+        return Err(HostnameError::ServiceFailed {
+            service: "uname",
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::PosixLayer;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn layer_from_sysname() {
+        assert_eq!(
+            PosixLayer::from_sysname("CYGWIN_NT-10.0-19045"),
+            Some(PosixLayer::Cygwin)
+        );
+        assert_eq!(
+            PosixLayer::from_sysname("MSYS_NT-10.0-19045"),
+            Some(PosixLayer::Msys2)
+        );
+        assert_eq!(
+            PosixLayer::from_sysname("MINGW64_NT-10.0-19045"),
+            Some(PosixLayer::Msys2)
+        );
+        assert_eq!(PosixLayer::from_sysname("Linux"), None);
+    }
+}
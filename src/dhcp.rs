@@ -0,0 +1,244 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The hostname and domain assigned by DHCP.
+
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::error::HostnameError;
+
+/// The hostname and domain from a DHCP lease.
+///
+/// See [`dhcp_hostname()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DhcpHostname {
+    hostname: Option<String>,
+    domain: Option<String>,
+    lease_file: PathBuf,
+}
+
+impl DhcpHostname {
+    /// The hostname of DHCP option 12, if any.
+    pub fn hostname(&self) -> Option<&str> {
+        self.hostname.as_deref()
+    }
+
+    /// The domain name of DHCP option 15, if any.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// The lease file we read the hostname and domain from.
+    pub fn lease_file(&self) -> &Path {
+        &self.lease_file
+    }
+}
+
+/// A format of lease files.
+#[derive(Debug, Clone, Copy)]
+enum LeaseFormat {
+    /// Lease files of systemd-networkd, with `KEY=value` lines.
+    Networkd,
+    /// Device state of NetworkManager, in INI format.
+    NetworkManager,
+    /// Lease files of ISC dhclient.
+    Dhclient,
+}
+
+/// Where to look for lease files.
+const LEASE_DIRECTORIES: &[(&str, LeaseFormat)] = &[
+    ("run/systemd/netif/leases", LeaseFormat::Networkd),
+    ("run/NetworkManager/devices", LeaseFormat::NetworkManager),
+    ("var/lib/dhcp", LeaseFormat::Dhclient),
+    ("var/lib/dhclient", LeaseFormat::Dhclient),
+];
+
+/// Get the hostname and domain the network assigned via DHCP.
+///
+/// Read the leases of systemd-networkd from `/run/systemd/netif/leases`, the
+/// DHCP state of NetworkManager from `/run/NetworkManager/devices`, and the
+/// leases of ISC dhclient from `/var/lib/dhcp` and `/var/lib/dhclient`, in this
+/// order.  Return the hostname (option 12) and domain name (option 15) of the
+/// first lease which has either.  For dhclient use the most recent lease in a
+/// lease file.
+///
+/// On many networks the name DHCP assigns, and not the local setting, is the
+/// authoritative name of the machine.
+///
+/// Fail with [`HostnameError::NotFound`] if no lease has a hostname or domain.
+///
+/// ```
+/// match gethostname::dhcp_hostname() {
+///     Ok(dhcp) => println!("DHCP hostname: {:?}", dhcp.hostname()),
+///     Err(error) => eprintln!("No DHCP hostname: {}", error),
+/// }
+/// ```
+pub fn dhcp_hostname() -> Result<DhcpHostname, HostnameError> {
+    dhcp_hostname_in(Path::new("/"))
+}
+
+fn dhcp_hostname_in(root: &Path) -> Result<DhcpHostname, HostnameError> {
+    for (directory, format) in LEASE_DIRECTORIES {
+        let mut files = match fs::read_dir(root.join(directory)) {
+            Ok(entries) => entries
+                .filter_map(|entry| Some(entry.ok()?.path()))
+                .filter(|path| path.is_file())
+                .collect::<Vec<_>>(),
+            Err(error) if error.kind() == ErrorKind::NotFound => continue,
+            Err(error) => return Err(HostnameError::from_io(&error)),
+        };
+        files.sort();
+        for file in files {
+            let contents = match fs::read_to_string(&file) {
+                Ok(contents) => contents,
+                // Some lease files are only readable by root
+                Err(error) if error.kind() == ErrorKind::PermissionDenied => continue,
+                Err(error) => return Err(HostnameError::from_io(&error)),
+            };
+            let (hostname, domain) = match format {
+                LeaseFormat::Networkd => parse_networkd(&contents),
+                LeaseFormat::NetworkManager => parse_network_manager(&contents),
+                LeaseFormat::Dhclient => parse_dhclient(&contents),
+            };
+            if hostname.is_some() || domain.is_some() {
+                return Ok(DhcpHostname {
+                    hostname,
+                    domain,
+                    lease_file: file,
+                });
+            }
+        }
+    }
+    Err(HostnameError::NotFound {
+        what: "DHCP hostname",
+    })
+}
+
+type Names = (Option<String>, Option<String>);
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim().trim_matches('"');
+    Some(value.to_string()).filter(|v| !v.is_empty())
+}
+
+/// Parse a lease file of systemd-networkd.
+fn parse_networkd(contents: &str) -> Names {
+    let value = |key: &str| {
+        contents.lines().find_map(|line| {
+            let value = line.strip_prefix(key)?.strip_prefix('=')?;
+            non_empty(value)
+        })
+    };
+    (value("HOSTNAME"), value("DOMAINNAME"))
+}
+
+/// Parse the DHCP section of a device state file of NetworkManager.
+fn parse_network_manager(contents: &str) -> Names {
+    let mut in_dhcp4 = false;
+    let (mut hostname, mut domain) = (None, None);
+    for line in contents.lines().map(str::trim) {
+        if line.starts_with('[') {
+            in_dhcp4 = line == "[dhcp4]";
+        } else if let (true, Some((key, value))) = (in_dhcp4, line.split_once('=')) {
+            match key.trim() {
+                "option_12_host_name" | "host_name" => hostname = non_empty(value),
+                "option_15_domain_name" | "domain_name" => domain = non_empty(value),
+                _ => {}
+            }
+        }
+    }
+    (hostname, domain)
+}
+
+/// Parse a lease file of ISC dhclient, and use the last lease.
+fn parse_dhclient(contents: &str) -> Names {
+    let lease = contents.rsplit("lease {").next().unwrap_or("");
+    let option = |name: &str| {
+        lease.lines().find_map(|line| {
+            let value = line.trim().strip_prefix("option ")?.strip_prefix(name)?;
+            non_empty(value.strip_prefix(' ')?.trim_end_matches(';'))
+        })
+    };
+    (option("host-name"), option("domain-name"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{dhcp_hostname_in, parse_dhclient, parse_network_manager, parse_networkd};
+    use pretty_assertions::assert_eq;
+    use std::fs;
+    use std::path::Path;
+
+    fn names(hostname: &str, domain: &str) -> (Option<String>, Option<String>) {
+        (Some(hostname.to_string()), Some(domain.to_string()))
+    }
+
+    #[test]
+    fn networkd_lease() {
+        let lease = "# This is private data. Do not parse.\nADDRESS=10.0.0.5\nHOSTNAME=web-1\nDOMAINNAME=example.com\n";
+        assert_eq!(parse_networkd(lease), names("web-1", "example.com"));
+    }
+
+    #[test]
+    fn network_manager_state() {
+        let state = "[device]\nhost_name=wrong\n\n[dhcp4]\nip_address=10.0.0.5\noption_12_host_name=web-1\noption_15_domain_name=example.com\n";
+        assert_eq!(parse_network_manager(state), names("web-1", "example.com"));
+    }
+
+    #[test]
+    fn dhclient_uses_last_lease() {
+        let leases = r#"lease {
+  interface "eth0";
+  option host-name "old";
+  option domain-name "old.example.com";
+}
+lease {
+  interface "eth0";
+  fixed-address 10.0.0.5;
+  option host-name "web-1";
+  option domain-name-servers 10.0.0.1;
+  option domain-name "example.com";
+}
+"#;
+        assert_eq!(parse_dhclient(leases), names("web-1", "example.com"));
+    }
+
+    #[test]
+    fn first_lease_with_names() {
+        let root = std::env::temp_dir().join(format!("gethostname-dhcp-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&root);
+        fs::create_dir_all(root.join("run/systemd/netif/leases")).unwrap();
+        fs::create_dir_all(root.join("var/lib/dhcp")).unwrap();
+        fs::write(
+            root.join("run/systemd/netif/leases/2"),
+            "ADDRESS=10.0.0.5\n",
+        )
+        .unwrap();
+        fs::write(
+            root.join("var/lib/dhcp/dhclient.eth0.leases"),
+            "lease {\n  option host-name \"web-1\";\n}\n",
+        )
+        .unwrap();
+        let dhcp = dhcp_hostname_in(&root).unwrap();
+        assert_eq!(dhcp.hostname(), Some("web-1"));
+        assert_eq!(dhcp.domain(), None);
+        assert_eq!(
+            dhcp.lease_file(),
+            root.join("var/lib/dhcp/dhclient.eth0.leases")
+        );
+        assert!(dhcp_hostname_in(Path::new("/nonexistent")).is_err());
+    }
+}
@@ -0,0 +1,333 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Find common misconfigurations of the hostname.
+
+use std::fmt;
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::backend::{ResolverBackend, SystemBackend, TimeoutBackend};
+use crate::error::HostnameError;
+use crate::hostname::MAX_LABEL_LEN;
+
+/// A problem with the hostname of the current machine, found by [`diagnose()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Finding {
+    /// The hostname is `localhost` or `localhost.localdomain`, i.e. the
+    /// machine was never given a proper name.
+    Localhost {
+        /// The hostname.
+        hostname: String,
+    },
+    /// The hostname doesn't resolve to any address.
+    Unresolvable {
+        /// The hostname.
+        hostname: String,
+        /// Why resolving the hostname failed.
+        error: HostnameError,
+    },
+    /// The hostname resolves to loopback addresses only, e.g. to `127.0.1.1`
+    /// as Debian puts into `/etc/hosts`, so other machines can't reach it by
+    /// this name.
+    LoopbackOnly {
+        /// The hostname.
+        hostname: String,
+        /// The addresses the hostname resolves to.
+        addresses: Vec<IpAddr>,
+    },
+    /// A label of the hostname exceeds 63 bytes, which DNS doesn't permit.
+    LabelTooLong {
+        /// The label.
+        label: String,
+    },
+    /// The hostname of the kernel differs from `/etc/hostname`, so it'll
+    /// change with the next reboot.
+    EtcHostnameMismatch {
+        /// The hostname of the kernel.
+        kernel: String,
+        /// The hostname in `/etc/hostname`.
+        etc_hostname: String,
+    },
+}
+
+impl fmt::Display for Finding {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Finding::Localhost { hostname } => {
+                write!(f, "hostname {} is a placeholder", hostname)
+            }
+            Finding::Unresolvable { hostname, error } => {
+                write!(f, "hostname {} does not resolve: {}", hostname, error)
+            }
+            Finding::LoopbackOnly {
+                hostname,
+                addresses,
+            } => {
+                write!(
+                    f,
+                    "hostname {} resolves to loopback addresses only:",
+                    hostname
+                )?;
+                for address in addresses {
+                    write!(f, " {}", address)?;
+                }
+                Ok(())
+            }
+            Finding::LabelTooLong { label } => write!(
+                f,
+                "label {} exceeds {} bytes ({} bytes)",
+                label,
+                MAX_LABEL_LEN,
+                label.len()
+            ),
+            Finding::EtcHostnameMismatch {
+                kernel,
+                etc_hostname,
+            } => write!(
+                f,
+                "hostname {} differs from {} in /etc/hostname",
+                kernel, etc_hostname
+            ),
+        }
+    }
+}
+
+/// Check the hostname of the current machine for common misconfigurations.
+///
+/// Report a [`Finding`] if the hostname
+///
+/// * is `localhost` or `localhost.localdomain`,
+/// * doesn't resolve, or resolves to loopback addresses only,
+/// * has a label longer than 63 bytes, or
+/// * differs from `/etc/hostname` on Linux.
+///
+/// Return an empty list if all is well.  This resolves the hostname, so it
+/// may block for a while if the resolver is slow; use [`diagnose_timeout()`]
+/// to bound the time it takes.  Fail if
+/// [`try_gethostname()`](crate::try_gethostname) fails.
+///
+/// ```
+/// for finding in gethostname::diagnose().unwrap() {
+///     println!("warning: {}", finding);
+/// }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn diagnose() -> Result<Vec<Finding>, HostnameError> {
+    diagnose_with(&SystemBackend)
+}
+
+/// Find common misconfigurations of the hostname within `timeout`.
+///
+/// Like [`diagnose()`], but give up resolving the hostname after `timeout`,
+/// and report that it doesn't resolve.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn diagnose_timeout(timeout: Duration) -> Result<Vec<Finding>, HostnameError> {
+    diagnose_with(&TimeoutBackend::new(SystemBackend, timeout))
+}
+
+/// Like [`diagnose()`], but resolve the hostname with `backend`.
+///
+/// Wrap `backend` in a [`TimeoutBackend`] to bound the time this takes.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn diagnose_with(backend: &dyn ResolverBackend) -> Result<Vec<Finding>, HostnameError> {
+    let hostname = crate::try_gethostname()?.to_string_lossy().into_owned();
+    let addresses = backend.addresses(&hostname);
+    Ok(findings(&hostname, addresses, etc_hostname().as_deref()))
+}
+
+#[cfg(target_os = "linux")]
+fn etc_hostname() -> Option<String> {
+    let contents = std::fs::read_to_string("/etc/hostname").ok()?;
+    parse_etc_hostname(&contents)
+}
+
+#[cfg(not(target_os = "linux"))]
+fn etc_hostname() -> Option<String> {
+    None
+}
+
+/// Get the hostname from the contents of `/etc/hostname`, skipping comments
+/// and empty lines as systemd does.
+#[cfg(any(target_os = "linux", test))]
+fn parse_etc_hostname(contents: &str) -> Option<String> {
+    contents
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+}
+
+fn findings(
+    hostname: &str,
+    addresses: Result<Vec<IpAddr>, HostnameError>,
+    etc_hostname: Option<&str>,
+) -> Vec<Finding> {
+    let mut findings = Vec::new();
+    let is_localhost = ["localhost", "localhost.localdomain"]
+        .iter()
+        .any(|name| crate::case::eq_folded(hostname, name));
+    if is_localhost {
+        findings.push(Finding::Localhost {
+            hostname: hostname.to_string(),
+        });
+    }
+    match addresses {
+        Err(error) => findings.push(Finding::Unresolvable {
+            hostname: hostname.to_string(),
+            error,
+        }),
+        Ok(addresses) if addresses.is_empty() => findings.push(Finding::Unresolvable {
+            hostname: hostname.to_string(),
+            error: HostnameError::NotFound { what: "address" },
+        }),
+        // localhost resolving to loopback is expected; we reported it already
+        Ok(addresses) if !is_localhost && addresses.iter().all(IpAddr::is_loopback) => findings
+            .push(Finding::LoopbackOnly {
+                hostname: hostname.to_string(),
+                addresses,
+            }),
+        Ok(_) => {}
+    }
+    findings.extend(
+        hostname
+            .split('.')
+            .filter(|label| MAX_LABEL_LEN < label.len())
+            .map(|label| Finding::LabelTooLong {
+                label: label.to_string(),
+            }),
+    );
+    if let Some(etc_hostname) = etc_hostname {
+        if etc_hostname != hostname {
+            findings.push(Finding::EtcHostnameMismatch {
+                kernel: hostname.to_string(),
+                etc_hostname: etc_hostname.to_string(),
+            });
+        }
+    }
+    findings
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{findings, parse_etc_hostname, Finding};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    fn address(a: u8, b: u8, c: u8, d: u8) -> IpAddr {
+        IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+    }
+
+    #[test]
+    fn healthy_hostname() {
+        let addresses = Ok(vec![address(192, 0, 2, 1)]);
+        assert_eq!(findings("foo", addresses, Some("foo")), Vec::new());
+    }
+
+    #[test]
+    fn localhost() {
+        for hostname in &["localhost", "localhost.localdomain"] {
+            let addresses = Ok(vec![address(127, 0, 0, 1)]);
+            assert_eq!(
+                findings(hostname, addresses, None),
+                vec![Finding::Localhost {
+                    hostname: hostname.to_string()
+                }]
+            );
+        }
+    }
+
+    #[test]
+    fn unresolvable() {
+        let error = HostnameError::ResolutionFailed {
+            code: -2,
+            message: "Name or service not known".to_string(),
+        };
+        assert_eq!(
+            findings("foo", Err(error.clone()), None),
+            vec![Finding::Unresolvable {
+                hostname: "foo".to_string(),
+                error
+            }]
+        );
+        assert_eq!(
+            findings("foo", Ok(Vec::new()), None),
+            vec![Finding::Unresolvable {
+                hostname: "foo".to_string(),
+                error: HostnameError::NotFound { what: "address" }
+            }]
+        );
+    }
+
+    #[test]
+    fn loopback_only() {
+        let addresses = vec![address(127, 0, 1, 1), IpAddr::V6(Ipv6Addr::LOCALHOST)];
+        let finding = &findings("foo", Ok(addresses.clone()), None)[0];
+        assert_eq!(
+            finding,
+            &Finding::LoopbackOnly {
+                hostname: "foo".to_string(),
+                addresses
+            }
+        );
+        assert_eq!(
+            finding.to_string(),
+            "hostname foo resolves to loopback addresses only: 127.0.1.1 ::1"
+        );
+    }
+
+    #[test]
+    fn label_too_long() {
+        let label = "a".repeat(64);
+        let hostname = format!("{}.example.com", label);
+        let addresses = Ok(vec![address(192, 0, 2, 1)]);
+        assert_eq!(
+            findings(&hostname, addresses, None),
+            vec![Finding::LabelTooLong { label }]
+        );
+    }
+
+    #[test]
+    fn etc_hostname_mismatch() {
+        let addresses = Ok(vec![address(192, 0, 2, 1)]);
+        let finding = &findings("foo", addresses, Some("bar"))[0];
+        assert_eq!(
+            finding,
+            &Finding::EtcHostnameMismatch {
+                kernel: "foo".to_string(),
+                etc_hostname: "bar".to_string()
+            }
+        );
+        assert_eq!(
+            finding.to_string(),
+            "hostname foo differs from bar in /etc/hostname"
+        );
+    }
+
+    #[test]
+    fn etc_hostname_contents() {
+        assert_eq!(
+            parse_etc_hostname("# Managed by ansible\n\n  foo  \n"),
+            Some("foo".to_string())
+        );
+        assert_eq!(parse_etc_hostname("# nothing\n"), None);
+    }
+
+    #[test]
+    fn diagnose_current_machine() {
+        assert!(super::diagnose().is_ok());
+    }
+}
@@ -0,0 +1,234 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Diagnostics for the [miette] crate.
+//!
+//! [miette]: https://docs.rs/miette
+
+use std::fmt;
+
+use miette::{Diagnostic, LabeledSpan, SourceSpan};
+
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+
+impl HostnameError {
+    /// Attach the `input` which failed to validate to this error.
+    ///
+    /// The result is a [miette] diagnostic which points at the offending
+    /// characters in `input`:
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let input = "foo.b_r.example";
+    /// let error = input.parse::<Hostname>().unwrap_err().with_input(input);
+    /// let report = miette::Report::new(error);
+    /// println!("{:?}", report);
+    /// ```
+    ///
+    /// [miette]: https://docs.rs/miette
+    pub fn with_input<S: Into<String>>(self, input: S) -> InvalidHostname {
+        InvalidHostname {
+            input: input.into(),
+            error: self,
+        }
+    }
+}
+
+impl Diagnostic for HostnameError {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let code = match self {
+            HostnameError::SyscallFailed { .. } => "gethostname::syscall_failed",
+            HostnameError::BufferTooSmall => "gethostname::buffer_too_small",
+            HostnameError::InvalidName { .. } => "gethostname::invalid_name",
+            HostnameError::Unsupported => "gethostname::unsupported",
+            HostnameError::ResolutionFailed { .. } => "gethostname::resolution_failed",
+            HostnameError::ServiceFailed { .. } => "gethostname::service_failed",
+            HostnameError::NotFound { .. } => "gethostname::not_found",
+            HostnameError::Cancelled => "gethostname::cancelled",
+            HostnameError::ElevationRequired => "gethostname::elevation_required",
+        };
+        Some(Box::new(code))
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        let help = match self {
+            HostnameError::InvalidName { reason, .. } => match reason {
+                InvalidNameReason::InvalidCharacter(_) => {
+                    "hostnames may only contain letters, digits, hyphens and dots"
+                }
+                InvalidNameReason::LeadingHyphen | InvalidNameReason::TrailingHyphen => {
+                    "labels must start and end with a letter or digit"
+                }
+                InvalidNameReason::LeadingDigit => "labels must start with a letter",
+                InvalidNameReason::EmptyLabel => "remove the superfluous dot",
+                InvalidNameReason::NumericTopLabel => {
+                    "the last label must contain at least one letter"
+                }
+                InvalidNameReason::InvalidRange => {
+                    "ranges must ascend, like `[001-016]`, and cover at most 65536 hostnames"
+                }
+                InvalidNameReason::TooLongForPlatform => "use a shorter name, or a single label",
+                InvalidNameReason::NetbiosTooLong => {
+                    "Windows truncates longer names to their first 15 characters for NetBIOS"
+                }
+                _ => return None,
+            },
+            HostnameError::ElevationRequired => "run the program as administrator",
+            _ => return None,
+        };
+        Some(Box::new(help))
+    }
+}
+
+/// A hostname which failed to validate, together with its input.
+///
+/// Unlike [`HostnameError`] this type carries the rejected input, and labels
+/// the offending characters in its [`Diagnostic`] implementation.
+///
+/// Create it with [`HostnameError::with_input()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InvalidHostname {
+    input: String,
+    error: HostnameError,
+}
+
+impl InvalidHostname {
+    /// The input which failed to validate.
+    pub fn input(&self) -> &str {
+        &self.input
+    }
+
+    /// The underlying error.
+    pub fn error(&self) -> &HostnameError {
+        &self.error
+    }
+
+    /// The span of the offending characters in the input, if any.
+    fn span(&self) -> Option<SourceSpan> {
+        let (reason, position) = match self.error {
+            HostnameError::InvalidName { reason, position } => (reason, position),
+            _ => return None,
+        };
+        let rest = self.input.get(position..).unwrap_or("");
+        let len = match reason {
+            InvalidNameReason::InvalidCharacter(c) => c.len_utf8(),
+            InvalidNameReason::LeadingHyphen
+            | InvalidNameReason::TrailingHyphen
+            | InvalidNameReason::LeadingDigit => 1,
+            InvalidNameReason::LabelTooLong => rest.find('.').unwrap_or(rest.len()),
+            InvalidNameReason::TooLong
+            | InvalidNameReason::NumericTopLabel
+            | InvalidNameReason::TooLongForPlatform
+            | InvalidNameReason::NetbiosTooLong => rest.len(),
+            InvalidNameReason::InvalidRange => rest.find([',', ']']).unwrap_or(rest.len()),
+            _ => 0,
+        };
+        Some(SourceSpan::new(
+            position.min(self.input.len()).into(),
+            len.min(rest.len()),
+        ))
+    }
+}
+
+impl fmt::Display for InvalidHostname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match &self.error {
+            HostnameError::InvalidName { reason, .. } => {
+                write!(f, "invalid hostname {:?}: {}", self.input, reason)
+            }
+            error => error.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for InvalidHostname {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+impl Diagnostic for InvalidHostname {
+    fn code<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.code()
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn fmt::Display + 'a>> {
+        self.error.help()
+    }
+
+    fn source_code(&self) -> Option<&dyn miette::SourceCode> {
+        Some(&self.input)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = LabeledSpan> + '_>> {
+        let reason = match self.error {
+            HostnameError::InvalidName { reason, .. } => reason,
+            _ => return None,
+        };
+        let label = LabeledSpan::new_with_span(Some(reason.to_string()), self.span()?);
+        Some(Box::new(std::iter::once(label)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Hostname;
+    use miette::{Diagnostic, LabeledSpan};
+    use pretty_assertions::assert_eq;
+
+    fn labels(input: &str) -> Vec<(usize, usize)> {
+        input
+            .parse::<Hostname>()
+            .unwrap_err()
+            .with_input(input)
+            .labels()
+            .unwrap()
+            .map(|label: LabeledSpan| (label.offset(), label.len()))
+            .collect()
+    }
+
+    #[test]
+    fn labels_point_at_offending_characters() {
+        assert_eq!(labels("foo.b_r"), vec![(5, 1)]);
+        assert_eq!(labels("foo.bü_r"), vec![(7, 1)]);
+        assert_eq!(labels("foo.-bar"), vec![(4, 1)]);
+        assert_eq!(labels("foo-.bar"), vec![(3, 1)]);
+        assert_eq!(labels("foo..bar"), vec![(4, 0)]);
+        assert_eq!(labels("10.0.0.1"), vec![(7, 1)]);
+        assert_eq!(
+            labels(&format!("foo.{}.bar", "a".repeat(70))),
+            vec![(67, 7)]
+        );
+        assert_eq!(labels(""), vec![(0, 0)]);
+    }
+
+    #[test]
+    fn diagnostic_code_and_help() {
+        let error = "foo_bar"
+            .parse::<Hostname>()
+            .unwrap_err()
+            .with_input("foo_bar");
+        assert_eq!(
+            error.code().unwrap().to_string(),
+            "gethostname::invalid_name"
+        );
+        assert!(error.help().is_some());
+        assert_eq!(
+            error.to_string(),
+            "invalid hostname \"foo_bar\": invalid character '_'"
+        );
+    }
+}
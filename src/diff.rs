@@ -0,0 +1,167 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Classify changes of the hostname.
+
+use std::fmt;
+
+use crate::case::fold_case;
+
+/// How a hostname changed, see [`classify_change()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum HostnameChange {
+    /// The hostname didn't change at all.
+    Unchanged,
+    /// Only the case of the hostname changed, e.g. from `web-01` to `Web-01`.
+    ///
+    /// Hostnames are case-insensitive, so the name still refers to the same
+    /// host.
+    CaseOnly,
+    /// The first label stayed the same, but the domain changed, e.g. from
+    /// `web-01.staging.example.com` to `web-01.example.com`, or from `web-01`
+    /// to `web-01.example.com`.
+    DomainChanged,
+    /// The domain stayed the same, but the first label changed, e.g. from
+    /// `web-01.example.com` to `web-02.example.com`, or from `web-01` to
+    /// `web-02`.
+    LabelChanged,
+    /// The first label and the domain changed.
+    Renamed,
+}
+
+impl HostnameChange {
+    /// Whether the hostname refers to a different host after this change.
+    ///
+    /// Return `false` for [`HostnameChange::Unchanged`] and
+    /// [`HostnameChange::CaseOnly`], and `true` otherwise, e.g. to decide
+    /// whether to register a service under the new hostname.
+    pub fn is_significant(&self) -> bool {
+        !matches!(self, HostnameChange::Unchanged | HostnameChange::CaseOnly)
+    }
+}
+
+impl fmt::Display for HostnameChange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            HostnameChange::Unchanged => "unchanged",
+            HostnameChange::CaseOnly => "case only",
+            HostnameChange::DomainChanged => "domain changed",
+            HostnameChange::LabelChanged => "label changed",
+            HostnameChange::Renamed => "renamed",
+        })
+    }
+}
+
+/// Classify the change of a hostname from `old` to `new`.
+///
+/// Compare both names with their case [folded](crate::fold_case) and without
+/// trailing dots, and tell whether only the case, the domain, or the first
+/// label changed, or both the first label and the domain.  Use this to decide
+/// whether a change needs any action, e.g. on changes from
+/// [`HostnameChanges`](crate::HostnameChanges).
+///
+/// ```
+/// use gethostname::{classify_change, HostnameChange};
+///
+/// assert_eq!(classify_change("web-01.example.com", "WEB-01.example.com"), HostnameChange::CaseOnly);
+/// assert_eq!(classify_change("web-01.example.com", "web-01.example.org"), HostnameChange::DomainChanged);
+/// assert_eq!(classify_change("web-01.example.com", "web-02.example.com"), HostnameChange::LabelChanged);
+/// assert_eq!(classify_change("web-01.example.com", "db-01.example.org"), HostnameChange::Renamed);
+/// assert!(!classify_change("web-01", "Web-01").is_significant());
+/// ```
+pub fn classify_change(old: &str, new: &str) -> HostnameChange {
+    if old == new {
+        return HostnameChange::Unchanged;
+    }
+    let old = fold_case(old.strip_suffix('.').unwrap_or(old));
+    let new = fold_case(new.strip_suffix('.').unwrap_or(new));
+    if old == new {
+        return HostnameChange::CaseOnly;
+    }
+    let (old_label, old_domain) = split_first_label(&old);
+    let (new_label, new_domain) = split_first_label(&new);
+    match (old_label == new_label, old_domain == new_domain) {
+        (true, _) => HostnameChange::DomainChanged,
+        (false, true) => HostnameChange::LabelChanged,
+        (false, false) => HostnameChange::Renamed,
+    }
+}
+
+/// Split `name` into its first label and its domain, if any.
+fn split_first_label(name: &str) -> (&str, Option<&str>) {
+    match name.split_once('.') {
+        Some((label, domain)) => (label, Some(domain)),
+        None => (name, None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{classify_change, HostnameChange};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn unchanged() {
+        assert_eq!(classify_change("foo", "foo"), HostnameChange::Unchanged);
+        assert!(!HostnameChange::Unchanged.is_significant());
+    }
+
+    #[test]
+    fn case_only() {
+        assert_eq!(
+            classify_change("foo.example.com", "Foo.EXAMPLE.com"),
+            HostnameChange::CaseOnly
+        );
+        assert_eq!(
+            classify_change("foo.example.com.", "foo.example.com"),
+            HostnameChange::CaseOnly
+        );
+        assert!(!HostnameChange::CaseOnly.is_significant());
+    }
+
+    #[test]
+    fn domain_changed() {
+        for (old, new) in &[
+            ("foo.example.com", "foo.example.org"),
+            ("foo", "foo.example.com"),
+            ("Foo.example.com", "foo"),
+        ] {
+            assert_eq!(classify_change(old, new), HostnameChange::DomainChanged);
+        }
+    }
+
+    #[test]
+    fn label_changed() {
+        assert_eq!(
+            classify_change("foo.example.com", "bar.Example.com"),
+            HostnameChange::LabelChanged
+        );
+        assert_eq!(classify_change("foo", "bar"), HostnameChange::LabelChanged);
+    }
+
+    #[test]
+    fn renamed() {
+        assert_eq!(
+            classify_change("foo.example.com", "bar.example.org"),
+            HostnameChange::Renamed
+        );
+        assert_eq!(
+            classify_change("foo", "bar.example.org"),
+            HostnameChange::Renamed
+        );
+        assert!(HostnameChange::Renamed.is_significant());
+        assert_eq!(HostnameChange::Renamed.to_string(), "renamed");
+    }
+}
@@ -0,0 +1,264 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Plan changes to the system without performing them.
+
+use std::cell::RefCell;
+use std::fmt;
+use std::path::PathBuf;
+
+use crate::error::HostnameError;
+
+/// A change to the system which a setter of this crate performs.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Change {
+    /// Call a system function, e.g. `sethostname` or `SetComputerNameExW`.
+    Syscall {
+        /// The name of the function.
+        call: &'static str,
+        /// The argument of the call, e.g. the new hostname.
+        argument: String,
+    },
+    /// Atomically replace a file.
+    WriteFile {
+        /// The path of the file.
+        path: PathBuf,
+        /// The new contents of the file.
+        contents: String,
+    },
+    /// Remove a file, e.g. to roll back the creation of a file.
+    RemoveFile {
+        /// The path of the file.
+        path: PathBuf,
+    },
+    /// Call a method of a system service over D-Bus.
+    DbusCall {
+        /// The bus name of the service.
+        destination: &'static str,
+        /// The name of the method.
+        method: &'static str,
+        /// The argument of the method.
+        argument: String,
+        /// Whether the service may ask the user to authenticate.
+        interactive: bool,
+    },
+    /// Set or remove a system preference on macOS.
+    Preference {
+        /// The key of the preference, e.g. `LocalHostName`.
+        key: &'static str,
+        /// The new value, or `None` to remove the preference.
+        value: Option<String>,
+    },
+}
+
+impl Change {
+    /// The new value, e.g. the new hostname or the new contents of a file.
+    ///
+    /// `None` if the change removes a preference or a file.
+    pub fn value(&self) -> Option<&str> {
+        match self {
+            Change::Syscall { argument, .. } | Change::DbusCall { argument, .. } => Some(argument),
+            Change::WriteFile { contents, .. } => Some(contents),
+            Change::RemoveFile { .. } => None,
+            Change::Preference { value, .. } => value.as_deref(),
+        }
+    }
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Change::Syscall { call, argument } => write!(f, "{}({:?})", call, argument),
+            Change::WriteFile { path, contents } => {
+                write!(f, "write {:?} to {}", contents, path.display())
+            }
+            Change::RemoveFile { path } => write!(f, "remove {}", path.display()),
+            Change::DbusCall {
+                destination,
+                method,
+                argument,
+                interactive,
+            } => write!(
+                f,
+                "call {}.{}({:?}, {})",
+                destination, method, argument, interactive
+            ),
+            Change::Preference { key, value: None } => write!(f, "remove preference {}", key),
+            Change::Preference {
+                key,
+                value: Some(value),
+            } => write!(f, "set preference {} to {:?}", key, value),
+        }
+    }
+}
+
+thread_local! {
+    static RECORDED: RefCell<Option<Vec<Change>>> = const { RefCell::new(None) };
+}
+
+/// The changes which setters would have performed, see [`DryRun::record()`].
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct DryRun {
+    changes: Vec<Change>,
+}
+
+impl DryRun {
+    /// Run `f` in dry-run mode, and return its result and its changes.
+    ///
+    /// While `f` runs, all setters of this crate which `f` calls on the current
+    /// thread, e.g. `set_hostname_persistent()` on Linux or
+    /// `rename_computer()` on Windows, record the system calls, file writes,
+    /// D-Bus calls and preference changes they would perform, in order,
+    /// instead of performing them, and succeed.  Setters still read the
+    /// system, e.g. to decide whether to go through systemd-hostnamed, so the
+    /// plan is exactly what the setters would do right now, and setters still
+    /// fail for invalid names.  They don't fail for insufficient privileges
+    /// though, because only the system calls which they skip check these; ask
+    /// [`can_set_hostname()`](crate::can_set_hostname) to find out whether
+    /// the plan would succeed.
+    ///
+    /// This lets configuration management tools show a plan before applying
+    /// it.
+    ///
+    /// ```
+    /// # #[cfg(target_os = "linux")] {
+    /// use gethostname::{set_hostname_persistent, DryRun};
+    ///
+    /// let hostname = "build-agent-7".parse().unwrap();
+    /// let (result, plan) = DryRun::record(|| set_hostname_persistent(&hostname));
+    /// assert!(result.is_ok());
+    /// for change in plan.changes() {
+    ///     println!("{}", change);
+    /// }
+    /// # }
+    /// ```
+    pub fn record<R>(f: impl FnOnce() -> R) -> (R, DryRun) {
+        let previous = RECORDED.with(|recorded| recorded.replace(Some(Vec::new())));
+        let result = f();
+        let changes = RECORDED.with(|recorded| recorded.replace(previous));
+        let dry_run = DryRun {
+            changes: changes.unwrap_or_default(),
+        };
+        (result, dry_run)
+    }
+
+    /// The changes, in the order setters would perform them.
+    pub fn changes(&self) -> &[Change] {
+        &self.changes
+    }
+
+    /// Whether the setters would change nothing.
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for DryRun {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+        Ok(())
+    }
+}
+
+impl IntoIterator for DryRun {
+    type Item = Change;
+    type IntoIter = std::vec::IntoIter<Change>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.changes.into_iter()
+    }
+}
+
+/// Perform `change` with `apply`, or only record it in dry-run mode.
+///
+/// Tell [observers](crate::on_hostname_set) about the change if `apply`
+/// succeeds, with the `old` value from before the change.
+#[cfg_attr(
+    not(any(
+        target_os = "linux",
+        windows,
+        all(target_os = "macos", feature = "macos-setters")
+    )),
+    allow(dead_code)
+)]
+pub(crate) fn perform(
+    change: Change,
+    old: impl FnOnce() -> Option<String>,
+    apply: impl FnOnce() -> Result<(), HostnameError>,
+) -> Result<(), HostnameError> {
+    let recorded = RECORDED.with(|recorded| match recorded.borrow_mut().as_mut() {
+        Some(changes) => {
+            changes.push(change.clone());
+            true
+        }
+        None => false,
+    });
+    if recorded {
+        return Ok(());
+    }
+    // Only find the old value if anyone's interested
+    let old = if crate::observe::has_observers() {
+        old()
+    } else {
+        None
+    };
+    apply()?;
+    crate::observe::notify(change, old);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{perform, Change, DryRun};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+
+    fn change(argument: &str) -> Change {
+        Change::Syscall {
+            call: "sethostname",
+            argument: argument.to_string(),
+        }
+    }
+
+    #[test]
+    fn records_changes_instead_of_applying_them() {
+        let (result, dry_run) = DryRun::record(|| {
+            perform(change("foo"), || None, || Err(HostnameError::Unsupported))?;
+            perform(change("bar"), || None, || Err(HostnameError::Unsupported))
+        });
+        assert_eq!(result, Ok(()));
+        assert_eq!(dry_run.changes(), &[change("foo"), change("bar")]);
+        assert_eq!(
+            dry_run.to_string(),
+            "sethostname(\"foo\")\nsethostname(\"bar\")\n"
+        );
+    }
+
+    #[test]
+    fn applies_changes_outside_of_dry_run() {
+        assert_eq!(
+            perform(change("foo"), || None, || Err(HostnameError::Unsupported)),
+            Err(HostnameError::Unsupported)
+        );
+        let (_, dry_run) = DryRun::record(|| ());
+        assert!(dry_run.is_empty());
+        assert_eq!(
+            perform(change("foo"), || None, || Err(HostnameError::Unsupported)),
+            Err(HostnameError::Unsupported)
+        );
+    }
+}
@@ -0,0 +1,357 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The error type of this crate.
+
+use std::fmt;
+use std::io;
+
+use crate::hostname::InvalidNameReason;
+
+/// An error of a fallible operation of this crate.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum HostnameError {
+    /// A system call failed with an OS error code.
+    ///
+    /// On Unix `errno` is the value of `errno`, on Windows the result of
+    /// `GetLastError()`.  The core functions like
+    /// [`try_gethostname()`](crate::try_gethostname) also tell which call
+    /// failed, and the size of the buffer they passed to it, so that bug
+    /// reports carry enough information to act on.
+    SyscallFailed {
+        /// The OS error code.
+        errno: i32,
+        /// The name of the system call which failed, e.g. `gethostname` or
+        /// `GetComputerNameExW`, if known.
+        call: Option<&'static str>,
+        /// The size of the buffer passed to the failed call, if any: in bytes
+        /// on Unix, and in wide characters on Windows.
+        buffer_size: Option<usize>,
+    },
+    /// The buffer for the result of a system call was too small.
+    BufferTooSmall,
+    /// A name is not a valid hostname.
+    InvalidName {
+        /// Why the name is invalid.
+        reason: InvalidNameReason,
+        /// The byte offset in the name at which it became invalid.
+        position: usize,
+    },
+    /// The operation is not supported on this platform.
+    Unsupported,
+    /// Resolving the hostname failed.
+    ResolutionFailed {
+        /// The error code of the resolver, e.g. `EAI_NONAME`, or the HTTP
+        /// status of a metadata service.
+        code: i32,
+        /// The error message of the resolver.
+        message: String,
+    },
+    /// A system service failed, or is not available.
+    ServiceFailed {
+        /// The name of the service.
+        service: &'static str,
+        /// The error message.
+        message: String,
+    },
+    /// The requested information does not exist on this system.
+    NotFound {
+        /// What we looked for.
+        what: &'static str,
+    },
+    /// The operation was cancelled with a
+    /// [`CancellationToken`](crate::CancellationToken).
+    Cancelled,
+    /// The operation needs an elevated process on Windows, but the current
+    /// process runs with a limited token.
+    ElevationRequired,
+}
+
+impl HostnameError {
+    /// Create an error from the last OS error.
+    #[inline]
+    pub(crate) fn last_os_error() -> HostnameError {
+        HostnameError::from_io(&io::Error::last_os_error())
+    }
+
+    /// Create an error for a failed system call with the given OS error code.
+    #[inline]
+    pub(crate) fn from_errno(errno: i32) -> HostnameError {
+        HostnameError::SyscallFailed {
+            errno,
+            call: None,
+            buffer_size: None,
+        }
+    }
+
+    /// Record the system `call` and the `buffer_size` it got in this error.
+    ///
+    /// Return other errors unchanged.
+    #[inline]
+    pub(crate) fn in_call(self, call: &'static str, buffer_size: Option<usize>) -> HostnameError {
+        match self {
+            HostnameError::SyscallFailed { errno, .. } => HostnameError::SyscallFailed {
+                errno,
+                call: Some(call),
+                buffer_size,
+            },
+            error => error,
+        }
+    }
+
+    /// Create an error for a failed allocation.
+    #[inline]
+    pub(crate) fn out_of_memory() -> HostnameError {
+        HostnameError::from_errno(ENOMEM)
+    }
+
+    /// Create an error for an operation which timed out.
+    pub(crate) fn timed_out() -> HostnameError {
+        HostnameError::from_errno(ETIMEDOUT)
+    }
+
+    /// Create an error from an I/O error.
+    ///
+    /// Use the OS error code of `error` if any; otherwise this is an error we
+    /// created ourselves, which we don't expect, so treat it as the OS failing
+    /// with `EIO`.
+    #[inline]
+    pub(crate) fn from_io(error: &io::Error) -> HostnameError {
+        HostnameError::from_errno(error.raw_os_error().unwrap_or(EIO))
+    }
+
+    /// Whether this error is likely transient, so that trying again may succeed.
+    ///
+    /// This holds for temporary failures of the resolver, i.e. `EAI_AGAIN` on
+    /// Unix or `WSATRY_AGAIN` on Windows, and for system calls which timed out
+    /// or would block.  Early during boot the resolver often fails like this
+    /// until the network is up.
+    pub fn is_transient(&self) -> bool {
+        match self {
+            HostnameError::ResolutionFailed { code, .. } => *code == EAI_AGAIN,
+            HostnameError::SyscallFailed { errno, .. } => *errno == ETIMEDOUT || *errno == EAGAIN,
+            _ => false,
+        }
+    }
+
+    /// Create an error for an invalid name.
+    pub(crate) fn invalid_name(reason: InvalidNameReason, position: usize) -> HostnameError {
+        HostnameError::InvalidName { reason, position }
+    }
+}
+
+#[cfg(not(windows))]
+const EIO: i32 = libc::EIO;
+#[cfg(not(windows))]
+const ENOMEM: i32 = libc::ENOMEM;
+#[cfg(not(windows))]
+const ETIMEDOUT: i32 = libc::ETIMEDOUT;
+#[cfg(not(windows))]
+const EAGAIN: i32 = libc::EAGAIN;
+#[cfg(not(windows))]
+const EAI_AGAIN: i32 = libc::EAI_AGAIN;
+// ERROR_IO_DEVICE
+#[cfg(windows)]
+const EIO: i32 = 1117;
+// ERROR_NOT_ENOUGH_MEMORY
+#[cfg(windows)]
+const ENOMEM: i32 = 8;
+// WSAETIMEDOUT
+#[cfg(windows)]
+const ETIMEDOUT: i32 = 10060;
+// WSAEWOULDBLOCK
+#[cfg(windows)]
+const EAGAIN: i32 = 10035;
+// WSATRY_AGAIN
+#[cfg(windows)]
+const EAI_AGAIN: i32 = 11002;
+
+impl fmt::Display for HostnameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            HostnameError::SyscallFailed {
+                errno,
+                call,
+                buffer_size,
+            } => {
+                f.write_str(call.unwrap_or("system call"))?;
+                f.write_str(" failed")?;
+                if let Some(size) = buffer_size {
+                    write!(f, " with buffer of size {}", size)?;
+                }
+                write!(f, ": {}", io::Error::from_raw_os_error(*errno))
+            }
+            HostnameError::BufferTooSmall => f.write_str("buffer too small"),
+            HostnameError::InvalidName { reason, position } => {
+                write!(f, "invalid hostname at {}: {}", position, reason)
+            }
+            HostnameError::Unsupported => f.write_str("not supported on this platform"),
+            HostnameError::ResolutionFailed { code, message } => {
+                write!(f, "failed to resolve hostname: {} ({})", message, code)
+            }
+            HostnameError::ServiceFailed { service, message } => {
+                write!(f, "{} failed: {}", service, message)
+            }
+            HostnameError::NotFound { what } => write!(f, "{} not found", what),
+            HostnameError::Cancelled => f.write_str("operation cancelled"),
+            HostnameError::ElevationRequired => {
+                f.write_str("operation requires an elevated process")
+            }
+        }
+    }
+}
+
+impl std::error::Error for HostnameError {}
+
+impl From<HostnameError> for io::Error {
+    /// Convert a [`HostnameError`] into an I/O error.
+    ///
+    /// Turn [`HostnameError::SyscallFailed`] without a call into an I/O error
+    /// with the same raw OS error code.  Wrap all other errors, with a
+    /// matching [`io::ErrorKind`], so that [`io::Error::get_ref()`] gets them
+    /// back, e.g. with the call and the buffer size of a failed system call.
+    fn from(error: HostnameError) -> Self {
+        let kind = match &error {
+            HostnameError::SyscallFailed {
+                errno,
+                call: None,
+                buffer_size: None,
+            } => return io::Error::from_raw_os_error(*errno),
+            HostnameError::SyscallFailed { errno, .. } => {
+                io::Error::from_raw_os_error(*errno).kind()
+            }
+            HostnameError::BufferTooSmall => io::ErrorKind::InvalidInput,
+            HostnameError::InvalidName { .. } => io::ErrorKind::InvalidData,
+            HostnameError::Unsupported => io::ErrorKind::Unsupported,
+            HostnameError::NotFound { .. } => io::ErrorKind::NotFound,
+            HostnameError::Cancelled => io::ErrorKind::Interrupted,
+            HostnameError::ElevationRequired => io::ErrorKind::PermissionDenied,
+            HostnameError::ResolutionFailed { .. } | HostnameError::ServiceFailed { .. } => {
+                io::ErrorKind::Other
+            }
+        };
+        io::Error::new(kind, error)
+    }
+}
+
+impl From<io::Error> for HostnameError {
+    /// Convert an I/O error into a [`HostnameError`].
+    ///
+    /// Turn I/O errors from the operating system into
+    /// [`HostnameError::SyscallFailed`] with the same OS error code, and
+    /// unwrap [`HostnameError`]s which were converted into I/O errors before.
+    /// Treat all other I/O errors as `EIO`.
+    fn from(error: io::Error) -> Self {
+        if error.raw_os_error().is_none() {
+            if let Some(inner) = error
+                .get_ref()
+                .and_then(|e| e.downcast_ref::<HostnameError>())
+            {
+                return inner.clone();
+            }
+        }
+        HostnameError::from_io(&error)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostnameError;
+    use crate::InvalidNameReason;
+    use pretty_assertions::assert_eq;
+    use std::io;
+
+    #[test]
+    fn from_io_uses_os_error_code() {
+        let error = io::Error::from_raw_os_error(2);
+        assert_eq!(HostnameError::from_io(&error), HostnameError::from_errno(2));
+    }
+
+    #[test]
+    fn display_syscall_context() {
+        let message = io::Error::from_raw_os_error(2).to_string();
+        assert_eq!(
+            HostnameError::from_errno(2).to_string(),
+            format!("system call failed: {}", message)
+        );
+        assert_eq!(
+            HostnameError::from_errno(2)
+                .in_call("gethostname", Some(65))
+                .to_string(),
+            format!("gethostname failed with buffer of size 65: {}", message)
+        );
+        assert_eq!(
+            HostnameError::BufferTooSmall.in_call("gethostname", None),
+            HostnameError::BufferTooSmall
+        );
+    }
+
+    #[test]
+    fn into_io_error() {
+        let error = io::Error::from(HostnameError::from_errno(2));
+        assert_eq!(error.raw_os_error(), Some(2));
+        let failed = HostnameError::from_errno(2).in_call("gethostname", Some(65));
+        let error = io::Error::from(failed.clone());
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        let inner = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<HostnameError>());
+        assert_eq!(inner, Some(&failed));
+        let error = io::Error::from(HostnameError::Unsupported);
+        assert_eq!(error.kind(), io::ErrorKind::Unsupported);
+        let error = io::Error::from(HostnameError::NotFound { what: "FQDN" });
+        assert_eq!(error.kind(), io::ErrorKind::NotFound);
+        assert_eq!(error.to_string(), "FQDN not found");
+        let error = io::Error::from(HostnameError::ElevationRequired);
+        assert_eq!(error.kind(), io::ErrorKind::PermissionDenied);
+    }
+
+    #[test]
+    fn from_io_error() {
+        assert_eq!(
+            HostnameError::from(io::Error::from_raw_os_error(2)),
+            HostnameError::from_errno(2)
+        );
+        let invalid = HostnameError::invalid_name(InvalidNameReason::Empty, 0);
+        assert_eq!(
+            HostnameError::from(io::Error::from(invalid.clone())),
+            invalid
+        );
+        let failed = HostnameError::from_errno(2).in_call("gethostname", Some(65));
+        assert_eq!(HostnameError::from(io::Error::from(failed.clone())), failed);
+    }
+
+    #[test]
+    fn transient_errors() {
+        assert!(HostnameError::from_errno(super::ETIMEDOUT).is_transient());
+        assert!(HostnameError::ResolutionFailed {
+            code: super::EAI_AGAIN,
+            message: "Temporary failure in name resolution".to_string()
+        }
+        .is_transient());
+        assert!(!HostnameError::from_errno(2).is_transient());
+        assert!(!HostnameError::Unsupported.is_transient());
+    }
+
+    #[test]
+    fn display_invalid_name() {
+        let error = HostnameError::invalid_name(InvalidNameReason::LeadingHyphen, 4);
+        assert_eq!(
+            error.to_string(),
+            "invalid hostname at 4: label starts with a hyphen"
+        );
+    }
+}
@@ -0,0 +1,199 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Integration with the [figment] crate.
+//!
+//! [`HostnameProvider`] provides the names of the current machine as
+//! configuration values, for use as defaults, or to fill placeholders like
+//! `${hostname}` in configuration files with
+//! [`HostnameProvider::interpolate()`]:
+//!
+//! ```
+//! use figment::Figment;
+//! use gethostname::figment::HostnameProvider;
+//!
+//! let figment = Figment::from(HostnameProvider::new());
+//! let hostname: String = figment.extract_inner("hostname").unwrap();
+//! assert_eq!(hostname, gethostname::gethostname().to_string_lossy());
+//!
+//! let log_file = HostnameProvider::new().interpolate("/var/log/app/${hostname}.log");
+//! assert_eq!(log_file, format!("/var/log/app/{}.log", hostname));
+//! ```
+//!
+//! [figment]: https://docs.rs/figment
+
+use std::time::Duration;
+
+use ::figment::value::{Dict, Map, Value};
+use ::figment::{Error, Metadata, Profile, Provider};
+
+/// The key of the hostname, from [`try_gethostname()`](crate::try_gethostname).
+pub const HOSTNAME: &str = "hostname";
+
+/// The key of the fully qualified domain name, from [`getfqdn()`](crate::getfqdn).
+pub const FQDN: &str = "fqdn";
+
+/// The key of the machine ID, from [`machine_id()`](crate::machine_id).
+pub const MACHINE_ID: &str = "machine_id";
+
+/// A figment provider for the names of the current machine.
+///
+/// Provide [`HOSTNAME`], [`FQDN`] and [`MACHINE_ID`] as string values in the
+/// default profile, or in the profile given to
+/// [`profile()`](HostnameProvider::profile).  Look up the names whenever
+/// figment asks for the data, and omit names which are not available, e.g.
+/// the FQDN if the hostname doesn't resolve, so that other providers can
+/// provide defaults for them.
+#[derive(Debug, Clone, PartialEq)]
+pub struct HostnameProvider {
+    profile: Profile,
+    timeout: Option<Duration>,
+}
+
+impl Default for HostnameProvider {
+    fn default() -> Self {
+        HostnameProvider {
+            profile: Profile::Default,
+            timeout: None,
+        }
+    }
+}
+
+impl HostnameProvider {
+    /// Create a provider for the default profile.
+    pub fn new() -> HostnameProvider {
+        HostnameProvider::default()
+    }
+
+    /// Provide the names in `profile`.
+    pub fn profile<P: Into<Profile>>(mut self, profile: P) -> HostnameProvider {
+        self.profile = profile.into();
+        self
+    }
+
+    /// Give up resolving the [`FQDN`] after `timeout`, and omit it, as with
+    /// [`getfqdn_timeout()`](crate::getfqdn_timeout).
+    pub fn timeout(mut self, timeout: Duration) -> HostnameProvider {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Replace the placeholders `${hostname}`, `${fqdn}` and `${machine_id}` in
+    /// `text` with the names of the current machine.
+    ///
+    /// Keep placeholders of unknown or unavailable names as they are.  Figment
+    /// doesn't interpolate values by itself; use this function on values
+    /// extracted from a figment, or on configuration files before parsing.
+    pub fn interpolate(&self, text: &str) -> String {
+        interpolate(text, &names(self.timeout))
+    }
+}
+
+impl Provider for HostnameProvider {
+    fn metadata(&self) -> Metadata {
+        Metadata::named("gethostname")
+    }
+
+    fn data(&self) -> Result<Map<Profile, Dict>, Error> {
+        let dict = names(self.timeout)
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), Value::from(value)))
+            .collect();
+        Ok(self.profile.collect(dict))
+    }
+}
+
+/// Look up all available names of the current machine.
+fn names(timeout: Option<Duration>) -> Vec<(&'static str, String)> {
+    let mut names = Vec::with_capacity(3);
+    match crate::try_gethostname() {
+        Ok(hostname) => names.push((HOSTNAME, hostname.to_string_lossy().into_owned())),
+        Err(error) => warn!("Omitting {}, hostname not available: {}", HOSTNAME, error),
+    }
+    match crate::fqdn::getfqdn_within(timeout) {
+        Ok(fqdn) => names.push((FQDN, fqdn.to_string_lossy().into_owned())),
+        Err(error) => warn!("Omitting {}, FQDN not available: {}", FQDN, error),
+    }
+    match crate::machine_id() {
+        Ok(id) => names.push((MACHINE_ID, id)),
+        Err(error) => warn!(
+            "Omitting {}, machine ID not available: {}",
+            MACHINE_ID, error
+        ),
+    }
+    names
+}
+
+/// Replace placeholders of `names` in `text`.
+fn interpolate(text: &str, names: &[(&str, String)]) -> String {
+    let mut output = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        output.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let value = rest.find('}').and_then(|end| {
+            let name = &rest[2..end];
+            let value = names.iter().find(|(key, _)| *key == name)?;
+            Some((end, &value.1))
+        });
+        match value {
+            Some((end, value)) => {
+                output.push_str(value);
+                rest = &rest[end + 1..];
+            }
+            None => {
+                output.push_str("${");
+                rest = &rest[2..];
+            }
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{interpolate, HostnameProvider, FQDN, HOSTNAME};
+    use ::figment::Figment;
+    use pretty_assertions::assert_eq;
+    use std::time::Duration;
+
+    #[test]
+    fn interpolate_names() {
+        let names = [
+            (HOSTNAME, "foo".to_string()),
+            (FQDN, "foo.example.com".to_string()),
+        ];
+        assert_eq!(
+            interpolate("${hostname} is ${fqdn}", &names),
+            "foo is foo.example.com"
+        );
+        assert_eq!(
+            interpolate("${machine_id}, ${unknown}, ${hostname", &names),
+            "${machine_id}, ${unknown}, ${hostname"
+        );
+        assert_eq!(interpolate("$${hostname}}", &names), "$foo}");
+    }
+
+    #[test]
+    fn provide_hostname() {
+        // Don't wait for the resolver; this test doesn't need the FQDN
+        let provider = HostnameProvider::new()
+            .profile("test")
+            .timeout(Duration::from_millis(1));
+        let figment = Figment::from(provider).select("test");
+        let hostname: String = figment.extract_inner(HOSTNAME).unwrap();
+        assert_eq!(hostname, crate::gethostname().to_string_lossy());
+    }
+}
@@ -0,0 +1,285 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The fully qualified domain name of the current machine.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::str::FromStr;
+use std::time::Duration;
+
+use crate::error::HostnameError;
+use crate::hostname::{Hostname, InvalidNameReason};
+use crate::options::Strictness;
+
+/// Get the fully qualified domain name of the current machine.
+///
+/// On Unix resolve the hostname returned by [`try_gethostname()`](crate::try_gethostname)
+/// with [getaddrinfo] and return the canonical name of the first result.  This
+/// involves a DNS lookup and may block for a while if the resolver is slow or
+/// unreachable; use [`getfqdn_timeout()`] to bound the wait.  On Windows
+/// return the fully qualified DNS name of the local computer, as returned by
+/// [GetComputerNameExW] with `ComputerNameDnsFullyQualified` as `NameType`.
+///
+/// If the current machine has no domain the result is just the hostname.
+///
+/// ```
+/// println!("FQDN: {:?}", gethostname::getfqdn());
+/// ```
+///
+/// [getaddrinfo]: http://pubs.opengroup.org/onlinepubs/9699919799/functions/getaddrinfo.html
+/// [GetComputerNameExW]: https://docs.microsoft.com/en-us/windows/desktop/api/sysinfoapi/nf-sysinfoapi-getcomputernameexw
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn getfqdn() -> Result<OsString, HostnameError> {
+    getfqdn_impl()
+}
+
+/// Get the fully qualified domain name of the current machine, or give up
+/// after `timeout`.
+///
+/// Like [`getfqdn()`], but resolve the hostname on a helper thread, and fail
+/// with a [transient](HostnameError::is_transient) error if the resolver
+/// doesn't answer within `timeout`.  The system resolver can't be cancelled,
+/// so the helper thread keeps running after a timeout until the resolver
+/// returns.  Use [`Resolver`](crate::Resolver) to also retry failures.
+///
+/// ```
+/// use std::time::Duration;
+///
+/// println!("FQDN: {:?}", gethostname::getfqdn_timeout(Duration::from_secs(2)));
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn getfqdn_timeout(timeout: Duration) -> Result<OsString, HostnameError> {
+    getfqdn_within(Some(timeout))
+}
+
+/// Get the fully qualified domain name within `timeout`, if any.
+pub(crate) fn getfqdn_within(timeout: Option<Duration>) -> Result<OsString, HostnameError> {
+    crate::resolver::within(timeout, getfqdn_impl)
+}
+
+#[cfg(not(windows))]
+fn getfqdn_impl() -> Result<OsString, HostnameError> {
+    canonical_name(&crate::try_gethostname()?)
+}
+
+/// Resolve the canonical name of `hostname` with getaddrinfo.
+#[cfg(not(windows))]
+pub(crate) fn canonical_name(hostname: &std::ffi::OsStr) -> Result<OsString, HostnameError> {
+    use libc::{addrinfo, freeaddrinfo, getaddrinfo, AF_UNSPEC, AI_CANONNAME};
+    use std::ffi::{CStr, CString};
+    use std::os::unix::ffi::{OsStrExt, OsStringExt};
+
+    use crate::hostname::InvalidNameReason;
+
+    let hostname = CString::new(hostname.as_bytes()).map_err(|error| {
+        HostnameError::invalid_name(
+            InvalidNameReason::InvalidCharacter('\0'),
+            error.nul_position(),
+        )
+    })?;
+    let mut hints: addrinfo = unsafe { std::mem::zeroed() };
+    hints.ai_family = AF_UNSPEC;
+    hints.ai_flags = AI_CANONNAME;
+    let mut result: *mut addrinfo = std::ptr::null_mut();
+    let returncode =
+        unsafe { getaddrinfo(hostname.as_ptr(), std::ptr::null(), &hints, &mut result) };
+    if returncode != 0 {
+        return Err(crate::resolver::gai_error(returncode));
+    }
+    // getaddrinfo puts the canonical name into the first result only
+    let canonname = unsafe { (*result).ai_canonname };
+    let fqdn = if canonname.is_null() {
+        None
+    } else {
+        Some(OsString::from_vec(
+            unsafe { CStr::from_ptr(canonname) }.to_bytes().to_vec(),
+        ))
+    };
+    unsafe { freeaddrinfo(result) };
+    fqdn.ok_or(HostnameError::NotFound {
+        what: "canonical name",
+    })
+}
+
+#[cfg(windows)]
+fn getfqdn_impl() -> Result<OsString, HostnameError> {
+    use winapi::um::sysinfoapi::ComputerNameDnsFullyQualified;
+    crate::get_computer_name_ex(ComputerNameDnsFullyQualified)
+}
+
+/// A valid fully qualified domain name.
+///
+/// DNS tells absolute names, which end with a dot, from relative names, which
+/// the resolver may extend with search domains.  DNS registration needs the
+/// absolute form, whereas protocols like HTTP must never send the trailing
+/// dot.  This type accepts either form, and returns whichever form is needed
+/// with [`as_absolute()`](Fqdn::as_absolute) and
+/// [`as_relative()`](Fqdn::as_relative).  Display the relative form.
+///
+/// Apart from the optional trailing dot an FQDN must be a valid
+/// [`Hostname`].
+///
+/// ```
+/// use gethostname::Fqdn;
+///
+/// let fqdn: Fqdn = "foo.example.com".parse().unwrap();
+/// assert_eq!(fqdn.as_absolute(), "foo.example.com.");
+/// assert_eq!(fqdn.as_relative(), "foo.example.com");
+/// assert_eq!(fqdn, "foo.example.com.".parse().unwrap());
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fqdn(
+    // Always in absolute form
+    String,
+);
+
+impl Fqdn {
+    /// Get the fully qualified domain name of the current machine.
+    ///
+    /// Fail if [`getfqdn()`] fails, or if its result is not a valid FQDN.
+    pub fn current() -> Result<Fqdn, HostnameError> {
+        Fqdn::current_within(None)
+    }
+
+    /// Get the fully qualified domain name of the current machine, or give up
+    /// after `timeout`.
+    ///
+    /// Fail if [`getfqdn_timeout()`] fails, or if its result is not a valid
+    /// FQDN.
+    pub fn current_timeout(timeout: Duration) -> Result<Fqdn, HostnameError> {
+        Fqdn::current_within(Some(timeout))
+    }
+
+    fn current_within(timeout: Option<Duration>) -> Result<Fqdn, HostnameError> {
+        let fqdn = getfqdn_within(timeout)?;
+        match fqdn.to_str() {
+            Some(s) => s.parse(),
+            None => Err(HostnameError::invalid_name(
+                InvalidNameReason::NotUnicode,
+                fqdn.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+            )),
+        }
+    }
+
+    /// Get this FQDN in absolute form, i.e. with a trailing dot.
+    pub fn as_absolute(&self) -> &str {
+        &self.0
+    }
+
+    /// Get this FQDN in relative form, i.e. without a trailing dot.
+    pub fn as_relative(&self) -> &str {
+        &self.0[..self.0.len() - 1]
+    }
+
+    /// Get the top-level domain of this FQDN, i.e. its last label.
+    ///
+    /// Ignore the trailing dot of the absolute form, and return `None` for
+    /// single-label names.  See [`Hostname::tld()`].
+    pub fn tld(&self) -> Option<&str> {
+        crate::hostname::tld(self.as_relative())
+    }
+
+    /// Convert this FQDN into a hostname.
+    pub fn to_hostname(&self) -> Hostname {
+        Hostname::new_unchecked(self.as_relative().to_string())
+    }
+}
+
+impl FromStr for Fqdn {
+    type Err = HostnameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let relative = s.strip_suffix('.').unwrap_or(s);
+        let hostname = Hostname::validated(relative.to_string(), Strictness::default())?;
+        Ok(Fqdn::from(hostname))
+    }
+}
+
+impl From<Hostname> for Fqdn {
+    fn from(hostname: Hostname) -> Self {
+        let mut name = hostname.into_string();
+        name.push('.');
+        Fqdn(name)
+    }
+}
+
+impl fmt::Display for Fqdn {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(self.as_relative())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Fqdn;
+    use crate::{Hostname, HostnameError, InvalidNameReason, ParseOptions, Strictness};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn getfqdn_is_not_empty() {
+        // The resolver may not know the hostname of this machine
+        if let Ok(fqdn) = super::getfqdn() {
+            assert!(!fqdn.is_empty());
+        }
+    }
+
+    #[test]
+    fn absolute_and_relative_forms() {
+        for name in &["foo.example.com", "foo.example.com."] {
+            let fqdn: Fqdn = name.parse().unwrap();
+            assert_eq!(fqdn.as_absolute(), "foo.example.com.");
+            assert_eq!(fqdn.as_relative(), "foo.example.com");
+            assert_eq!(fqdn.to_string(), "foo.example.com");
+            assert_eq!(fqdn.to_hostname().as_str(), "foo.example.com");
+            assert_eq!(fqdn.tld(), Some("com"));
+        }
+        let hostname: Hostname = "foo".parse().unwrap();
+        assert_eq!(Fqdn::from(hostname.clone()).as_absolute(), "foo.");
+        assert_eq!(Fqdn::from(hostname).tld(), None);
+    }
+
+    #[test]
+    fn liberal_fqdn_to_hostname() {
+        let options = ParseOptions::new().strictness(Strictness::Liberal);
+        let hostname = Hostname::parse_with("foo_bar.example", &options).unwrap();
+        let fqdn = Fqdn::from(hostname.clone());
+        assert_eq!(fqdn.as_absolute(), "foo_bar.example.");
+        assert_eq!(fqdn.to_hostname(), hostname);
+    }
+
+    #[test]
+    fn invalid_fqdns() {
+        let invalid = |reason, position| Err(HostnameError::invalid_name(reason, position));
+        assert_eq!("".parse::<Fqdn>(), invalid(InvalidNameReason::Empty, 0));
+        assert_eq!(".".parse::<Fqdn>(), invalid(InvalidNameReason::Empty, 0));
+        assert_eq!(
+            "foo..".parse::<Fqdn>(),
+            invalid(InvalidNameReason::EmptyLabel, 4)
+        );
+        assert_eq!(
+            "foo.-bar.".parse::<Fqdn>(),
+            invalid(InvalidNameReason::LeadingHyphen, 4)
+        );
+    }
+
+    #[test]
+    fn current_fqdn() {
+        // The resolver may not know the hostname of this machine
+        if let Ok(fqdn) = Fqdn::current() {
+            assert!(fqdn.as_absolute().ends_with('.'));
+            assert!(!fqdn.as_relative().ends_with('.'));
+        }
+    }
+}
@@ -0,0 +1,195 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Generate unique hostnames.
+
+use std::collections::hash_map::RandomState;
+use std::hash::{BuildHasher, Hasher};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+use crate::error::HostnameError;
+use crate::hostname::{Hostname, InvalidNameReason};
+
+/// The default alphabet of random suffixes.
+const DEFAULT_ALPHABET: &str = "abcdefghijklmnopqrstuvwxyz0123456789";
+
+/// The default length of random suffixes.
+const DEFAULT_LENGTH: usize = 5;
+
+/// Generate a unique hostname from `prefix`.
+///
+/// Append a hyphen and five random lowercase letters or digits to `prefix`,
+/// e.g. `build-agent-7f3k2`.  Use [`UniqueNameGenerator`] to change the
+/// alphabet or the length of the random suffix.
+///
+/// Fail if the result is not a valid hostname, e.g. if `prefix` contains
+/// invalid characters or is too long.
+///
+/// ```
+/// let hostname = gethostname::generate_unique("build-agent").unwrap();
+/// assert!(hostname.as_str().starts_with("build-agent-"));
+/// assert_eq!(hostname.as_str().len(), "build-agent-".len() + 5);
+/// ```
+pub fn generate_unique(prefix: &str) -> Result<Hostname, HostnameError> {
+    UniqueNameGenerator::new().generate(prefix)
+}
+
+/// Generate unique hostnames with a random suffix.
+///
+/// ```
+/// use gethostname::UniqueNameGenerator;
+///
+/// let generator = UniqueNameGenerator::new().alphabet("0123456789abcdef").length(8);
+/// let hostname = generator.generate("worker").unwrap();
+/// assert_eq!(hostname.as_str().len(), "worker-".len() + 8);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct UniqueNameGenerator {
+    alphabet: String,
+    length: usize,
+}
+
+impl Default for UniqueNameGenerator {
+    fn default() -> Self {
+        UniqueNameGenerator {
+            alphabet: DEFAULT_ALPHABET.to_string(),
+            length: DEFAULT_LENGTH,
+        }
+    }
+}
+
+impl UniqueNameGenerator {
+    /// Create a generator for suffixes of five lowercase letters or digits.
+    pub fn new() -> UniqueNameGenerator {
+        UniqueNameGenerator::default()
+    }
+
+    /// Set the alphabet of the random suffix.
+    ///
+    /// The alphabet may only contain lowercase ASCII letters and digits;
+    /// hostnames are case-insensitive, so uppercase letters would not make
+    /// names any more unique.
+    pub fn alphabet(mut self, alphabet: &str) -> UniqueNameGenerator {
+        self.alphabet = alphabet.to_string();
+        self
+    }
+
+    /// Set the length of the random suffix.
+    pub fn length(mut self, length: usize) -> UniqueNameGenerator {
+        self.length = length;
+        self
+    }
+
+    /// Generate a unique hostname from `prefix`.
+    ///
+    /// Fail with [`InvalidNameReason::InvalidCharacter`] if the alphabet
+    /// contains characters other than lowercase ASCII letters and digits, with
+    /// [`InvalidNameReason::Empty`] if the alphabet is empty, or if the result
+    /// is not a valid hostname.
+    pub fn generate(&self, prefix: &str) -> Result<Hostname, HostnameError> {
+        let suffix_start = prefix.len() + 1;
+        if let Some((offset, c)) = self
+            .alphabet
+            .char_indices()
+            .find(|&(_, c)| !(c.is_ascii_lowercase() || c.is_ascii_digit()))
+        {
+            return Err(HostnameError::invalid_name(
+                InvalidNameReason::InvalidCharacter(c),
+                suffix_start + offset,
+            ));
+        }
+        let alphabet = self.alphabet.as_bytes();
+        if alphabet.is_empty() {
+            return Err(HostnameError::invalid_name(
+                InvalidNameReason::Empty,
+                suffix_start,
+            ));
+        }
+        let mut name = String::with_capacity(suffix_start + self.length);
+        name.push_str(prefix);
+        name.push('-');
+        for _ in 0..self.length {
+            let index = (random_u64() % alphabet.len() as u64) as usize;
+            name.push(char::from(alphabet[index]));
+        }
+        name.parse()
+    }
+}
+
+/// Get a random number.
+///
+/// Not cryptographically secure, but std seeds the keys of `RandomState` from
+/// the random number generator of the operating system, which is good enough
+/// to make names unique.
+fn random_u64() -> u64 {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let mut hasher = RandomState::new().build_hasher();
+    hasher.write_usize(COUNTER.fetch_add(1, Ordering::Relaxed));
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{generate_unique, UniqueNameGenerator};
+    use crate::{HostnameError, InvalidNameReason};
+    use pretty_assertions::assert_eq;
+    use std::collections::HashSet;
+
+    #[test]
+    fn generated_names_are_unique() {
+        let names = (0..100)
+            .map(|_| generate_unique("build-agent").unwrap())
+            .collect::<HashSet<_>>();
+        assert_eq!(names.len(), 100);
+    }
+
+    #[test]
+    fn custom_alphabet_and_length() {
+        let hostname = UniqueNameGenerator::new()
+            .alphabet("ab")
+            .length(10)
+            .generate("web")
+            .unwrap();
+        let suffix = hostname.as_str().strip_prefix("web-").unwrap();
+        assert_eq!(suffix.len(), 10);
+        assert!(suffix.chars().all(|c| c == 'a' || c == 'b'));
+    }
+
+    #[test]
+    fn invalid_alphabets() {
+        let generate = |alphabet| {
+            UniqueNameGenerator::new()
+                .alphabet(alphabet)
+                .generate("web")
+        };
+        assert_eq!(
+            generate("abC").unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::InvalidCharacter('C'), 6)
+        );
+        assert_eq!(
+            generate("a-b").unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::InvalidCharacter('-'), 5)
+        );
+        assert_eq!(
+            generate("").unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::Empty, 4)
+        );
+    }
+
+    #[test]
+    fn invalid_prefixes() {
+        assert!(generate_unique("web_server").is_err());
+        assert!(generate_unique(&"a".repeat(60)).is_err());
+    }
+}
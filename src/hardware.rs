@@ -0,0 +1,459 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hardware identity and model of the current machine.
+
+#[cfg(any(windows, test))]
+use std::convert::TryInto;
+
+use crate::error::HostnameError;
+
+/// Get the hardware UUID of the current machine.
+///
+/// * On Linux return the SMBIOS system UUID from
+///   `/sys/class/dmi/id/product_uuid`, or the UUID of the virtual machine
+///   from `/sys/hypervisor/uuid` on Xen.
+/// * On macOS return the `IOPlatformUUID` of the platform expert device, as
+///   reported by `ioreg`.
+/// * On Windows return the SMBIOS system UUID from the raw SMBIOS tables, as
+///   returned by [GetSystemFirmwareTable].
+///
+/// Return the UUID in its canonical lowercase form, e.g.
+/// `4c4c4544-0042-4a10-8048-b4c04f4d4e32`.
+///
+/// Unlike [`machine_id()`](crate::machine_id) the hardware UUID survives
+/// reinstalling the operating system.  On Linux only root may read the
+/// SMBIOS UUID; if no other source has a UUID fail with the permission error
+/// then, rather than with [`HostnameError::NotFound`].  Fail with
+/// [`HostnameError::Unsupported`] on other systems.
+///
+/// [GetSystemFirmwareTable]: https://docs.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-getsystemfirmwaretable
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn get_hardware_uuid() -> Result<String, HostnameError> {
+    hardware_uuid_impl()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn hardware_uuid_impl() -> Result<String, HostnameError> {
+    use std::io::ErrorKind;
+
+    let mut denied = None;
+    for path in &["/sys/class/dmi/id/product_uuid", "/sys/hypervisor/uuid"] {
+        match std::fs::read_to_string(path) {
+            Ok(contents) if !contents.trim().is_empty() => {
+                return Ok(contents.trim().to_lowercase())
+            }
+            Err(error) if error.kind() == ErrorKind::PermissionDenied => {
+                warn!("Cannot read hardware UUID from {}: {}", path, error);
+                denied = Some(HostnameError::from_io(&error));
+            }
+            _ => {}
+        }
+    }
+    Err(denied.unwrap_or(HostnameError::NotFound {
+        what: "hardware UUID",
+    }))
+}
+
+#[cfg(target_os = "macos")]
+fn hardware_uuid_impl() -> Result<String, HostnameError> {
+    crate::machine_id::platform_expert_property("IOPlatformUUID").map(|uuid| uuid.to_lowercase())
+}
+
+#[cfg(windows)]
+fn hardware_uuid_impl() -> Result<String, HostnameError> {
+    smbios_system_uuid(&raw_smbios_tables()?).ok_or(HostnameError::NotFound {
+        what: "SMBIOS system UUID",
+    })
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_os = "macos",
+    windows
+)))]
+fn hardware_uuid_impl() -> Result<String, HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+/// Get the hardware model of the current machine.
+///
+/// * On Linux return the SMBIOS product name from
+///   `/sys/class/dmi/id/product_name`, or the model from the device tree on
+///   systems without SMBIOS, e.g. `Raspberry Pi 4 Model B Rev 1.4`.
+/// * On macOS and BSDs return the `hw.model` sysctl, e.g. `MacBookPro18,3`.
+/// * On iOS, tvOS, watchOS and visionOS return the `hw.machine` sysctl, e.g.
+///   `iPhone15,2`.
+/// * On Windows return the SMBIOS product name from the raw SMBIOS tables,
+///   which is also what WMI reports as `Model` of `Win32_ComputerSystem`.
+///
+/// Fail with [`HostnameError::NotFound`] if the system has no model, and with
+/// [`HostnameError::Unsupported`] on other systems.
+///
+/// ```
+/// match gethostname::get_hardware_model() {
+///     Ok(model) => println!("Model: {}", model),
+///     Err(error) => eprintln!("No model: {}", error),
+/// }
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn get_hardware_model() -> Result<String, HostnameError> {
+    hardware_model_impl()
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn hardware_model_impl() -> Result<String, HostnameError> {
+    for path in &[
+        "/sys/class/dmi/id/product_name",
+        "/sys/firmware/devicetree/base/model",
+    ] {
+        match std::fs::read(path) {
+            Ok(contents) => {
+                // The device tree terminates strings with NUL
+                let model = String::from_utf8_lossy(&contents);
+                let model = model.trim_end_matches('\0').trim();
+                if !model.is_empty() {
+                    return Ok(model.to_string());
+                }
+            }
+            Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+                return Err(HostnameError::from_io(&error))
+            }
+            Err(_) => {}
+        }
+    }
+    Err(HostnameError::NotFound {
+        what: "hardware model",
+    })
+}
+
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+fn hardware_model_impl() -> Result<String, HostnameError> {
+    // On iOS and its siblings hw.model is the board, e.g. D83AP, and hw.machine
+    // the model identifier we want
+    if cfg!(all(target_vendor = "apple", not(target_os = "macos"))) {
+        sysctl_string(b"hw.machine\0", "hw.machine")
+    } else {
+        sysctl_string(b"hw.model\0", "hw.model")
+    }
+}
+
+/// Get the string value of the sysctl `name`, which ends with a NUL byte.
+///
+/// Fail with [`HostnameError::NotFound`] for `what` if the value is empty.
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd"
+))]
+pub(crate) fn sysctl_string(name: &[u8], what: &'static str) -> Result<String, HostnameError> {
+    use libc::sysctlbyname;
+
+    let mut size: libc::size_t = 0;
+    let returncode = unsafe {
+        sysctlbyname(
+            name.as_ptr() as *const _,
+            std::ptr::null_mut(),
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if returncode != 0 {
+        return Err(HostnameError::last_os_error());
+    }
+    let mut buffer = crate::zeroed_buffer::<u8>(size)?;
+    let returncode = unsafe {
+        sysctlbyname(
+            name.as_ptr() as *const _,
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if returncode != 0 {
+        return Err(HostnameError::last_os_error());
+    }
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(size);
+    buffer.truncate(end);
+    let value = String::from_utf8_lossy(&buffer).trim().to_string();
+    if value.is_empty() {
+        Err(HostnameError::NotFound { what })
+    } else {
+        Ok(value)
+    }
+}
+
+#[cfg(windows)]
+fn hardware_model_impl() -> Result<String, HostnameError> {
+    smbios_system_product_name(&raw_smbios_tables()?).ok_or(HostnameError::NotFound {
+        what: "SMBIOS product name",
+    })
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    windows
+)))]
+fn hardware_model_impl() -> Result<String, HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+/// Get the raw SMBIOS tables with `GetSystemFirmwareTable`.
+#[cfg(windows)]
+fn raw_smbios_tables() -> Result<Vec<u8>, HostnameError> {
+    use winapi::um::sysinfoapi::GetSystemFirmwareTable;
+
+    // The 'RSMB' provider, as big-endian integer
+    let provider = u32::from_be_bytes(*b"RSMB");
+    let size = unsafe { GetSystemFirmwareTable(provider, 0, std::ptr::null_mut(), 0) };
+    if size == 0 {
+        return Err(HostnameError::last_os_error());
+    }
+    let mut buffer = crate::zeroed_buffer::<u8>(size as usize)?;
+    let written =
+        unsafe { GetSystemFirmwareTable(provider, 0, buffer.as_mut_ptr() as *mut _, size) };
+    if written == 0 {
+        return Err(HostnameError::last_os_error());
+    }
+    buffer.truncate(written as usize);
+    Ok(buffer)
+}
+
+/// The SMBIOS structure type of system information.
+#[cfg(any(windows, test))]
+const SMBIOS_SYSTEM_INFORMATION: u8 = 1;
+
+/// The SMBIOS structure type of the system enclosure or chassis.
+#[cfg(any(windows, test))]
+const SMBIOS_SYSTEM_ENCLOSURE: u8 = 3;
+
+/// Find the first SMBIOS structure of the given `kind` in `raw`.
+///
+/// `raw` is the `RawSMBIOSData` returned by `GetSystemFirmwareTable`, i.e. an
+/// eight byte header with the SMBIOS version, followed by the SMBIOS
+/// structures.  Return the SMBIOS version, the formatted area of the
+/// structure, and the strings which follow it.
+#[cfg(any(windows, test))]
+fn find_smbios_structure(raw: &[u8], kind: u8) -> Option<SmbiosStructure<'_>> {
+    let version = (*raw.get(1)?, *raw.get(2)?);
+    let length = u32::from_le_bytes(raw.get(4..8)?.try_into().ok()?) as usize;
+    let mut tables = 8usize.checked_add(length).and_then(|end| raw.get(8..end))?;
+    while 4 <= tables.len() {
+        let formatted_length = usize::from(tables[1]);
+        let formatted = tables.get(..formatted_length)?;
+        // The strings follow the formatted area, up to the double NUL byte.
+        let strings = tables.get(formatted_length..)?;
+        let end = strings.windows(2).position(|w| w == [0, 0])?;
+        if tables[0] == kind {
+            return Some(SmbiosStructure {
+                version,
+                formatted,
+                strings: &strings[..end],
+            });
+        }
+        tables = &strings[end + 2..];
+    }
+    None
+}
+
+/// A structure in the SMBIOS tables.
+#[cfg(any(windows, test))]
+struct SmbiosStructure<'a> {
+    /// The SMBIOS version as major and minor version.
+    version: (u8, u8),
+    /// The formatted area, including the header.
+    formatted: &'a [u8],
+    /// The NUL-separated strings of the structure.
+    strings: &'a [u8],
+}
+
+#[cfg(any(windows, test))]
+impl SmbiosStructure<'_> {
+    /// Get the string referenced by the byte at `offset` in the formatted
+    /// area.
+    ///
+    /// String references are one-based; zero means there's no string.
+    fn string(&self, offset: usize) -> Option<String> {
+        let index = usize::from(*self.formatted.get(offset)?).checked_sub(1)?;
+        let s = self.strings.split(|&b| b == 0).nth(index)?;
+        let s = String::from_utf8_lossy(s).trim().to_string();
+        Some(s).filter(|s| !s.is_empty())
+    }
+}
+
+/// Extract the system UUID from the raw SMBIOS tables.
+#[cfg(any(windows, test))]
+fn smbios_system_uuid(raw: &[u8]) -> Option<String> {
+    let system = find_smbios_structure(raw, SMBIOS_SYSTEM_INFORMATION)?;
+    let uuid: [u8; 16] = system.formatted.get(8..24)?.try_into().ok()?;
+    // All zeros mean there's no UUID, all ones that it's not set
+    if uuid.iter().all(|&b| b == 0) || uuid.iter().all(|&b| b == 0xff) {
+        return None;
+    }
+    // Since SMBIOS 2.6 the first three fields are little-endian
+    let mut bytes = uuid;
+    if (2, 6) <= system.version {
+        bytes[..4].reverse();
+        bytes[4..6].reverse();
+        bytes[6..8].reverse();
+    }
+    let hex = bytes
+        .iter()
+        .map(|b| format!("{:02x}", b))
+        .collect::<String>();
+    Some(format!(
+        "{}-{}-{}-{}-{}",
+        &hex[..8],
+        &hex[8..12],
+        &hex[12..16],
+        &hex[16..20],
+        &hex[20..]
+    ))
+}
+
+/// Extract the product name of the system from the raw SMBIOS tables.
+#[cfg(any(windows, test))]
+fn smbios_system_product_name(raw: &[u8]) -> Option<String> {
+    find_smbios_structure(raw, SMBIOS_SYSTEM_INFORMATION)?.string(5)
+}
+
+/// Get the SMBIOS chassis type of the current machine, if any.
+#[cfg(windows)]
+pub(crate) fn smbios_chassis_type() -> Result<Option<u8>, HostnameError> {
+    Ok(smbios_enclosure_type(&raw_smbios_tables()?))
+}
+
+/// Extract the chassis type of the system enclosure from the raw SMBIOS
+/// tables.
+#[cfg(any(windows, test))]
+fn smbios_enclosure_type(raw: &[u8]) -> Option<u8> {
+    find_smbios_structure(raw, SMBIOS_SYSTEM_ENCLOSURE)?
+        .formatted
+        .get(5)
+        .copied()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{smbios_enclosure_type, smbios_system_product_name, smbios_system_uuid};
+    use pretty_assertions::assert_eq;
+
+    /// Build raw SMBIOS data with the given version and structures.
+    fn raw_smbios(version: (u8, u8), structures: &[&[u8]]) -> Vec<u8> {
+        let tables = structures.concat();
+        let mut raw = vec![0, version.0, version.1, 0];
+        raw.extend_from_slice(&(tables.len() as u32).to_le_bytes());
+        raw.extend_from_slice(&tables);
+        raw
+    }
+
+    const BIOS_INFORMATION: &[u8] = &[0, 4, 0, 0, b'A', b'M', b'I', 0, 0];
+
+    const SYSTEM_INFORMATION: &[u8] = &[
+        1, 24, 1, 0, 1, 2, 0, 0, 0x44, 0x45, 0x4c, 0x4c, 0x42, 0x00, 0x10, 0x4a, 0x80, 0x48, 0xb4,
+        0xc0, 0x4f, 0x4d, 0x4e, 0x32, b'D', b'e', b'l', b'l', 0, b'X', b'P', b'S', 0, 0,
+    ];
+
+    #[test]
+    fn system_uuid_little_endian() {
+        let raw = raw_smbios((3, 2), &[BIOS_INFORMATION, SYSTEM_INFORMATION]);
+        assert_eq!(
+            smbios_system_uuid(&raw).unwrap(),
+            "4c4c4544-0042-4a10-8048-b4c04f4d4e32"
+        );
+    }
+
+    #[test]
+    fn system_uuid_big_endian_before_2_6() {
+        let raw = raw_smbios((2, 4), &[SYSTEM_INFORMATION]);
+        assert_eq!(
+            smbios_system_uuid(&raw).unwrap(),
+            "44454c4c-4200-104a-8048-b4c04f4d4e32"
+        );
+    }
+
+    #[test]
+    fn missing_system_uuid() {
+        let mut system = SYSTEM_INFORMATION.to_vec();
+        system[8..24].copy_from_slice(&[0xff; 16]);
+        assert_eq!(smbios_system_uuid(&raw_smbios((3, 2), &[&system])), None);
+        assert_eq!(
+            smbios_system_uuid(&raw_smbios((3, 2), &[BIOS_INFORMATION])),
+            None
+        );
+        assert_eq!(smbios_system_uuid(&[]), None);
+    }
+
+    #[test]
+    fn overflowing_table_length() {
+        let mut raw = raw_smbios((3, 2), &[SYSTEM_INFORMATION]);
+        raw[4..8].copy_from_slice(&u32::MAX.to_le_bytes());
+        assert_eq!(smbios_system_uuid(&raw), None);
+    }
+
+    #[test]
+    fn system_product_name() {
+        let raw = raw_smbios((3, 2), &[BIOS_INFORMATION, SYSTEM_INFORMATION]);
+        assert_eq!(smbios_system_product_name(&raw).unwrap(), "XPS");
+        let mut system = SYSTEM_INFORMATION.to_vec();
+        system[5] = 0;
+        assert_eq!(
+            smbios_system_product_name(&raw_smbios((3, 2), &[&system])),
+            None
+        );
+        system[5] = 3;
+        assert_eq!(
+            smbios_system_product_name(&raw_smbios((3, 2), &[&system])),
+            None
+        );
+    }
+
+    #[test]
+    fn enclosure_type() {
+        let enclosure: &[u8] = &[3, 9, 2, 0, 1, 0x8a, 0, 0, 0, b'D', b'e', b'l', b'l', 0, 0];
+        let raw = raw_smbios((3, 2), &[SYSTEM_INFORMATION, enclosure]);
+        assert_eq!(smbios_enclosure_type(&raw), Some(0x8a));
+        let raw = raw_smbios((3, 2), &[SYSTEM_INFORMATION]);
+        assert_eq!(smbios_enclosure_type(&raw), None);
+    }
+
+    #[test]
+    fn hardware_model_is_not_empty() {
+        if let Ok(model) = super::get_hardware_model() {
+            assert!(!model.is_empty());
+        }
+    }
+
+    #[test]
+    fn hardware_uuid_is_canonical() {
+        if let Ok(uuid) = super::get_hardware_uuid() {
+            assert_eq!(uuid.len(), 36);
+            assert_eq!(uuid, uuid.to_lowercase());
+        }
+    }
+}
@@ -0,0 +1,65 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Get the hostname into a [heapless] string.
+//!
+//! [heapless]: https://docs.rs/heapless
+
+use crate::error::HostnameError;
+
+/// Get the hostname of the current machine into a string of at most `N`
+/// bytes, without allocating.
+///
+/// Like [`try_gethostname_into()`](crate::try_gethostname_into), but return
+/// an owned string with a fixed capacity.  Fail with
+/// [`HostnameError::BufferTooSmall`] if the hostname doesn't fit into `N`
+/// bytes; 255 bytes fit every hostname.
+///
+/// ```
+/// let hostname = gethostname::gethostname_heapless::<255>().unwrap();
+/// assert_eq!(hostname.as_str(), gethostname::gethostname().to_str().unwrap());
+/// ```
+pub fn gethostname_heapless<const N: usize>() -> Result<heapless::String<N>, HostnameError> {
+    // The maximum hostname size of all supported systems, plus the NUL byte
+    let mut buffer = [0; 256];
+    let name = crate::try_gethostname_into(&mut buffer)?;
+    let mut hostname = heapless::String::new();
+    hostname
+        .push_str(name)
+        .map_err(|_| HostnameError::BufferTooSmall)?;
+    Ok(hostname)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::gethostname_heapless;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hostname_fits() {
+        assert_eq!(
+            gethostname_heapless::<255>().unwrap().as_str(),
+            crate::gethostname()
+        );
+    }
+
+    #[test]
+    fn hostname_does_not_fit() {
+        assert_eq!(
+            gethostname_heapless::<0>(),
+            Err(HostnameError::BufferTooSmall)
+        );
+    }
+}
@@ -0,0 +1,402 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Hostlists as in [SLURM], e.g. `node[001-016,020].cluster`.
+//!
+//! HPC schedulers and tools like `pdsh` write sets of hostnames compactly as
+//! hostlists: Comma-separated names, in which brackets hold comma-separated
+//! numbers and ranges of numbers.  A name expands to one hostname for each
+//! number in its brackets, or the product of all numbers if a name has
+//! multiple brackets, e.g. `rack[1-2]-node[1-2]`.
+//!
+//! ```
+//! use gethostname::hostlist;
+//!
+//! let hostnames = hostlist::expand("node[001-003,010].cluster").unwrap();
+//! assert_eq!(
+//!     hostnames,
+//!     ["node001.cluster", "node002.cluster", "node003.cluster", "node010.cluster"]
+//! );
+//! assert_eq!(hostlist::compress(&hostnames), "node[001-003,010].cluster");
+//! ```
+//!
+//! [SLURM]: https://slurm.schedmd.com/hostlist.html
+
+use std::fmt::Write;
+
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+
+/// The maximum number of hostnames a hostlist may expand to.
+///
+/// This guards against small hostlists like `node[0-99999999999]` which would
+/// exhaust memory.
+pub const MAX_HOSTS: usize = 65536;
+
+/// Expand `hostlist` into individual hostnames.
+///
+/// Zero-pad numbers to the length of the lower bound of their range, e.g.
+/// `node[08-10]` expands to `node08`, `node09`, and `node10`.  Keep hostnames
+/// in the order of the hostlist, and don't remove duplicates.  An empty
+/// hostlist expands to no hostnames.
+///
+/// Fail with [`HostnameError::InvalidName`] at the offending position if
+/// `hostlist` has unbalanced or nested brackets, an empty name, or a range
+/// which is invalid, descending or too large, or if it expands to more than
+/// [`MAX_HOSTS`] hostnames.  Don't validate the hostnames themselves; use
+/// [`Hostname`](crate::Hostname) for this.
+pub fn expand(hostlist: &str) -> Result<Vec<String>, HostnameError> {
+    let mut hostnames = Vec::new();
+    if hostlist.is_empty() {
+        return Ok(hostnames);
+    }
+    for (start, name) in split_names(hostlist)? {
+        let expanded = expand_name(name, start)?;
+        if MAX_HOSTS < hostnames.len() + expanded.len() {
+            return Err(HostnameError::invalid_name(
+                InvalidNameReason::InvalidRange,
+                start,
+            ));
+        }
+        hostnames.extend(expanded);
+    }
+    Ok(hostnames)
+}
+
+/// Split `hostlist` at commas outside of brackets.
+///
+/// Return each name with its byte offset in `hostlist`.
+fn split_names(hostlist: &str) -> Result<Vec<(usize, &str)>, HostnameError> {
+    let mut names = Vec::new();
+    let mut open = None;
+    let mut start = 0;
+    for (i, c) in hostlist.char_indices() {
+        match c {
+            '[' | ']' if open.is_some() == (c == '[') => {
+                return Err(HostnameError::invalid_name(
+                    InvalidNameReason::InvalidCharacter(c),
+                    i,
+                ))
+            }
+            '[' => open = Some(i),
+            ']' => open = None,
+            ',' if open.is_none() => {
+                names.push((start, &hostlist[start..i]));
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    if let Some(i) = open {
+        return Err(HostnameError::invalid_name(
+            InvalidNameReason::InvalidCharacter('['),
+            i,
+        ));
+    }
+    names.push((start, &hostlist[start..]));
+    Ok(names)
+}
+
+/// Expand a single `name` with balanced brackets at offset `start`.
+fn expand_name(name: &str, start: usize) -> Result<Vec<String>, HostnameError> {
+    if name.is_empty() {
+        return Err(HostnameError::invalid_name(InvalidNameReason::Empty, start));
+    }
+    let mut hostnames = vec![String::new()];
+    let mut offset = start;
+    let mut rest = name;
+    while let Some(open) = rest.find('[') {
+        // split_names checked that brackets are balanced
+        let close = open + rest[open..].find(']').unwrap_or(rest.len() - open);
+        let numbers = expand_ranges(&rest[open + 1..close], offset + open + 1)?;
+        if MAX_HOSTS < hostnames.len() * numbers.len() {
+            return Err(HostnameError::invalid_name(
+                InvalidNameReason::InvalidRange,
+                offset + open,
+            ));
+        }
+        let literal = &rest[..open];
+        hostnames = hostnames
+            .iter()
+            .flat_map(|hostname| {
+                numbers
+                    .iter()
+                    .map(move |number| format!("{}{}{}", hostname, literal, number))
+            })
+            .collect();
+        offset += close + 1;
+        rest = &rest[close + 1..];
+    }
+    for hostname in &mut hostnames {
+        hostname.push_str(rest);
+    }
+    Ok(hostnames)
+}
+
+/// Expand the comma-separated numbers and ranges in brackets at `offset`.
+fn expand_ranges(ranges: &str, offset: usize) -> Result<Vec<String>, HostnameError> {
+    let mut numbers = Vec::new();
+    let mut start = offset;
+    for range in ranges.split(',') {
+        let invalid_range = || HostnameError::invalid_name(InvalidNameReason::InvalidRange, start);
+        let (low, high) = match range.find('-') {
+            Some(hyphen) => (&range[..hyphen], &range[hyphen + 1..]),
+            None => (range, range),
+        };
+        if let Some((i, c)) = low
+            .char_indices()
+            .chain(
+                high.char_indices()
+                    .map(|(i, c)| (range.len() - high.len() + i, c)),
+            )
+            .find(|(_, c)| !c.is_ascii_digit())
+        {
+            return Err(HostnameError::invalid_name(
+                InvalidNameReason::InvalidCharacter(c),
+                start + i,
+            ));
+        }
+        let width = low.len();
+        let low = low.parse::<u64>().map_err(|_| invalid_range())?;
+        let high = high.parse::<u64>().map_err(|_| invalid_range())?;
+        let count = high
+            .checked_sub(low)
+            .and_then(|d| d.checked_add(1))
+            .and_then(|count| count.checked_add(numbers.len() as u64))
+            .ok_or_else(invalid_range)?;
+        if (MAX_HOSTS as u64) < count {
+            return Err(invalid_range());
+        }
+        numbers.extend((low..=high).map(|number| format!("{:0width$}", number, width = width)));
+        start += range.len() + 1;
+    }
+    Ok(numbers)
+}
+
+/// Compress `hostnames` into a hostlist.
+///
+/// Group hostnames which differ only in their last number, and write the
+/// numbers of each group in brackets, with consecutive numbers as ranges,
+/// e.g. `node[001-003,010].cluster`.  Keep zero-padded and unpadded numbers
+/// in separate groups, so that the hostlist expands to the same hostnames
+/// again.  Order groups by their first hostname in `hostnames`, sort numbers
+/// within groups, and drop duplicate hostnames.
+pub fn compress<S: AsRef<str>>(hostnames: &[S]) -> String {
+    // Group by prefix, suffix, and the width of zero-padded numbers
+    let mut groups: Vec<(GroupKey, Vec<u64>)> = Vec::new();
+    let mut plain: Vec<(usize, &str)> = Vec::new();
+    for hostname in hostnames {
+        let hostname = hostname.as_ref();
+        match split_number(hostname) {
+            Some((key, number)) => match groups.iter_mut().find(|(k, _)| *k == key) {
+                Some((_, numbers)) => numbers.push(number),
+                None => groups.push((key, vec![number])),
+            },
+            None => {
+                if !plain.iter().any(|(_, h)| *h == hostname) {
+                    plain.push((groups.len(), hostname));
+                }
+            }
+        }
+    }
+    let mut hostlist = String::new();
+    let mut plain = plain.into_iter().peekable();
+    for (index, ((prefix, suffix, width), mut numbers)) in groups.into_iter().enumerate() {
+        while let Some((_, hostname)) = plain.next_if(|(i, _)| *i <= index) {
+            push_name(&mut hostlist, hostname);
+        }
+        numbers.sort_unstable();
+        numbers.dedup();
+        if !hostlist.is_empty() {
+            hostlist.push(',');
+        }
+        hostlist.push_str(prefix);
+        if numbers.len() == 1 {
+            let _ = write!(hostlist, "{:0width$}", numbers[0], width = width);
+        } else {
+            hostlist.push('[');
+            let mut ranges = numbers.iter().peekable();
+            let mut first = true;
+            while let Some(&low) = ranges.next() {
+                let mut high = low;
+                while let Some(&&next) = ranges.peek() {
+                    if next != high + 1 {
+                        break;
+                    }
+                    high = next;
+                    ranges.next();
+                }
+                if !first {
+                    hostlist.push(',');
+                }
+                first = false;
+                let _ = write!(hostlist, "{:0width$}", low, width = width);
+                if low != high {
+                    let _ = write!(hostlist, "-{:0width$}", high, width = width);
+                }
+            }
+            hostlist.push(']');
+        }
+        hostlist.push_str(suffix);
+    }
+    for (_, hostname) in plain {
+        push_name(&mut hostlist, hostname);
+    }
+    hostlist
+}
+
+fn push_name(hostlist: &mut String, hostname: &str) {
+    if !hostlist.is_empty() {
+        hostlist.push(',');
+    }
+    hostlist.push_str(hostname);
+}
+
+/// The prefix, suffix, and zero-padded width of a group of hostnames.
+type GroupKey<'a> = (&'a str, &'a str, usize);
+
+/// Split `hostname` at its last number.
+///
+/// Return the prefix before and the suffix after the number, the width of the
+/// number if zero-padded or zero otherwise, and the number.
+fn split_number(hostname: &str) -> Option<(GroupKey<'_>, u64)> {
+    let end = hostname.rfind(|c: char| c.is_ascii_digit())? + 1;
+    let start = hostname[..end]
+        .rfind(|c: char| !c.is_ascii_digit())
+        .map_or(0, |i| i + 1);
+    let digits = &hostname[start..end];
+    let number = digits.parse().ok()?;
+    let width = if digits.starts_with('0') && digits.len() > 1 {
+        digits.len()
+    } else {
+        0
+    };
+    Some(((&hostname[..start], &hostname[end..], width), number))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{compress, expand, MAX_HOSTS};
+    use crate::{HostnameError, InvalidNameReason};
+    use pretty_assertions::assert_eq;
+
+    fn invalid(reason: InvalidNameReason, position: usize) -> Result<Vec<String>, HostnameError> {
+        Err(HostnameError::invalid_name(reason, position))
+    }
+
+    #[test]
+    fn expand_plain_names() {
+        assert_eq!(expand("").unwrap(), Vec::<String>::new());
+        assert_eq!(expand("foo").unwrap(), ["foo"]);
+        assert_eq!(expand("foo,bar.example").unwrap(), ["foo", "bar.example"]);
+    }
+
+    #[test]
+    fn expand_ranges() {
+        assert_eq!(
+            expand("node[001-003,010].cluster").unwrap(),
+            [
+                "node001.cluster",
+                "node002.cluster",
+                "node003.cluster",
+                "node010.cluster"
+            ]
+        );
+        assert_eq!(expand("node[8-10]").unwrap(), ["node8", "node9", "node10"]);
+        assert_eq!(
+            expand("node[08-10]").unwrap(),
+            ["node08", "node09", "node10"]
+        );
+        assert_eq!(
+            expand("node[7],gpu[1-2]").unwrap(),
+            ["node7", "gpu1", "gpu2"]
+        );
+    }
+
+    #[test]
+    fn expand_multiple_brackets() {
+        assert_eq!(
+            expand("rack[1-2]-node[1-2]").unwrap(),
+            ["rack1-node1", "rack1-node2", "rack2-node1", "rack2-node2"]
+        );
+    }
+
+    #[test]
+    fn expand_invalid_hostlists() {
+        use InvalidNameReason::*;
+        assert_eq!(expand("node[1-2"), invalid(InvalidCharacter('['), 4));
+        assert_eq!(expand("node1-2]"), invalid(InvalidCharacter(']'), 7));
+        assert_eq!(expand("node[1[2]]"), invalid(InvalidCharacter('['), 6));
+        assert_eq!(expand("node[1-x]"), invalid(InvalidCharacter('x'), 7));
+        assert_eq!(expand("node[1,,2]"), invalid(InvalidRange, 7));
+        assert_eq!(expand("node[3-1]"), invalid(InvalidRange, 5));
+        assert_eq!(expand("a,,b"), invalid(Empty, 2));
+        assert_eq!(expand("node[0-99999999999]"), invalid(InvalidRange, 5));
+        assert_eq!(expand("a[0-999]b[0-999]"), invalid(InvalidRange, 9));
+    }
+
+    #[test]
+    fn expand_up_to_max_hosts() {
+        let hostlist = format!("node[1-{}]", MAX_HOSTS);
+        assert_eq!(expand(&hostlist).unwrap().len(), MAX_HOSTS);
+        assert_eq!(
+            expand(&format!("{},extra", hostlist)),
+            invalid(InvalidNameReason::InvalidRange, hostlist.len() + 1)
+        );
+        assert_eq!(
+            expand(&format!("node[1-{}]", MAX_HOSTS + 1)),
+            invalid(InvalidNameReason::InvalidRange, 5)
+        );
+    }
+
+    #[test]
+    fn expand_full_u64_range() {
+        assert_eq!(
+            expand(&format!("node[0-{}]", u64::MAX)),
+            invalid(InvalidNameReason::InvalidRange, 5)
+        );
+    }
+
+    #[test]
+    fn compress_hostnames() {
+        assert_eq!(compress::<&str>(&[]), "");
+        assert_eq!(compress(&["foo"]), "foo");
+        assert_eq!(compress(&["node1"]), "node1");
+        assert_eq!(
+            compress(&["node3", "node1", "node2", "node5", "node1"]),
+            "node[1-3,5]"
+        );
+        assert_eq!(compress(&["node9", "node10"]), "node[9-10]");
+        assert_eq!(
+            compress(&["gpu1", "login", "node01", "gpu2", "node02", "node3"]),
+            "gpu[1-2],login,node[01-02],node3"
+        );
+        assert_eq!(
+            compress(&["rack1-node1", "rack1-node2", "rack2-node1"]),
+            "rack1-node[1-2],rack2-node1"
+        );
+    }
+
+    #[test]
+    fn compress_roundtrips() {
+        for hostlist in &[
+            "node[001-016,020].cluster",
+            "gpu[1-2],login,node[01-02],node3",
+            "node[8-10]",
+            "a0,b[00-01]",
+        ] {
+            assert_eq!(compress(&expand(hostlist).unwrap()), *hostlist);
+        }
+    }
+}
@@ -0,0 +1,846 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A validated hostname type.
+
+use std::borrow::Cow;
+use std::convert::TryFrom;
+use std::fmt;
+use std::str::FromStr;
+
+use crate::error::HostnameError;
+use crate::options::{ParseOptions, Strictness};
+
+/// The maximum length of a hostname in bytes, as per [RFC 1123].
+///
+/// [RFC 1123]: https://tools.ietf.org/html/rfc1123#section-2.1
+pub(crate) const MAX_HOSTNAME_LEN: usize = 253;
+
+/// The maximum length of a single label in bytes.
+pub(crate) const MAX_LABEL_LEN: usize = 63;
+
+/// The maximum length of NetBIOS computer names, i.e. `MAX_COMPUTERNAME_LENGTH`.
+pub(crate) const MAX_NETBIOS_LEN: usize = 15;
+
+/// A valid hostname.
+///
+/// A hostname is a non-empty sequence of labels separated by dots, as per
+/// [RFC 1123]:
+///
+/// * The whole name is at most 253 bytes long, and every label is between 1 and
+///   63 bytes long.
+/// * Labels consist of letters, digits and hyphens, but must neither start nor
+///   end with a hyphen.
+/// * The last label is not entirely numeric, so that a hostname never looks
+///   like an IPv4 address.
+///
+/// [`Hostname::parse_with()`] accepts more or fewer names depending on its
+/// [`Strictness`].
+///
+/// Besides ASCII letters a hostname may contain non-ASCII letters and digits,
+/// to represent internationalized hostnames in their unicode form.  With the
+/// `unicode-normalization` feature these are normalized to [NFC], so that the
+/// same name compares equal regardless of the form the system returned it in.
+/// Note that positions in errors then refer to the normalized name.
+///
+/// ```
+/// use gethostname::Hostname;
+///
+/// let hostname: Hostname = "foo.example.com".parse().unwrap();
+/// assert_eq!(hostname.as_str(), "foo.example.com");
+/// assert!("-foo".parse::<Hostname>().is_err());
+/// ```
+///
+/// [RFC 1123]: https://tools.ietf.org/html/rfc1123#section-2.1
+/// [NFC]: https://unicode.org/reports/tr15/
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Hostname(Cow<'static, str>);
+
+impl Hostname {
+    /// The placeholder `unknown`, for hosts whose name is not known.
+    ///
+    /// Use it as default which doesn't need to be parsed at runtime:
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname = Hostname::current().unwrap_or(Hostname::UNKNOWN);
+    /// assert_eq!(Hostname::UNKNOWN.as_str(), "unknown");
+    /// ```
+    pub const UNKNOWN: Hostname = Hostname(Cow::Borrowed(UNKNOWN_NAME));
+
+    /// Get the hostname of the current machine.
+    ///
+    /// Fail if [`try_gethostname()`](crate::try_gethostname) fails, or if the
+    /// hostname is not a valid hostname.
+    pub fn current() -> Result<Hostname, HostnameError> {
+        Hostname::current_with(&ParseOptions::default())
+    }
+
+    /// The hostname `localhost`.
+    pub const fn localhost() -> Hostname {
+        Hostname(Cow::Borrowed("localhost"))
+    }
+
+    /// Create a hostname from a static `name` without allocating.
+    ///
+    /// Validate `name` like parsing does with the default [`Strictness`], but
+    /// only accept ASCII names, since unicode rules aren't available at
+    /// compile time.  Panic if `name` isn't valid; in constants this fails to
+    /// compile instead:
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// const DEFAULT: Hostname = Hostname::from_static("build.example.com");
+    /// assert_eq!(DEFAULT, "build.example.com".parse().unwrap());
+    /// ```
+    ///
+    /// ```compile_fail
+    /// use gethostname::Hostname;
+    ///
+    /// const INVALID: Hostname = Hostname::from_static("-build.example.com");
+    /// ```
+    pub const fn from_static(name: &'static str) -> Hostname {
+        if !is_valid_ascii(name.as_bytes()) {
+            panic!("Hostname::from_static() requires a valid ASCII hostname");
+        }
+        Hostname(Cow::Borrowed(name))
+    }
+
+    /// Create a hostname from a `name` which is known to be valid.
+    ///
+    /// `name` may come from a hostname parsed with any strictness, so only
+    /// assert that it's valid with [`Strictness::Liberal`].
+    pub(crate) fn new_unchecked(name: String) -> Hostname {
+        debug_assert!(
+            validate(&name, Strictness::Liberal).is_ok(),
+            "invalid hostname {:?}",
+            name
+        );
+        Hostname(Cow::Owned(name))
+    }
+
+    /// Get mutable access to the underlying string.
+    ///
+    /// The caller must leave a valid hostname, or make sure that the hostname
+    /// is only dropped afterwards.
+    #[cfg(feature = "zeroize")]
+    pub(crate) fn as_mut_cow(&mut self) -> &mut Cow<'static, str> {
+        &mut self.0
+    }
+
+    /// Get this hostname as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Convert this hostname into a string.
+    pub fn into_string(mut self) -> String {
+        // Hostname implements Drop with the zeroize feature, so we can't move
+        // out of it
+        std::mem::take(&mut self.0).into_owned()
+    }
+
+    /// Map this hostname to a metric label.
+    ///
+    /// Lowercase all ASCII letters and replace every character other than ASCII
+    /// letters and digits with an underscore, and prefix the label with an
+    /// underscore if it starts with a digit.  The result is a valid
+    /// [Prometheus] label name and value, and a valid StatsD tag.
+    ///
+    /// Note that the mapping is not injective: `foo-bar` and `foo.bar` both map
+    /// to `foo_bar`.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname: Hostname = "Web-01.example.com".parse().unwrap();
+    /// assert_eq!(hostname.to_metric_label(), "web_01_example_com");
+    /// ```
+    ///
+    /// [Prometheus]: https://prometheus.io/docs/concepts/data_model/#metric-names-and-labels
+    pub fn to_metric_label(&self) -> String {
+        let mut label = String::with_capacity(self.0.len() + 1);
+        if self.0.starts_with(|c: char| c.is_ascii_digit()) {
+            label.push('_');
+        }
+        label.extend(self.0.chars().map(|c| {
+            if c.is_ascii_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                '_'
+            }
+        }));
+        label
+    }
+
+    /// Truncate every label of this hostname to at most `max` bytes.
+    ///
+    /// Truncate at character boundaries and trim hyphens left at the end of
+    /// truncated labels.  Return `None` if no valid hostname remains, e.g. if
+    /// `max` is 0 or if the truncated last label is entirely numeric.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname: Hostname = "build-server.example.com".parse().unwrap();
+    /// assert_eq!(hostname.truncate_label(6).unwrap().as_str(), "build.exampl.com");
+    /// ```
+    pub fn truncate_label(&self, max: usize) -> Option<Hostname> {
+        let labels = self
+            .0
+            .split('.')
+            .map(|label| crate::sanitize::truncate_label(label, max))
+            .collect::<Vec<_>>();
+        revalidate(labels.join("."))
+    }
+
+    /// Truncate this hostname to at most `max` bytes.
+    ///
+    /// Keep as many leading labels as fit into `max` bytes, and drop trailing
+    /// labels which are entirely numeric.  If not even the first label fits
+    /// truncate it like [`truncate_label()`](Hostname::truncate_label).  Return
+    /// `None` if no valid hostname remains.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname: Hostname = "build-server.example.com".parse().unwrap();
+    /// assert_eq!(hostname.truncate_fqdn(20).unwrap().as_str(), "build-server.example");
+    /// assert_eq!(hostname.truncate_fqdn(10).unwrap().as_str(), "build-serv");
+    /// ```
+    pub fn truncate_fqdn(&self, max: usize) -> Option<Hostname> {
+        if self.0.len() <= max {
+            return Some(self.clone());
+        }
+        let mut len = 0;
+        let mut labels = self
+            .0
+            .split('.')
+            .take_while(|label| {
+                len += label.len() + usize::from(len != 0);
+                len <= max
+            })
+            .collect::<Vec<_>>();
+        while 1 < labels.len() && labels.last().is_some_and(|l| is_numeric(l)) {
+            labels.pop();
+        }
+        if labels.is_empty() {
+            let first = self.0.split('.').next().unwrap_or("");
+            labels.push(crate::sanitize::truncate_label(first, max));
+        }
+        revalidate(labels.join("."))
+    }
+
+    /// Get the top-level domain of this hostname, i.e. its last label.
+    ///
+    /// Return `None` for single-label names like `localhost`, which have no
+    /// top-level domain.  A hostname never ends with a dot; use
+    /// [`Fqdn::tld()`](crate::Fqdn::tld) for names which may.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname: Hostname = "printer.office.corp".parse().unwrap();
+    /// assert_eq!(hostname.tld(), Some("corp"));
+    /// assert_eq!("localhost".parse::<Hostname>().unwrap().tld(), None);
+    /// ```
+    pub fn tld(&self) -> Option<&str> {
+        tld(&self.0)
+    }
+
+    /// Get the number at the end of the first label of this hostname.
+    ///
+    /// Clustered applications often derive their shard or replica from the
+    /// index in their hostname, e.g. `7` for `web-07.example.com`.  Return
+    /// `None` if the first label doesn't end with a digit, or if its number
+    /// doesn't fit into `u64`.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let hostname: Hostname = "web-07.example.com".parse().unwrap();
+    /// let index = hostname.trailing_index().unwrap();
+    /// assert_eq!(index.value(), 7);
+    /// assert_eq!(index.width(), 2);
+    /// assert_eq!(index.to_string(), "07");
+    /// ```
+    pub fn trailing_index(&self) -> Option<TrailingIndex> {
+        let label = self.0.split('.').next().unwrap_or("");
+        let digits = &label[label.trim_end_matches(|c: char| c.is_ascii_digit()).len()..];
+        Some(TrailingIndex {
+            value: digits.parse().ok()?,
+            width: digits.len(),
+        })
+    }
+
+    /// Whether this hostname and `other` name the same host.
+    ///
+    /// Compare the ASCII forms of both names after [IDNA] processing, which
+    /// maps unicode labels to punycode, ignores case and normalizes unicode,
+    /// so that `bücher.example`, `Bücher.Example` and `xn--bcher-kva.example`
+    /// are all equivalent.  Fall back to comparing both names with their
+    /// [case folded](crate::fold_case) if either isn't a valid
+    /// internationalized name.
+    ///
+    /// Requires the `idna` feature.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let unicode: Hostname = "bücher.example".parse().unwrap();
+    /// let punycode: Hostname = "xn--bcher-kva.example".parse().unwrap();
+    /// assert_ne!(unicode, punycode);
+    /// assert!(unicode.equivalent(&punycode));
+    /// ```
+    ///
+    /// [IDNA]: https://www.unicode.org/reports/tr46/
+    #[cfg(feature = "idna")]
+    pub fn equivalent(&self, other: &Hostname) -> bool {
+        match (
+            idna::domain_to_ascii(&self.0),
+            idna::domain_to_ascii(&other.0),
+        ) {
+            (Ok(this), Ok(other)) => this == other,
+            _ => crate::fold_case(&self.0) == crate::fold_case(&other.0),
+        }
+    }
+}
+
+/// The number at the end of the first label of a hostname.
+///
+/// See [`Hostname::trailing_index()`].  Display the index with its original
+/// zero-padding.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TrailingIndex {
+    value: u64,
+    width: usize,
+}
+
+impl TrailingIndex {
+    /// The numeric value of the index.
+    pub fn value(&self) -> u64 {
+        self.value
+    }
+
+    /// The number of digits of the index, including leading zeros.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+}
+
+impl fmt::Display for TrailingIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:0width$}", self.value, width = self.width)
+    }
+}
+
+/// Whether `name` is a valid hostname with ASCII characters only, with the
+/// default strictness.
+///
+/// Like [`validate()`], but usable in constants.
+const fn is_valid_ascii(name: &[u8]) -> bool {
+    if name.is_empty() || MAX_HOSTNAME_LEN < name.len() {
+        return false;
+    }
+    let mut start = 0;
+    let mut numeric = true;
+    let mut i = 0;
+    while i <= name.len() {
+        if i == name.len() || name[i] == b'.' {
+            let len = i - start;
+            if len == 0 || MAX_LABEL_LEN < len || name[start] == b'-' || name[i - 1] == b'-' {
+                return false;
+            }
+            if i == name.len() {
+                return !numeric;
+            }
+            start = i + 1;
+            numeric = true;
+        } else if name[i].is_ascii_alphanumeric() || name[i] == b'-' {
+            numeric = numeric && name[i].is_ascii_digit();
+        } else {
+            return false;
+        }
+        i += 1;
+    }
+    false
+}
+
+/// Get the last label of `name`, if `name` has more than one label.
+pub(crate) fn tld(name: &str) -> Option<&str> {
+    name.rsplit_once('.').map(|(_, tld)| tld)
+}
+
+pub(crate) fn is_numeric(label: &str) -> bool {
+    label.bytes().all(|b| b.is_ascii_digit())
+}
+
+fn validate(s: &str, strictness: Strictness) -> Result<(), HostnameError> {
+    match violations(s, strictness).first() {
+        Some(&(reason, position)) => Err(HostnameError::invalid_name(reason, position)),
+        None => Ok(()),
+    }
+}
+
+/// Find all reasons why `s` is not a valid hostname, with their positions.
+///
+/// Report at most one violation per label, and stop at the first violation
+/// if `s` is empty.
+pub(crate) fn violations(s: &str, strictness: Strictness) -> Vec<(InvalidNameReason, usize)> {
+    let mut violations = Vec::new();
+    if s.is_empty() {
+        violations.push((InvalidNameReason::Empty, 0));
+        return violations;
+    }
+    if MAX_HOSTNAME_LEN < s.len() {
+        violations.push((InvalidNameReason::TooLong, MAX_HOSTNAME_LEN));
+    }
+    let mut start = 0;
+    let mut last_label = "";
+    for label in s.split('.') {
+        if let Some(violation) = label_violation(label, start, strictness) {
+            violations.push(violation);
+        }
+        last_label = label;
+        start += label.len() + 1;
+    }
+    if is_numeric(last_label) {
+        violations.push((
+            InvalidNameReason::NumericTopLabel,
+            s.len() - last_label.len(),
+        ));
+    }
+    violations
+}
+
+/// Find the first reason why `label` at `start` is not a valid label.
+fn label_violation(
+    label: &str,
+    start: usize,
+    strictness: Strictness,
+) -> Option<(InvalidNameReason, usize)> {
+    if label.is_empty() {
+        return Some((InvalidNameReason::EmptyLabel, start));
+    }
+    if MAX_LABEL_LEN < label.len() {
+        return Some((InvalidNameReason::LabelTooLong, start + MAX_LABEL_LEN));
+    }
+    if let Some((offset, c)) = label.char_indices().find(|&(_, c)| {
+        !(c.is_ascii_alphanumeric()
+            || c == '-'
+            || (c == '_' && strictness == Strictness::Liberal)
+            || (!c.is_ascii() && c.is_alphanumeric()))
+    }) {
+        return Some((InvalidNameReason::InvalidCharacter(c), start + offset));
+    }
+    if strictness == Strictness::Rfc952 && label.starts_with(|c: char| c.is_ascii_digit()) {
+        return Some((InvalidNameReason::LeadingDigit, start));
+    }
+    if label.starts_with('-') {
+        return Some((InvalidNameReason::LeadingHyphen, start));
+    }
+    if label.ends_with('-') {
+        return Some((InvalidNameReason::TrailingHyphen, start + label.len() - 1));
+    }
+    None
+}
+
+impl FromStr for Hostname {
+    type Err = HostnameError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Hostname::try_from(s.to_string())
+    }
+}
+
+impl TryFrom<String> for Hostname {
+    type Error = HostnameError;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        Hostname::validated(s, Strictness::default())
+    }
+}
+
+impl Hostname {
+    /// Normalize and validate `s` with the given `strictness`.
+    pub(crate) fn validated(s: String, strictness: Strictness) -> Result<Hostname, HostnameError> {
+        let s = normalize(s);
+        match validate(&s, strictness) {
+            Ok(()) => Ok(Hostname(Cow::Owned(s))),
+            Err(error) => {
+                crate::zeroize::wipe(s);
+                Err(error)
+            }
+        }
+    }
+}
+
+/// The name of [`Hostname::UNKNOWN`].
+///
+/// With the `zeroize` feature `Hostname` implements `Drop`, so temporaries of
+/// `Hostname::UNKNOWN` would call back into `zeroize()`, which uses this name.
+pub(crate) const UNKNOWN_NAME: &str = "unknown";
+
+/// Validate `name` truncated from a hostname.
+///
+/// The hostname may have been parsed with any strictness, so validate as
+/// liberally as possible; truncating only removes characters, so this only
+/// rejects empty labels and numeric last labels which truncating left.
+fn revalidate(name: String) -> Option<Hostname> {
+    Hostname::validated(name, Strictness::Liberal).ok()
+}
+
+/// Normalize `s` to unicode normalization form C.
+///
+/// macOS returns computer names in decomposed form, whereas most other systems
+/// use the composed form; normalizing makes hostnames from either compare
+/// equal.
+#[cfg(feature = "unicode-normalization")]
+fn normalize(s: String) -> String {
+    use unicode_normalization::{is_nfc_quick, IsNormalized, UnicodeNormalization};
+    match is_nfc_quick(s.chars()) {
+        IsNormalized::Yes => s,
+        _ => {
+            let normalized = s.nfc().collect();
+            crate::zeroize::wipe(s);
+            normalized
+        }
+    }
+}
+
+#[cfg(not(feature = "unicode-normalization"))]
+fn normalize(s: String) -> String {
+    s
+}
+
+impl<'a> TryFrom<&'a str> for Hostname {
+    type Error = HostnameError;
+
+    fn try_from(s: &'a str) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<Hostname> for String {
+    fn from(hostname: Hostname) -> Self {
+        hostname.into_string()
+    }
+}
+
+impl AsRef<str> for Hostname {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Display for Hostname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(feature = "defmt")]
+impl defmt::Format for Hostname {
+    fn format(&self, f: defmt::Formatter) {
+        defmt::write!(f, "{=str}", self.as_str())
+    }
+}
+
+/// Why a hostname is invalid.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "defmt", derive(defmt::Format))]
+#[non_exhaustive]
+pub enum InvalidNameReason {
+    /// The hostname is empty.
+    Empty,
+    /// The hostname exceeds 253 bytes.
+    TooLong,
+    /// The hostname contains an empty label, e.g. two consecutive dots.
+    EmptyLabel,
+    /// A label exceeds 63 bytes.
+    LabelTooLong,
+    /// The hostname contains a character not permitted in hostnames.
+    InvalidCharacter(char),
+    /// A label starts with a hyphen.
+    LeadingHyphen,
+    /// A label ends with a hyphen.
+    TrailingHyphen,
+    /// A label starts with a digit, which [RFC 952] forbids.
+    ///
+    /// [RFC 952]: https://tools.ietf.org/html/rfc952
+    LeadingDigit,
+    /// The last label is entirely numeric.
+    NumericTopLabel,
+    /// The hostname is an IP address rather than a domain name.
+    IpAddress,
+    /// The hostname is not valid unicode.
+    NotUnicode,
+    /// The hostname is not a valid internationalized domain name.
+    InvalidIdn,
+    /// A range in a [hostlist](crate::hostlist) is invalid, descending, or
+    /// too large.
+    InvalidRange,
+    /// The hostname exceeds the maximum length of hostnames on this
+    /// platform, see [`max_hostname_len()`](crate::max_hostname_len).
+    TooLongForPlatform,
+    /// The name exceeds the 15 characters of a NetBIOS name, which Windows
+    /// derives from the hostname.
+    NetbiosTooLong,
+}
+
+impl fmt::Display for InvalidNameReason {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidNameReason::Empty => f.write_str("hostname is empty"),
+            InvalidNameReason::TooLong => {
+                write!(f, "hostname exceeds {} bytes", MAX_HOSTNAME_LEN)
+            }
+            InvalidNameReason::EmptyLabel => f.write_str("empty label"),
+            InvalidNameReason::LabelTooLong => {
+                write!(f, "label exceeds {} bytes", MAX_LABEL_LEN)
+            }
+            InvalidNameReason::InvalidCharacter(c) => write!(f, "invalid character {:?}", c),
+            InvalidNameReason::LeadingHyphen => f.write_str("label starts with a hyphen"),
+            InvalidNameReason::TrailingHyphen => f.write_str("label ends with a hyphen"),
+            InvalidNameReason::LeadingDigit => f.write_str("label starts with a digit"),
+            InvalidNameReason::NumericTopLabel => f.write_str("last label is entirely numeric"),
+            InvalidNameReason::IpAddress => f.write_str("IP address instead of hostname"),
+            InvalidNameReason::NotUnicode => f.write_str("hostname is not valid unicode"),
+            InvalidNameReason::InvalidIdn => {
+                f.write_str("hostname is not a valid internationalized domain name")
+            }
+            InvalidNameReason::InvalidRange => f.write_str("invalid range in hostlist"),
+            InvalidNameReason::TooLongForPlatform => {
+                f.write_str("hostname exceeds the maximum length on this platform")
+            }
+            InvalidNameReason::NetbiosTooLong => {
+                write!(f, "name exceeds {} characters", MAX_NETBIOS_LEN)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Hostname, InvalidNameReason};
+    use crate::{HostnameError, ParseOptions, Strictness};
+    use pretty_assertions::assert_eq;
+
+    fn invalid(s: &str) -> (InvalidNameReason, usize) {
+        match s.parse::<Hostname>().unwrap_err() {
+            HostnameError::InvalidName { reason, position } => (reason, position),
+            error => panic!("Unexpected error: {}", error),
+        }
+    }
+
+    #[test]
+    fn valid_hostnames() {
+        for name in &[
+            "foo",
+            "foo.example.com",
+            "x-1.example",
+            "3com.com",
+            "bücher.example",
+            &"a".repeat(63),
+        ] {
+            assert_eq!(name.parse::<Hostname>().unwrap().as_str(), *name);
+        }
+    }
+
+    #[test]
+    fn invalid_hostnames() {
+        assert_eq!(invalid(""), (InvalidNameReason::Empty, 0));
+        assert_eq!(invalid("foo..bar"), (InvalidNameReason::EmptyLabel, 4));
+        assert_eq!(invalid("foo."), (InvalidNameReason::EmptyLabel, 4));
+        assert_eq!(
+            invalid(&format!("foo.{}", "a".repeat(64))),
+            (InvalidNameReason::LabelTooLong, 67)
+        );
+        assert_eq!(
+            invalid(&vec!["a".repeat(63); 5].join(".")),
+            (InvalidNameReason::TooLong, 253)
+        );
+        assert_eq!(
+            invalid("foo.b_r"),
+            (InvalidNameReason::InvalidCharacter('_'), 5)
+        );
+        assert_eq!(invalid("foo.-bar"), (InvalidNameReason::LeadingHyphen, 4));
+        assert_eq!(invalid("foo-.bar"), (InvalidNameReason::TrailingHyphen, 3));
+        assert_eq!(invalid("10.0.0.1"), (InvalidNameReason::NumericTopLabel, 7));
+    }
+
+    #[test]
+    #[cfg(feature = "unicode-normalization")]
+    fn normalizes_to_nfc() {
+        let decomposed: Hostname = "bu\u{308}cher.example".parse().unwrap();
+        let composed: Hostname = "b\u{fc}cher.example".parse().unwrap();
+        assert_eq!(decomposed, composed);
+        assert_eq!(decomposed.as_str(), "b\u{fc}cher.example");
+    }
+
+    #[test]
+    #[cfg(feature = "idna")]
+    fn equivalent_names() {
+        let equivalent = |a: &str, b: &str| {
+            let a = a.parse::<Hostname>().unwrap();
+            a.equivalent(&b.parse().unwrap())
+        };
+        assert!(equivalent("bücher.example", "xn--bcher-kva.example"));
+        assert!(equivalent("xn--bcher-kva.example", "Bücher.EXAMPLE"));
+        assert!(equivalent("Foo.example", "foo.example"));
+        assert!(!equivalent("bücher.example", "bucher.example"));
+        assert!(!equivalent("foo.example", "foo.example.com"));
+    }
+
+    #[test]
+    fn const_constructors() {
+        const DEFAULT: Hostname = Hostname::from_static("foo.example");
+        assert_eq!(DEFAULT, "foo.example".parse().unwrap());
+        assert_eq!(Hostname::localhost(), "localhost".parse().unwrap());
+        assert_eq!(Hostname::UNKNOWN, "unknown".parse().unwrap());
+        assert_eq!(Hostname::UNKNOWN.into_string(), "unknown");
+    }
+
+    #[test]
+    #[should_panic(expected = "requires a valid ASCII hostname")]
+    fn from_static_rejects_invalid_names() {
+        Hostname::from_static("foo..example");
+    }
+
+    #[test]
+    fn const_validation_agrees_with_parsing() {
+        let long_label = "a".repeat(64);
+        let long_name = vec!["a"; 127].join(".");
+        let too_long_name = format!("a.{}", long_name);
+        for name in &[
+            "",
+            "foo",
+            "foo.example",
+            "3com.com",
+            "foo-bar.example",
+            "-foo",
+            "foo-",
+            "foo.-bar",
+            "foo..bar",
+            ".foo",
+            "foo.",
+            "foo_bar",
+            "foo.123",
+            "123",
+            "10.0.0.1",
+            "1a.2b",
+            "bücher.example",
+            long_label.as_str(),
+            &long_label[1..],
+            long_name.as_str(),
+            &long_name[2..],
+            too_long_name.as_str(),
+        ] {
+            assert_eq!(
+                super::is_valid_ascii(name.as_bytes()),
+                name.is_ascii() && name.parse::<Hostname>().is_ok(),
+                "{:?}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn metric_labels() {
+        let label = |s: &str| s.parse::<Hostname>().unwrap().to_metric_label();
+        assert_eq!(label("foo"), "foo");
+        assert_eq!(label("Foo-Bar.example.com"), "foo_bar_example_com");
+        assert_eq!(label("3com.com"), "_3com_com");
+        assert_eq!(label("bücher.example"), "b_cher_example");
+    }
+
+    #[test]
+    fn truncate_labels() {
+        let truncate = |s: &str, max| {
+            s.parse::<Hostname>()
+                .unwrap()
+                .truncate_label(max)
+                .map(Hostname::into_string)
+        };
+        assert_eq!(truncate("foo.example.com", 63).unwrap(), "foo.example.com");
+        assert_eq!(truncate("foo-bar.example", 4).unwrap(), "foo.exam");
+        assert_eq!(truncate("bücher.example", 2).unwrap(), "b.ex");
+        assert_eq!(truncate("foo.1ab", 1), None);
+        assert_eq!(truncate("foo", 0), None);
+    }
+
+    #[test]
+    fn truncate_liberal_hostnames() {
+        let options = ParseOptions::new().strictness(Strictness::Liberal);
+        let hostname = Hostname::parse_with("foo_bar.my_domain.example", &options).unwrap();
+        assert_eq!(
+            hostname.truncate_label(6).unwrap().as_str(),
+            "foo_ba.my_dom.exampl"
+        );
+        assert_eq!(
+            hostname.truncate_fqdn(20).unwrap().as_str(),
+            "foo_bar.my_domain"
+        );
+    }
+
+    #[test]
+    fn truncate_fqdns() {
+        let truncate = |s: &str, max| {
+            s.parse::<Hostname>()
+                .unwrap()
+                .truncate_fqdn(max)
+                .map(Hostname::into_string)
+        };
+        assert_eq!(truncate("foo.example.com", 255).unwrap(), "foo.example.com");
+        assert_eq!(truncate("foo.example.com", 14).unwrap(), "foo.example");
+        assert_eq!(truncate("foo.123.com", 10).unwrap(), "foo");
+        assert_eq!(truncate("foo-bar.example", 4).unwrap(), "foo");
+        assert_eq!(truncate("123a.com", 3), None);
+    }
+
+    #[test]
+    fn tlds() {
+        let tld = |s: &str| s.parse::<Hostname>().unwrap().tld().map(str::to_string);
+        assert_eq!(tld("foo.example.com").unwrap(), "com");
+        assert_eq!(tld("nas.local").unwrap(), "local");
+        assert_eq!(tld("localhost"), None);
+    }
+
+    #[test]
+    fn trailing_indices() {
+        let index = |s: &str| {
+            s.parse::<Hostname>()
+                .unwrap()
+                .trailing_index()
+                .map(|index| (index.value(), index.width()))
+        };
+        assert_eq!(index("web-07"), Some((7, 2)));
+        assert_eq!(index("web-07.example.com"), Some((7, 2)));
+        assert_eq!(index("node12"), Some((12, 2)));
+        assert_eq!(index("node0"), Some((0, 1)));
+        assert_eq!(index("web.node-3"), None);
+        assert_eq!(index("web-a"), None);
+        assert_eq!(index(&format!("n{}", "9".repeat(20))), None);
+    }
+
+    #[test]
+    fn current_hostname_is_valid() {
+        assert_eq!(
+            Hostname::current().unwrap().as_str(),
+            crate::gethostname().to_str().unwrap()
+        );
+    }
+}
@@ -0,0 +1,165 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Names and metadata from [systemd-hostnamed].
+//!
+//! systemd-hostnamed manages the hostnames and some metadata of the machine on
+//! Linux systems with systemd, and offers them on the system bus.  Setters go
+//! through [polkit]; with `interactive` set polkit asks the user to
+//! authenticate if necessary, so unprivileged desktop applications can change
+//! these settings too.
+//!
+//! ```no_run
+//! use gethostname::hostnamed;
+//!
+//! hostnamed::set_pretty_hostname("Jane's Laptop", true).unwrap();
+//! println!("{}", hostnamed::pretty_hostname().unwrap());
+//! ```
+//!
+//! [systemd-hostnamed]: https://www.freedesktop.org/software/systemd/man/org.freedesktop.hostname1.html
+//! [polkit]: https://www.freedesktop.org/software/polkit/docs/latest/
+
+use std::convert::TryFrom;
+
+use zbus::blocking::Connection;
+use zbus::zvariant::OwnedValue;
+
+use crate::dry_run::{self, Change};
+use crate::error::HostnameError;
+use crate::hostname::Hostname;
+
+const DESTINATION: &str = "org.freedesktop.hostname1";
+const PATH: &str = "/org/freedesktop/hostname1";
+const INTERFACE: &str = "org.freedesktop.hostname1";
+
+fn hostnamed_failed(error: zbus::Error) -> HostnameError {
+    HostnameError::ServiceFailed {
+        service: "systemd-hostnamed",
+        message: error.to_string(),
+    }
+}
+
+/// Call a setter `method` of hostnamed with `value`, to change `property`.
+fn set(
+    method: &'static str,
+    property: &str,
+    value: &str,
+    interactive: bool,
+) -> Result<(), HostnameError> {
+    let change = Change::DbusCall {
+        destination: DESTINATION,
+        method,
+        argument: value.to_string(),
+        interactive,
+    };
+    dry_run::perform(
+        change,
+        || self::property(property).ok(),
+        || {
+            Connection::system()
+                .and_then(|connection| {
+                    connection.call_method(
+                        Some(DESTINATION),
+                        PATH,
+                        Some(INTERFACE),
+                        method,
+                        &(value, interactive),
+                    )
+                })
+                .map(|_| ())
+                .map_err(hostnamed_failed)
+        },
+    )
+}
+
+/// Get the string property `name` of hostnamed.
+pub(crate) fn property(name: &str) -> Result<String, HostnameError> {
+    let reply = Connection::system()
+        .and_then(|connection| {
+            connection.call_method(
+                Some(DESTINATION),
+                PATH,
+                Some("org.freedesktop.DBus.Properties"),
+                "Get",
+                &(INTERFACE, name),
+            )
+        })
+        .map_err(hostnamed_failed)?;
+    let value = reply.body::<OwnedValue>().map_err(hostnamed_failed)?;
+    String::try_from(value).map_err(|error| hostnamed_failed(error.into()))
+}
+
+/// Get the pretty hostname, e.g. `Jane's Laptop`.
+///
+/// Return an empty string if no pretty hostname is set.
+pub fn pretty_hostname() -> Result<String, HostnameError> {
+    property("PrettyHostname")
+}
+
+/// Set the pretty hostname, e.g. `Jane's Laptop`.
+///
+/// The pretty hostname is a free-form name for humans.  An empty `name`
+/// removes the pretty hostname.
+pub fn set_pretty_hostname(name: &str, interactive: bool) -> Result<(), HostnameError> {
+    set("SetPrettyHostname", "PrettyHostname", name, interactive)
+}
+
+/// Set the static hostname, i.e. the contents of `/etc/hostname`.
+pub fn set_static_hostname(name: &Hostname, interactive: bool) -> Result<(), HostnameError> {
+    set(
+        "SetStaticHostname",
+        "StaticHostname",
+        name.as_str(),
+        interactive,
+    )
+}
+
+/// Set the transient hostname, i.e. the hostname of the kernel.
+///
+/// The transient hostname lasts until the next reboot, or until the network
+/// configuration sets a different hostname.
+pub fn set_transient_hostname(name: &Hostname, interactive: bool) -> Result<(), HostnameError> {
+    set("SetHostname", "Hostname", name.as_str(), interactive)
+}
+
+/// Get the deployment environment, e.g. `production` or `staging`.
+///
+/// Return an empty string if no deployment environment is set.
+pub fn deployment() -> Result<String, HostnameError> {
+    property("Deployment")
+}
+
+/// Set the deployment environment, e.g. `production` or `staging`.
+///
+/// systemd recommends one of `development`, `integration`, `staging` and
+/// `production`, but hostnamed accepts any single word.  An empty `deployment`
+/// removes the deployment environment.
+pub fn set_deployment(deployment: &str, interactive: bool) -> Result<(), HostnameError> {
+    set("SetDeployment", "Deployment", deployment, interactive)
+}
+
+/// Get the location, e.g. `Berlin, Rack 23, Slot 5`.
+///
+/// Return an empty string if no location is set.
+pub fn location() -> Result<String, HostnameError> {
+    property("Location")
+}
+
+/// Set the location, e.g. `Berlin, Rack 23, Slot 5`.
+///
+/// The location is a free-form description for humans.  An empty `location`
+/// removes the location.
+pub fn set_location(location: &str, interactive: bool) -> Result<(), HostnameError> {
+    set("SetLocation", "Location", location, interactive)
+}
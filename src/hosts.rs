@@ -0,0 +1,319 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Names of the current machine in the hosts file.
+
+use std::net::IpAddr;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use crate::case::eq_folded;
+use crate::error::HostnameError;
+
+/// A line of a hosts file.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostsEntry {
+    address: IpAddr,
+    names: Vec<String>,
+}
+
+impl HostsEntry {
+    /// The address of this entry.
+    pub fn address(&self) -> IpAddr {
+        self.address
+    }
+
+    /// The names of this entry; the first name is the canonical name, the
+    /// others are aliases.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    /// Whether `name` is among the names of this entry, ignoring
+    /// [case](crate::fold_case).
+    fn has_name(&self, name: &str) -> bool {
+        self.names.iter().any(|n| eq_folded(n, name))
+    }
+}
+
+/// The entries of a hosts file like `/etc/hosts`.
+///
+/// ```
+/// use gethostname::HostsFile;
+///
+/// let hosts = HostsFile::parse("10.0.0.5 build.example.com build ci # build server");
+/// assert_eq!(hosts.aliases_of("build", &[]), ["build.example.com", "ci"]);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HostsFile {
+    entries: Vec<HostsEntry>,
+}
+
+impl HostsFile {
+    /// Create an empty hosts file, e.g. to [`add()`](HostsFile::add) entries
+    /// in memory.
+    pub fn new() -> HostsFile {
+        HostsFile::default()
+    }
+
+    /// Add an entry which maps `address` to `names`.
+    ///
+    /// ```
+    /// use gethostname::HostsFile;
+    ///
+    /// let mut hosts = HostsFile::new();
+    /// hosts.add("10.0.0.5".parse().unwrap(), ["build.example.com", "build"]);
+    /// assert_eq!(hosts.canonical_name("build"), Some("build.example.com"));
+    /// ```
+    pub fn add<I, S>(&mut self, address: IpAddr, names: I)
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        let names = names.into_iter().map(Into::into).collect::<Vec<_>>();
+        if !names.is_empty() {
+            self.entries.push(HostsEntry { address, names });
+        }
+    }
+
+    /// Parse the `contents` of a hosts file.
+    ///
+    /// Skip comments, and lines without a valid address or without names.
+    pub fn parse(contents: &str) -> HostsFile {
+        let entries = contents
+            .lines()
+            .filter_map(|line| {
+                let line = line.split('#').next().unwrap_or("");
+                let mut fields = line.split_whitespace();
+                let address = fields.next()?.parse().ok()?;
+                let names = fields.map(str::to_string).collect::<Vec<_>>();
+                if names.is_empty() {
+                    None
+                } else {
+                    Some(HostsEntry { address, names })
+                }
+            })
+            .collect();
+        HostsFile { entries }
+    }
+
+    /// Read and parse the hosts file at `path`.
+    pub fn read<P: AsRef<Path>>(path: P) -> Result<HostsFile, HostnameError> {
+        std::fs::read_to_string(path)
+            .map(|contents| HostsFile::parse(&contents))
+            .map_err(|error| HostnameError::from_io(&error))
+    }
+
+    /// Read and parse the hosts file of the system.
+    ///
+    /// On Unix read `/etc/hosts`, on Windows
+    /// `%SystemRoot%\System32\drivers\etc\hosts`.
+    pub fn system() -> Result<HostsFile, HostnameError> {
+        HostsFile::read(system_hosts_path())
+    }
+
+    /// The entries of this hosts file.
+    pub fn entries(&self) -> &[HostsEntry] {
+        &self.entries
+    }
+
+    /// Get the canonical name of `hostname`, i.e. the first name of the first
+    /// entry which has `hostname` among its names, ignoring
+    /// [case](crate::fold_case).
+    pub fn canonical_name(&self, hostname: &str) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.has_name(hostname))
+            .map(|entry| entry.names[0].as_str())
+    }
+
+    /// Get the addresses of all entries which have `hostname` among their
+    /// names, ignoring [case](crate::fold_case).
+    pub fn addresses_of(&self, hostname: &str) -> Vec<IpAddr> {
+        self.entries
+            .iter()
+            .filter(|entry| entry.has_name(hostname))
+            .map(|entry| entry.address)
+            .collect()
+    }
+
+    /// Get the name of `address`, i.e. the first name of the first entry for
+    /// `address`.
+    pub fn name_of(&self, address: IpAddr) -> Option<&str> {
+        self.entries
+            .iter()
+            .find(|entry| entry.address == address)
+            .map(|entry| entry.names[0].as_str())
+    }
+
+    /// Get all other names of `hostname` in this hosts file.
+    ///
+    /// Collect the names of all entries which have `hostname` among their
+    /// names, ignoring [case](crate::fold_case), or whose address is among
+    /// `addresses`, except for loopback addresses, which are shared with
+    /// `localhost`.
+    /// Omit `hostname` itself and duplicate names, and keep the order of the
+    /// file otherwise.
+    pub fn aliases_of(&self, hostname: &str, addresses: &[IpAddr]) -> Vec<String> {
+        let mut aliases: Vec<String> = Vec::new();
+        let matching = self.entries.iter().filter(|entry| {
+            entry.has_name(hostname)
+                || (!entry.address.is_loopback() && addresses.contains(&entry.address))
+        });
+        for name in matching.flat_map(|entry| &entry.names) {
+            if !eq_folded(name, hostname) && !aliases.iter().any(|a| eq_folded(a, name)) {
+                aliases.push(name.clone());
+            }
+        }
+        aliases
+    }
+}
+
+#[cfg(not(windows))]
+fn system_hosts_path() -> PathBuf {
+    PathBuf::from("/etc/hosts")
+}
+
+#[cfg(windows)]
+fn system_hosts_path() -> PathBuf {
+    let root = std::env::var_os("SystemRoot").unwrap_or_else(|| "C:\\Windows".into());
+    Path::new(&root).join("System32\\drivers\\etc\\hosts")
+}
+
+/// Get the aliases of the current machine from the hosts file.
+///
+/// Like `hostname --alias`, get the names the system hosts file maps to the
+/// hostname, or to the addresses the hostname resolves to, with
+/// [`HostsFile::aliases_of()`].  Environments without DNS often manage the
+/// names of machines only in hosts files.
+///
+/// Fail if getting the hostname or reading the hosts file fails, but don't
+/// fail if the hostname doesn't resolve.
+///
+/// ```
+/// println!("Aliases: {:?}", gethostname::host_aliases());
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn host_aliases() -> Result<Vec<String>, HostnameError> {
+    host_aliases_within(None)
+}
+
+/// Get the aliases of the current machine from the hosts file within `timeout`.
+///
+/// Like [`host_aliases()`], but give up resolving the hostname after
+/// `timeout`, and only use the names the hosts file maps to the hostname.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn host_aliases_timeout(timeout: Duration) -> Result<Vec<String>, HostnameError> {
+    host_aliases_within(Some(timeout))
+}
+
+fn host_aliases_within(timeout: Option<Duration>) -> Result<Vec<String>, HostnameError> {
+    let hostname = crate::try_gethostname()?.to_string_lossy().into_owned();
+    let name = hostname.clone();
+    let addresses = crate::resolver::within(timeout, move || crate::resolver::address_info(&name))
+        .unwrap_or_else(|error| {
+            warn!("Failed to resolve {}: {}", hostname, error);
+            Vec::new()
+        });
+    Ok(HostsFile::system()?.aliases_of(&hostname, &addresses))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostsFile;
+    use pretty_assertions::assert_eq;
+    use std::net::{IpAddr, Ipv4Addr};
+
+    const HOSTS: &str = "# Static table lookup for hostnames.
+127.0.0.1	localhost
+::1		localhost ip6-localhost ip6-loopback
+127.0.1.1	web-01.example.com web-01
+
+10.0.0.5 web-01.internal   api # the API alias
+10.0.0.6 db
+not-an-address foo
+10.0.0.7
+";
+
+    #[test]
+    fn parse_hosts_file() {
+        let hosts = HostsFile::parse(HOSTS);
+        let entries = hosts.entries();
+        assert_eq!(entries.len(), 5);
+        assert_eq!(entries[1].address(), "::1".parse::<IpAddr>().unwrap());
+        assert_eq!(
+            entries[1].names(),
+            ["localhost", "ip6-localhost", "ip6-loopback"]
+        );
+        assert_eq!(entries[3].names(), ["web-01.internal", "api"]);
+    }
+
+    #[test]
+    fn lookup_names_and_addresses() {
+        let hosts = HostsFile::parse(HOSTS);
+        assert_eq!(hosts.canonical_name("WEB-01"), Some("web-01.example.com"));
+        assert_eq!(hosts.canonical_name("api"), Some("web-01.internal"));
+        assert_eq!(hosts.canonical_name("unknown"), None);
+        assert_eq!(
+            hosts.addresses_of("localhost"),
+            [
+                IpAddr::V4(Ipv4Addr::LOCALHOST),
+                "::1".parse::<IpAddr>().unwrap()
+            ]
+        );
+        assert_eq!(
+            hosts.name_of(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 6))),
+            Some("db")
+        );
+        assert_eq!(hosts.name_of(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 7))), None);
+    }
+
+    #[test]
+    fn add_entries_in_memory() {
+        let mut hosts = HostsFile::new();
+        hosts.add(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 1)), ["a.example", "a"]);
+        hosts.add(IpAddr::V4(Ipv4Addr::new(10, 0, 0, 2)), Vec::<String>::new());
+        assert_eq!(hosts.entries().len(), 1);
+        assert_eq!(hosts.canonical_name("a"), Some("a.example"));
+    }
+
+    #[test]
+    fn aliases_by_name() {
+        let hosts = HostsFile::parse(HOSTS);
+        assert_eq!(hosts.aliases_of("web-01", &[]), ["web-01.example.com"]);
+        assert_eq!(hosts.aliases_of("WEB-01", &[]), ["web-01.example.com"]);
+        assert_eq!(hosts.aliases_of("db", &[]), Vec::<String>::new());
+        assert_eq!(hosts.aliases_of("unknown", &[]), Vec::<String>::new());
+    }
+
+    #[test]
+    fn aliases_by_address() {
+        let hosts = HostsFile::parse(HOSTS);
+        let addresses = [
+            IpAddr::V4(Ipv4Addr::new(10, 0, 0, 5)),
+            IpAddr::V4(Ipv4Addr::LOCALHOST),
+        ];
+        assert_eq!(
+            hosts.aliases_of("web-01", &addresses),
+            ["web-01.example.com", "web-01.internal", "api"]
+        );
+    }
+
+    #[test]
+    fn aliases_of_current_machine() {
+        let aliases = super::host_aliases().unwrap();
+        let hostname = crate::gethostname();
+        assert!(!aliases.iter().any(|alias| alias.as_str() == hostname));
+    }
+}
@@ -0,0 +1,109 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Integration with the [http] crate.
+//!
+//! ```
+//! let host = gethostname::http::host_header(Some(8080)).unwrap();
+//! println!("Host: {:?}", host);
+//! ```
+//!
+//! [http]: https://docs.rs/http
+
+use std::ffi::OsStr;
+
+use ::http::HeaderValue;
+
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+
+/// Get a value for the HTTP `Host` header of the current machine.
+///
+/// Take the hostname from [`try_gethostname()`](crate::try_gethostname) and append
+/// `port` if given.  Internationalized hostnames are converted to their ASCII
+/// form with [punycode] first, as the `Host` header must not contain non-ASCII
+/// characters.
+///
+/// Fail with [`HostnameError::InvalidName`] if the hostname is not valid
+/// unicode, cannot be converted to ASCII, or does not make a valid header
+/// value.
+///
+/// [punycode]: https://tools.ietf.org/html/rfc3492
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn host_header(port: Option<u16>) -> Result<HeaderValue, HostnameError> {
+    host_header_value(&crate::try_gethostname()?, port)
+}
+
+fn host_header_value(hostname: &OsStr, port: Option<u16>) -> Result<HeaderValue, HostnameError> {
+    let hostname = hostname.to_str().ok_or_else(|| {
+        HostnameError::invalid_name(
+            InvalidNameReason::NotUnicode,
+            hostname.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+        )
+    })?;
+    let host = if hostname.is_ascii() {
+        hostname.to_string()
+    } else {
+        idna::domain_to_ascii(hostname)
+            .map_err(|_| HostnameError::invalid_name(InvalidNameReason::InvalidIdn, 0))?
+    };
+    let value = match port {
+        Some(port) => format!("{}:{}", host, port),
+        None => host,
+    };
+    HeaderValue::from_str(&value).map_err(|_| {
+        // Header values must not contain control characters
+        let (position, c) = value
+            .char_indices()
+            .find(|(_, c)| c.is_ascii_control())
+            .unwrap_or((0, '\0'));
+        HostnameError::invalid_name(InvalidNameReason::InvalidCharacter(c), position)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::host_header_value;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn ascii_hostname_without_port() {
+        let value = host_header_value(OsStr::new("foo.example.com"), None).unwrap();
+        assert_eq!(value, "foo.example.com");
+    }
+
+    #[test]
+    fn ascii_hostname_with_port() {
+        let value = host_header_value(OsStr::new("foo"), Some(8080)).unwrap();
+        assert_eq!(value, "foo:8080");
+    }
+
+    #[test]
+    fn non_ascii_hostname_uses_punycode() {
+        let value = host_header_value(OsStr::new("bücher.example"), Some(443)).unwrap();
+        assert_eq!(value, "xn--bcher-kva.example:443");
+    }
+
+    #[test]
+    fn control_characters_are_rejected() {
+        assert!(host_header_value(OsStr::new("foo\nbar"), None).is_err());
+    }
+
+    #[test]
+    fn host_header_of_current_machine() {
+        let value = super::host_header(None).unwrap();
+        assert!(!value.is_empty());
+    }
+}
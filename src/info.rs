@@ -0,0 +1,324 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A snapshot of the identity of the current machine.
+
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// A snapshot of the names and identity of the current machine.
+///
+/// See [`HostInfo::collect()`].  With the `serde` feature this type
+/// implements `serde::Serialize`, e.g. to include it in crash reports.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct HostInfo {
+    hostname: String,
+    short_name: String,
+    fqdn: Option<String>,
+    domain: Option<String>,
+    machine_id: Option<String>,
+    pretty_name: Option<String>,
+    os: OsInfo,
+}
+
+/// The operating system of the current machine.
+///
+/// See [`HostInfo::os()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct OsInfo {
+    family: &'static str,
+    arch: &'static str,
+    name: Option<String>,
+    version: Option<String>,
+}
+
+impl HostInfo {
+    /// Collect information about the current machine.
+    ///
+    /// Get all information at once, so that it's consistent even if some of
+    /// it changes later.  Only fail if we cannot get the hostname; leave out
+    /// all other information which is not available.
+    ///
+    /// ```
+    /// let info = gethostname::HostInfo::collect().unwrap();
+    /// println!("{} ({:?})", info.hostname(), info.fqdn());
+    /// ```
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn collect() -> Result<HostInfo, HostnameError> {
+        HostInfo::collect_within(None)
+    }
+
+    /// Collect information about the current machine within `timeout`.
+    ///
+    /// Like [`collect()`](HostInfo::collect), but give up resolving the FQDN
+    /// after `timeout`, and leave it out then, as with
+    /// [`getfqdn_timeout()`](crate::getfqdn_timeout).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn collect_timeout(timeout: Duration) -> Result<HostInfo, HostnameError> {
+        HostInfo::collect_within(Some(timeout))
+    }
+
+    fn collect_within(timeout: Option<Duration>) -> Result<HostInfo, HostnameError> {
+        let hostname = crate::try_gethostname()?.to_string_lossy().into_owned();
+        let fqdn = crate::fqdn::getfqdn_within(timeout)
+            .map_err(|error| warn!("Failed to get FQDN: {}", error))
+            .ok()
+            .map(|fqdn| fqdn.to_string_lossy().trim_end_matches('.').to_string());
+        let domain = fqdn
+            .as_deref()
+            .and_then(|fqdn| fqdn.split_once('.'))
+            .map(|(_, domain)| domain.to_string());
+        Ok(HostInfo {
+            short_name: short_name(&hostname).to_string(),
+            hostname,
+            fqdn,
+            domain,
+            machine_id: crate::machine_id().ok(),
+            pretty_name: pretty_name(),
+            os: OsInfo::collect(),
+        })
+    }
+
+    /// The hostname, as returned by [`gethostname()`](crate::gethostname).
+    pub fn hostname(&self) -> &str {
+        &self.hostname
+    }
+
+    /// The hostname up to the first dot.
+    pub fn short_name(&self) -> &str {
+        &self.short_name
+    }
+
+    /// The fully qualified domain name, as returned by
+    /// [`getfqdn()`](crate::getfqdn), without a trailing dot.
+    pub fn fqdn(&self) -> Option<&str> {
+        self.fqdn.as_deref()
+    }
+
+    /// The domain, i.e. the FQDN after the first dot, if any.
+    pub fn domain(&self) -> Option<&str> {
+        self.domain.as_deref()
+    }
+
+    /// The unique ID of the machine, as returned by
+    /// [`machine_id()`](crate::machine_id).
+    pub fn machine_id(&self) -> Option<&str> {
+        self.machine_id.as_deref()
+    }
+
+    /// The pretty name of the machine, e.g. `Jane's Laptop`.
+    ///
+    /// On Linux this is the `PRETTY_HOSTNAME` from [`/etc/machine-info`][1],
+    /// on macOS the computer name, and on Android, iOS, tvOS, watchOS and
+    /// visionOS the device name from `device_name()`, with the `android` or
+    /// `ios` feature respectively.
+    ///
+    /// [1]: https://www.freedesktop.org/software/systemd/man/machine-info.html
+    pub fn pretty_name(&self) -> Option<&str> {
+        self.pretty_name.as_deref()
+    }
+
+    /// The operating system.
+    pub fn os(&self) -> &OsInfo {
+        &self.os
+    }
+}
+
+impl OsInfo {
+    fn collect() -> OsInfo {
+        let (name, version) = os_name_and_version();
+        OsInfo {
+            family: std::env::consts::OS,
+            arch: std::env::consts::ARCH,
+            name,
+            version,
+        }
+    }
+
+    /// The operating system family, e.g. `linux` or `macos`.
+    ///
+    /// See [`std::env::consts::OS`].
+    pub fn family(&self) -> &'static str {
+        self.family
+    }
+
+    /// The CPU architecture, e.g. `x86_64` or `aarch64`.
+    ///
+    /// See [`std::env::consts::ARCH`].
+    pub fn arch(&self) -> &'static str {
+        self.arch
+    }
+
+    /// The name of the operating system, e.g. `Fedora Linux` or `macOS`.
+    ///
+    /// On Linux this is the `NAME` from [`/etc/os-release`][1], and on macOS
+    /// the product name of `sw_vers`.
+    ///
+    /// [1]: https://www.freedesktop.org/software/systemd/man/os-release.html
+    pub fn name(&self) -> Option<&str> {
+        self.name.as_deref()
+    }
+
+    /// The version of the operating system, e.g. `40` or `14.4.1`.
+    ///
+    /// On Linux this is the `VERSION_ID` from `/etc/os-release`, and on macOS
+    /// the product version of `sw_vers`.
+    pub fn version(&self) -> Option<&str> {
+        self.version.as_deref()
+    }
+}
+
+fn short_name(hostname: &str) -> &str {
+    hostname.split('.').next().unwrap_or(hostname)
+}
+
+/// Get the value of `key` from the contents of an environment-like file
+/// such as `/etc/os-release`.
+#[cfg(any(target_os = "linux", test))]
+fn env_file_value(contents: &str, key: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (k, value) = line.trim().split_once('=')?;
+        if k != key {
+            return None;
+        }
+        let value = value.trim_matches(|c| c == '"' || c == '\'');
+        Some(value.to_string()).filter(|v| !v.is_empty())
+    })
+}
+
+#[cfg(target_os = "linux")]
+pub(crate) fn pretty_name() -> Option<String> {
+    let contents = std::fs::read_to_string("/etc/machine-info").ok()?;
+    env_file_value(&contents, "PRETTY_HOSTNAME")
+}
+
+#[cfg(target_os = "macos")]
+pub(crate) fn pretty_name() -> Option<String> {
+    command_output("scutil", &["--get", "ComputerName"])
+}
+
+#[cfg(any(
+    all(target_os = "android", feature = "android"),
+    all(target_vendor = "apple", not(target_os = "macos"), feature = "ios")
+))]
+pub(crate) fn pretty_name() -> Option<String> {
+    crate::device_name()
+        .map_err(|error| warn!("Failed to get device name: {}", error))
+        .ok()
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "macos",
+    all(target_os = "android", feature = "android"),
+    all(target_vendor = "apple", not(target_os = "macos"), feature = "ios")
+)))]
+pub(crate) fn pretty_name() -> Option<String> {
+    None
+}
+
+#[cfg(target_os = "linux")]
+fn os_name_and_version() -> (Option<String>, Option<String>) {
+    // os-release(5) requires applications to fall back to /usr/lib/os-release
+    let contents = std::fs::read_to_string("/etc/os-release")
+        .or_else(|_| std::fs::read_to_string("/usr/lib/os-release"))
+        .unwrap_or_default();
+    (
+        env_file_value(&contents, "NAME"),
+        env_file_value(&contents, "VERSION_ID"),
+    )
+}
+
+#[cfg(target_os = "macos")]
+fn os_name_and_version() -> (Option<String>, Option<String>) {
+    (
+        command_output("sw_vers", &["-productName"]),
+        command_output("sw_vers", &["-productVersion"]),
+    )
+}
+
+#[cfg(all(target_vendor = "apple", not(target_os = "macos")))]
+fn os_name_and_version() -> (Option<String>, Option<String>) {
+    // Apps can't run sw_vers here
+    let name = match std::env::consts::OS {
+        "ios" => "iOS",
+        "tvos" => "tvOS",
+        "watchos" => "watchOS",
+        "visionos" => "visionOS",
+        other => other,
+    };
+    (
+        Some(name.to_string()),
+        crate::hardware::sysctl_string(b"kern.osproductversion\0", "kern.osproductversion").ok(),
+    )
+}
+
+#[cfg(not(any(target_os = "linux", target_vendor = "apple")))]
+fn os_name_and_version() -> (Option<String>, Option<String>) {
+    (None, None)
+}
+
+/// Run `program` with `args` and return its trimmed output, if any.
+#[cfg(target_os = "macos")]
+fn command_output(program: &str, args: &[&str]) -> Option<String> {
+    let output = std::process::Command::new(program)
+        .args(args)
+        .output()
+        .ok()
+        .filter(|output| output.status.success())?;
+    let output = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    Some(output).filter(|output| !output.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{env_file_value, short_name, HostInfo};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn short_names() {
+        assert_eq!(short_name("foo.example.com"), "foo");
+        assert_eq!(short_name("foo"), "foo");
+    }
+
+    #[test]
+    fn os_release_values() {
+        let contents = "NAME=\"Fedora Linux\"\nVERSION_ID=40\nID='fedora'\nVARIANT=\n";
+        assert_eq!(env_file_value(contents, "NAME").unwrap(), "Fedora Linux");
+        assert_eq!(env_file_value(contents, "VERSION_ID").unwrap(), "40");
+        assert_eq!(env_file_value(contents, "ID").unwrap(), "fedora");
+        assert_eq!(env_file_value(contents, "VARIANT"), None);
+        assert_eq!(env_file_value(contents, "VERSION"), None);
+    }
+
+    #[test]
+    fn collect_host_info() {
+        let info = HostInfo::collect().unwrap();
+        assert_eq!(info.hostname(), crate::gethostname().to_string_lossy());
+        assert!(info.hostname().starts_with(info.short_name()));
+        assert_eq!(info.os().family(), std::env::consts::OS);
+        if let (Some(fqdn), Some(domain)) = (info.fqdn(), info.domain()) {
+            assert!(fqdn.ends_with(domain));
+        }
+    }
+
+    #[test]
+    fn collect_host_info_within_timeout() {
+        let info = HostInfo::collect_timeout(std::time::Duration::from_millis(1)).unwrap();
+        assert_eq!(info.hostname(), crate::gethostname().to_string_lossy());
+    }
+}
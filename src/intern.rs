@@ -0,0 +1,161 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Interned hostnames.
+
+use std::collections::HashSet;
+use std::fmt;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex, OnceLock};
+
+use crate::hostname::Hostname;
+
+static INTERNED: OnceLock<Mutex<HashSet<Arc<str>>>> = OnceLock::new();
+
+/// An interned, case-folded hostname.
+///
+/// Systems which tag millions of events with a handful of distinct hostnames
+/// can store interned hostnames instead of strings: All interned hostnames
+/// which are equal up to case share a single allocation, so cloning and
+/// comparing them for equality only copies and compares a pointer.
+///
+/// Hostnames are case-insensitive, so interning folds their case with
+/// [`fold_case()`](crate::fold_case).  The
+/// process keeps every interned hostname until it exits, so don't intern
+/// untrusted hostnames from an unbounded set.
+///
+/// ```
+/// use gethostname::{Hostname, InternedHostname};
+///
+/// let a = InternedHostname::new(&"Web-01.example.com".parse::<Hostname>().unwrap());
+/// let b = InternedHostname::new(&"web-01.EXAMPLE.com".parse::<Hostname>().unwrap());
+/// assert_eq!(a, b);
+/// assert_eq!(a.as_str(), "web-01.example.com");
+/// ```
+#[derive(Clone)]
+pub struct InternedHostname(Arc<str>);
+
+impl InternedHostname {
+    /// Intern `hostname`.
+    pub fn new(hostname: &Hostname) -> InternedHostname {
+        let folded = crate::fold_case(hostname.as_str());
+        let mut interned = INTERNED
+            .get_or_init(Default::default)
+            .lock()
+            .unwrap_or_else(|error| error.into_inner());
+        match interned.get(folded.as_str()) {
+            Some(name) => InternedHostname(name.clone()),
+            None => {
+                let name: Arc<str> = Arc::from(folded);
+                interned.insert(name.clone());
+                InternedHostname(name)
+            }
+        }
+    }
+
+    /// Get this hostname as string slice.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+impl From<&Hostname> for InternedHostname {
+    fn from(hostname: &Hostname) -> Self {
+        InternedHostname::new(hostname)
+    }
+}
+
+impl PartialEq for InternedHostname {
+    fn eq(&self, other: &Self) -> bool {
+        // Interning guarantees that equal hostnames share the same allocation
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for InternedHostname {}
+
+impl Hash for InternedHostname {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        (self.0.as_ptr() as usize).hash(state);
+    }
+}
+
+impl PartialOrd for InternedHostname {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for InternedHostname {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        self.0.cmp(&other.0)
+    }
+}
+
+impl AsRef<str> for InternedHostname {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl fmt::Debug for InternedHostname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_tuple("InternedHostname").field(&&*self.0).finish()
+    }
+}
+
+impl fmt::Display for InternedHostname {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::InternedHostname;
+    use crate::Hostname;
+    use pretty_assertions::assert_eq;
+    use std::sync::Arc;
+
+    fn intern(s: &str) -> InternedHostname {
+        InternedHostname::new(&s.parse::<Hostname>().unwrap())
+    }
+
+    #[test]
+    fn equal_hostnames_share_allocation() {
+        let a = intern("Foo.Example.com");
+        let b = intern("foo.example.COM");
+        assert!(Arc::ptr_eq(&a.0, &b.0));
+        assert_eq!(a, b);
+        assert_eq!(a.as_str(), "foo.example.com");
+        assert_eq!(intern("BÜCHER.example").as_str(), "bücher.example");
+    }
+
+    #[test]
+    fn distinct_hostnames() {
+        let a = intern("a.example");
+        let b = intern("b.example");
+        assert_ne!(a, b);
+        assert!(a < b);
+    }
+
+    #[test]
+    fn interned_hostnames_in_sets() {
+        let set = ["x.example", "X.example", "y.example"]
+            .iter()
+            .map(|s| intern(s))
+            .collect::<std::collections::HashSet<_>>();
+        assert_eq!(set.len(), 2);
+    }
+}
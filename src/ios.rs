@@ -0,0 +1,188 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The hostname and the device name on iOS, tvOS, watchOS and visionOS.
+//!
+//! These systems share the kernel and most of the frameworks of iOS, so we
+//! treat them all alike, and just say iOS.
+//!
+//! iOS doesn't let apps see the name the user gave the device: The hostname is
+//! a generic name like `iPhone` or `localhost`, and since iOS 16 `UIDevice`
+//! also returns a generic name unless the app has the
+//! `com.apple.developer.device-information.user-assigned-device-name`
+//! entitlement.
+
+use std::os::raw::{c_int, c_void};
+
+#[cfg(feature = "ios")]
+use crate::error::HostnameError;
+
+/// Get the hostname from `sysctl` with `kern.hostname`, into `buffer`.
+///
+/// This is what `gethostname` does on Apple systems; we call `sysctl`
+/// directly to get the same behaviour on all iOS releases.  Like `gethostname`
+/// fail with `ENAMETOOLONG` if the hostname doesn't fit into `buffer`.
+pub(crate) fn kern_hostname(buffer: &mut [u8]) -> c_int {
+    let mut mib = [libc::CTL_KERN, libc::KERN_HOSTNAME];
+    let mut len = buffer.len();
+    let returncode = unsafe {
+        libc::sysctl(
+            mib.as_mut_ptr(),
+            mib.len() as libc::c_uint,
+            buffer.as_mut_ptr() as *mut c_void,
+            &mut len,
+            std::ptr::null_mut(),
+            0,
+        )
+    };
+    if returncode != 0 {
+        let errno = unsafe { &mut *libc::__error() };
+        if *errno == libc::ENOMEM {
+            *errno = libc::ENAMETOOLONG;
+        }
+    }
+    returncode
+}
+
+/// Get the name of the current device from `UIDevice`, e.g. `Anna's iPhone`.
+///
+/// On watchOS, which has no UIKit, use `WKInterfaceDevice` from WatchKit
+/// instead.
+///
+/// Since iOS 16 this is just the model, e.g. `iPhone`, unless the app has the
+/// [user-assigned device name entitlement][1], which Apple grants only on
+/// request, to apps which show the name to the user, e.g. to identify devices
+/// in multi-device workflows.
+///
+/// Apps may only use UIKit and WatchKit from the main thread; fail with
+/// [`HostnameError::ServiceFailed`] on any other thread.  The `ios` feature
+/// links this crate against UIKit or WatchKit, which aren't available to some
+/// app extensions.
+///
+/// [1]: https://developer.apple.com/documentation/bundleresources/entitlements/com_apple_developer_device-information_user-assigned-device-name
+#[cfg(feature = "ios")]
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn device_name() -> Result<String, HostnameError> {
+    use std::ffi::CStr;
+    use std::os::raw::c_char;
+
+    if unsafe { libc::pthread_main_np() } != 1 {
+        return Err(HostnameError::ServiceFailed {
+            service: DEVICE_FRAMEWORK,
+            message: "not on the main thread".to_string(),
+        });
+    }
+    unsafe {
+        let pool = objc_autoreleasePoolPush();
+        // objc_msgSend has no type of its own; call it with the type of the
+        // methods we send
+        let send = std::mem::transmute::<unsafe extern "C" fn(), unsafe extern "C" fn(Id, Sel) -> Id>(
+            objc_msgSend,
+        );
+        let device = send(
+            objc_getClass(DEVICE_CLASS.as_ptr() as *const c_char),
+            sel_registerName(b"currentDevice\0".as_ptr() as *const c_char),
+        );
+        let name = send(
+            device,
+            sel_registerName(b"name\0".as_ptr() as *const c_char),
+        );
+        let utf8 = if name.is_null() {
+            std::ptr::null_mut()
+        } else {
+            send(
+                name,
+                sel_registerName(b"UTF8String\0".as_ptr() as *const c_char),
+            )
+        };
+        let result = if utf8.is_null() {
+            None
+        } else {
+            Some(
+                CStr::from_ptr(utf8 as *const c_char)
+                    .to_string_lossy()
+                    .into_owned(),
+            )
+        };
+        // Release the autoreleased name only after we copied it
+        objc_autoreleasePoolPop(pool);
+        result
+            .filter(|name| !name.is_empty())
+            .ok_or(HostnameError::NotFound {
+                what: "device name",
+            })
+    }
+}
+
+#[cfg(feature = "ios")]
+type Id = *mut c_void;
+#[cfg(feature = "ios")]
+type Sel = *mut c_void;
+
+#[cfg(feature = "ios")]
+#[link(name = "objc")]
+extern "C" {
+    fn objc_getClass(name: *const std::os::raw::c_char) -> Id;
+    fn sel_registerName(name: *const std::os::raw::c_char) -> Sel;
+    fn objc_msgSend();
+    fn objc_autoreleasePoolPush() -> *mut c_void;
+    fn objc_autoreleasePoolPop(pool: *mut c_void);
+}
+
+/// The framework and the class which provide the device name.
+#[cfg(all(feature = "ios", not(target_os = "watchos")))]
+const DEVICE_FRAMEWORK: &str = "UIKit";
+#[cfg(all(feature = "ios", not(target_os = "watchos")))]
+const DEVICE_CLASS: &[u8] = b"UIDevice\0";
+#[cfg(all(feature = "ios", target_os = "watchos"))]
+const DEVICE_FRAMEWORK: &str = "WatchKit";
+#[cfg(all(feature = "ios", target_os = "watchos"))]
+const DEVICE_CLASS: &[u8] = b"WKInterfaceDevice\0";
+
+#[cfg(all(feature = "ios", not(target_os = "watchos")))]
+#[link(name = "UIKit", kind = "framework")]
+extern "C" {}
+#[cfg(all(feature = "ios", target_os = "watchos"))]
+#[link(name = "WatchKit", kind = "framework")]
+extern "C" {}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn kern_hostname_matches_gethostname() {
+        let mut buffer = [0; 256];
+        assert_eq!(super::kern_hostname(&mut buffer), 0);
+        let end = buffer.iter().position(|&b| b == 0).unwrap();
+        let mut expected = [0; 256];
+        assert_eq!(
+            unsafe {
+                libc::gethostname(expected.as_mut_ptr() as *mut libc::c_char, expected.len())
+            },
+            0
+        );
+        assert_eq!(&buffer[..end], &expected[..end]);
+    }
+
+    #[test]
+    fn kern_hostname_fails_if_buffer_is_too_small() {
+        let mut buffer = [0; 1];
+        assert_eq!(super::kern_hostname(&mut buffer), -1);
+        assert_eq!(
+            std::io::Error::last_os_error().raw_os_error(),
+            Some(libc::ENAMETOOLONG)
+        );
+    }
+}
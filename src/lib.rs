@@ -25,7 +25,190 @@
 #![deny(warnings, missing_docs, clippy::all)]
 
 use std::ffi::OsString;
-use std::io::Error;
+
+/// Log a warning with the log crate, if the `log` feature is enabled.
+///
+/// We use this to tell operators whenever we take a degraded path.
+macro_rules! warn {
+    ($($arg:tt)+) => {{
+        #[cfg(feature = "log")]
+        log::warn!($($arg)+);
+        // Type-check the arguments, but don't evaluate them
+        #[cfg(not(feature = "log"))]
+        if false {
+            let _ = format_args!($($arg)+);
+        }
+    }};
+}
+
+#[cfg(any(target_os = "android", test))]
+mod android;
+#[cfg(feature = "anonymize")]
+mod anonymize;
+#[cfg(feature = "arbitrary")]
+mod arbitrary;
+mod backend;
+mod backoff;
+mod best;
+mod cache;
+mod cancel;
+mod case;
+mod chassis;
+#[cfg(feature = "clap")]
+pub mod clap;
+#[cfg(feature = "cloud")]
+pub mod cloud;
+#[cfg(target_os = "linux")]
+mod cloud_init;
+#[cfg(feature = "configure")]
+pub mod config;
+#[cfg(any(target_os = "cygwin", windows))]
+mod cygwin;
+#[cfg(target_os = "linux")]
+mod dhcp;
+mod diagnose;
+#[cfg(feature = "miette")]
+mod diagnostic;
+mod diff;
+mod dry_run;
+mod error;
+#[cfg(feature = "figment")]
+pub mod figment;
+mod fqdn;
+mod generate;
+mod hardware;
+#[cfg(feature = "heapless")]
+mod heapless;
+pub mod hostlist;
+mod hostname;
+#[cfg(all(target_os = "linux", feature = "dbus"))]
+pub mod hostnamed;
+mod hosts;
+#[cfg(feature = "http")]
+pub mod http;
+mod info;
+mod intern;
+#[cfg(all(target_vendor = "apple", not(target_os = "macos")))]
+mod ios;
+mod mac;
+mod machine_id;
+#[cfg(all(target_os = "macos", feature = "macos-setters"))]
+pub mod macos;
+#[cfg(feature = "mdns")]
+pub mod mdns;
+#[cfg(feature = "async")]
+pub mod nonblocking;
+mod observe;
+mod options;
+#[cfg(feature = "otel")]
+pub mod otel;
+mod privilege;
+mod prompt;
+#[cfg(feature = "proptest")]
+pub mod proptest;
+mod query;
+#[cfg(windows)]
+mod rename;
+mod resolver;
+mod sanitize;
+#[cfg(feature = "schemars")]
+mod schemars;
+#[cfg(feature = "serde")]
+pub mod serde_bytes;
+#[cfg(feature = "serde")]
+pub mod serde_lossy;
+#[cfg(target_os = "linux")]
+mod set;
+pub mod smtp;
+mod startup;
+mod sys;
+pub mod syslog;
+#[cfg(all(target_os = "macos", any(feature = "mdns", feature = "macos-setters")))]
+// Each of these features uses only some of the bindings
+#[cfg_attr(
+    not(all(feature = "mdns", feature = "macos-setters")),
+    allow(dead_code)
+)]
+mod system_configuration;
+mod template;
+mod tls;
+#[cfg(feature = "tracing")]
+pub mod tracing;
+#[cfg(feature = "url")]
+mod url;
+mod validate;
+mod watch;
+#[cfg(windows)]
+mod win32;
+#[cfg(windows)]
+mod windows_names;
+mod zeroize;
+
+#[cfg(all(target_os = "android", feature = "android"))]
+pub use crate::android::device_name;
+#[cfg(feature = "anonymize")]
+pub use crate::anonymize::{anonymized_id, keyed_id, AnonymizedId};
+pub use crate::backend::{ResolverBackend, SystemBackend, TimeoutBackend};
+pub use crate::backoff::Backoff;
+pub use crate::best::{
+    best_hostname, best_hostname_timeout, BestHostname, CandidateSource, HostnameCandidate,
+};
+pub use crate::cache::{cached_hostname, spawn_cache_refresher};
+pub use crate::cancel::CancellationToken;
+pub use crate::case::fold_case;
+pub use crate::chassis::{get_chassis, Chassis};
+#[cfg(target_os = "linux")]
+pub use crate::cloud_init::{cloud_init_hostname, CloudInitHostname};
+#[cfg(any(target_os = "cygwin", windows))]
+pub use crate::cygwin::{cygwin_names, posix_layer, CygwinNames, PosixLayer};
+#[cfg(target_os = "linux")]
+pub use crate::dhcp::{dhcp_hostname, DhcpHostname};
+pub use crate::diagnose::{diagnose, diagnose_timeout, diagnose_with, Finding};
+#[cfg(feature = "miette")]
+pub use crate::diagnostic::InvalidHostname;
+pub use crate::diff::{classify_change, HostnameChange};
+pub use crate::dry_run::{Change, DryRun};
+pub use crate::error::HostnameError;
+pub use crate::fqdn::{getfqdn, getfqdn_timeout, Fqdn};
+pub use crate::generate::{generate_unique, UniqueNameGenerator};
+pub use crate::hardware::{get_hardware_model, get_hardware_uuid};
+#[cfg(feature = "heapless")]
+pub use crate::heapless::gethostname_heapless;
+pub use crate::hostname::{Hostname, InvalidNameReason, TrailingIndex};
+pub use crate::hosts::{host_aliases, host_aliases_timeout, HostsEntry, HostsFile};
+pub use crate::info::{HostInfo, OsInfo};
+pub use crate::intern::InternedHostname;
+#[cfg(all(target_vendor = "apple", not(target_os = "macos"), feature = "ios"))]
+pub use crate::ios::device_name;
+pub use crate::mac::candidate_from_mac;
+pub use crate::machine_id::machine_id;
+pub use crate::observe::{on_hostname_set, HostnameSet};
+pub use crate::options::{AsciiMode, ParseOptions, Strictness};
+pub use crate::privilege::{can_set_hostname, PrivilegeStatus};
+pub use crate::prompt::{color_for, emoji_for, host_color, host_emoji, HostColor};
+pub use crate::query::HostnameQuery;
+#[cfg(all(windows, feature = "runas"))]
+pub use crate::rename::relaunch_elevated;
+#[cfg(windows)]
+pub use crate::rename::{rename_computer, DomainCredentials, RenameOutcome};
+pub use crate::resolver::{FqdnStrategy, Resolver};
+pub use crate::sanitize::sanitize_to_hostname;
+#[cfg(target_os = "linux")]
+pub use crate::set::{
+    set_hostname_persistent, write_etc_hostname, SetHostnameError, SetHostnameReport,
+    SetHostnameStep,
+};
+pub use crate::startup::hostname_changed_since_start;
+pub use crate::template::{format, format_timeout};
+pub use crate::tls::{gethostname_tls, gethostname_tls_into};
+pub use crate::validate::validate_for_current_platform;
+pub use crate::watch::{CloseHandle, HostnameChanges};
+#[cfg(windows)]
+pub use crate::win32::{try_gethostname_win32, Win32Error, Win32Phase};
+#[cfg(windows)]
+pub use crate::windows_names::{get_computer_name_legacy, windows_names, WindowsNames};
+#[cfg(feature = "zeroize")]
+pub use crate::zeroize::try_gethostname_with;
 
 /// Get the standard host name for the current machine.
 ///
@@ -33,103 +216,468 @@ use std::io::Error;
 /// return the DNS host name of the local computer, as returned by
 /// [GetComputerNameExW] with `ComputerNamePhysicalDnsHostname` as `NameType`.
 ///
+/// On Android the kernel hostname is usually just `localhost`; in this case
+/// return the `net.hostname` system property instead, or if that's not set a
+/// name like `android-pixel-7` derived from the serial number or the model of
+/// the device.  In [Termux] prefer `$HOSTNAME` over these.
+///
+/// On iOS, tvOS, watchOS and visionOS get the hostname from `sysctl` with
+/// `kern.hostname`; apps only see a generic name like `iPhone` or `localhost`
+/// there.  With the `ios` feature use `device_name()` for the name the user
+/// gave the device, but mind its caveats.
+///
 /// This function panics if the buffer allocated for the hostname result of the
 /// operating system is too small; however we take great care to allocate a
 /// buffer of sufficient size:
 ///
 /// * On Unix we allocate the buffer using the maximum permitted hostname size,
-///     as returned by [sysconf] via `sysconf(_SC_HOST_NAME_MAX)`, plus an extra
-///     byte for the trailing NUL byte.  A hostname cannot exceed this limit, so
-///     this function can't realistically panic.
+///   as returned by [sysconf] via `sysconf(_SC_HOST_NAME_MAX)` and cached
+///   after the first call, plus an extra byte for the trailing NUL byte.  A
+///   hostname cannot exceed this limit, so this function can't realistically
+///   panic.  If `sysconf` fails we assume `MAXHOSTNAMELEN` where the system
+///   defines it, or 255 bytes, the minimum maximum size POSIX permits.
 /// * On Windows we call `GetComputerNameExW` with a NULL buffer first, which
-///     makes it return the length of the current host name.  We then use this
-///     length to allocate a buffer for the actual result; this leaves a tiny
-///     tiny race condition in case the hostname changes to a longer name right
-///     in between those two calls but that's a risk we don't consider of any
-///     practical relevance.
+///   makes it return the length of the current host name.  We then use this
+///   length to allocate a buffer for the actual result; this leaves a tiny
+///   tiny race condition in case the hostname changes to a longer name right
+///   in between those two calls but that's a risk we don't consider of any
+///   practical relevance.
+///
+/// Hence _if_ this function does panic please [report an issue][new].  If you
+/// cannot afford a panic at all use [`try_gethostname()`] instead.
 ///
-/// Hence _if_ this function does panic please [report an issue][new].
+/// With the `strict` feature also panic if the system reports an empty
+/// hostname, see [`try_gethostname()`].
 ///
 /// [gethostname]: http://pubs.opengroup.org/onlinepubs/9699919799/functions/gethostname.html
 /// [sysconf]: http://pubs.opengroup.org/onlinepubs/9699919799/functions/sysconf.html
-/// [GetComputerNameExW]: https://docs.microsoft.com/en-us/windows/desktop/api/sysinfoapi/nf-sysinfoapi-getcomputernameexw
+/// [GetComputerNameExW]:
+///   https://docs.microsoft.com/en-us/windows/desktop/api/sysinfoapi/nf-sysinfoapi-getcomputernameexw
 /// [new]: https://github.com/lunaryorn/gethostname.rs/issues/new
+/// [Termux]: https://termux.dev
 pub fn gethostname() -> OsString {
-    gethostname_impl()
+    try_gethostname().unwrap_or_else(|error| match error {
+        #[cfg(feature = "strict")]
+        HostnameError::InvalidName {
+            reason: InvalidNameReason::Empty,
+            ..
+        } => panic!("the system reported an empty hostname, which the `strict` feature forbids"),
+        error => panic!(
+            "failed to get hostname: {}
+Please report an issue to <https://github.com/lunaryorn/gethostname.rs/issues>!",
+            error
+        ),
+    })
+}
+
+/// Get the standard host name for the current machine as raw bytes.
+///
+/// Like [`gethostname()`], but return the bytes of the hostname, for wire
+/// protocols which need bytes and mustn't lose information in a lossy
+/// conversion to unicode:
+///
+/// * On Unix return the bytes the system returned, in whatever encoding the
+///   system uses; usually this is ASCII or UTF-8.
+/// * On Windows encode the UTF-16 hostname as [WTF-8], i.e. as UTF-8 if it's
+///   valid UTF-16, and encode unpaired surrogates like any other code point
+///   otherwise.
+///
+/// Panic like [`gethostname()`].
+///
+/// ```
+/// let hostname = gethostname::gethostname_bytes();
+/// assert_eq!(hostname, gethostname::gethostname().to_string_lossy().as_bytes());
+/// ```
+///
+/// [WTF-8]: https://simonsapin.github.io/wtf-8/
+pub fn gethostname_bytes() -> Vec<u8> {
+    os_string_into_bytes(gethostname())
 }
 
 #[cfg(not(windows))]
-#[inline]
-fn gethostname_impl() -> OsString {
-    use libc::{c_char, sysconf, _SC_HOST_NAME_MAX};
+fn os_string_into_bytes(s: OsString) -> Vec<u8> {
     use std::os::unix::ffi::OsStringExt;
-    // Get the maximum size of host names on this system, and account for the
-    // trailing NUL byte.
-    let hostname_max = unsafe { sysconf(_SC_HOST_NAME_MAX) };
-    let mut buffer = vec![0; (hostname_max as usize) + 1];
-    let returncode = unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut c_char, buffer.len()) };
+    s.into_vec()
+}
+
+#[cfg(windows)]
+fn os_string_into_bytes(s: OsString) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    wtf8_bytes(s.encode_wide())
+}
+
+/// Encode UTF-16 `units` as WTF-8.
+#[cfg(any(windows, test))]
+pub(crate) fn wtf8_bytes<I: IntoIterator<Item = u16>>(units: I) -> Vec<u8> {
+    let mut bytes = Vec::new();
+    for unit in std::char::decode_utf16(units) {
+        match unit {
+            Ok(c) => bytes.extend_from_slice(c.encode_utf8(&mut [0; 4]).as_bytes()),
+            Err(error) => {
+                // Encode the surrogate like a three-byte UTF-8 sequence
+                let surrogate = error.unpaired_surrogate();
+                bytes.extend_from_slice(&[
+                    0xE0 | (surrogate >> 12) as u8,
+                    0x80 | ((surrogate >> 6) & 0x3F) as u8,
+                    0x80 | (surrogate & 0x3F) as u8,
+                ]);
+            }
+        }
+    }
+    bytes
+}
+
+/// Get the standard host name for the current machine, or fail.
+///
+/// Like [`gethostname()`] but return an error instead of panicking if the
+/// operating system fails to return the hostname.  None of the fallible
+/// functions of this crate panics; they all use this function internally.
+///
+/// With the `no-panic` feature a release build fails to link if the compiler
+/// cannot prove that this function never panics.  The check is disabled in
+/// debug builds, and together with the `log` feature, because loggers may
+/// panic, and the `configure` feature, because configured sources may panic.
+///
+/// With the `configure` feature get the hostname as configured with
+/// `config::configure()`, if the process has a configuration.
+///
+/// With the `fixed-hostname` feature and the `GETHOSTNAME_FIXED` environment
+/// variable set at compile time return the value of this variable instead of
+/// asking the operating system, e.g. for reproducible firmware images or
+/// snapshot tests.  This affects this function, [`try_gethostname_into()`]
+/// and all functions which use either, but not other names like the FQDN on
+/// Windows.
+///
+/// With the `strict` feature fail with [`HostnameError::InvalidName`] and
+/// [`InvalidNameReason::Empty`] if the system reports an empty hostname,
+/// instead of returning an empty string which would corrupt data keyed on
+/// the hostname.  This affects all functions which get the hostname of the
+/// system.
+///
+/// ```
+/// match gethostname::try_gethostname() {
+///     Ok(hostname) => println!("Hostname: {:?}", hostname),
+///     Err(error) => eprintln!("No hostname: {}", error),
+/// }
+/// ```
+#[cfg_attr(
+    all(
+        feature = "no-panic",
+        not(feature = "log"),
+        not(feature = "configure"),
+        not(debug_assertions)
+    ),
+    no_panic::no_panic
+)]
+pub fn try_gethostname() -> Result<OsString, HostnameError> {
+    let hostname = try_gethostname_unchecked()?;
+    reject_empty(hostname.is_empty())?;
+    Ok(hostname)
+}
+
+#[inline]
+fn try_gethostname_unchecked() -> Result<OsString, HostnameError> {
+    #[cfg(feature = "fixed-hostname")]
+    if let Some(hostname) = FIXED_HOSTNAME {
+        return fixed_hostname(hostname);
+    }
+    #[cfg(feature = "configure")]
+    if let Some(result) = config::configured_hostname() {
+        return result;
+    }
+    gethostname_impl()
+}
+
+/// Get the hostname of the current machine into `buffer`, or fail.
+///
+/// Like [`try_gethostname()`], but write the hostname into `buffer` instead
+/// of allocating, and return it as string slice of `buffer`, e.g. to log it
+/// with [defmt] on embedded systems.  Fail with
+/// [`HostnameError::BufferTooSmall`] if the hostname doesn't fit into `buffer`,
+/// and with [`HostnameError::InvalidName`] if it isn't valid unicode.
+///
+/// On Unix the hostname needs one extra byte for the trailing NUL byte; 256
+/// bytes fit every hostname.
+///
+/// ```
+/// let mut buffer = [0; 256];
+/// let hostname = gethostname::try_gethostname_into(&mut buffer).unwrap();
+/// assert_eq!(hostname, gethostname::gethostname().to_str().unwrap());
+/// ```
+///
+/// [defmt]: https://defmt.ferrous-systems.com/
+pub fn try_gethostname_into(buffer: &mut [u8]) -> Result<&str, HostnameError> {
+    let hostname = try_gethostname_into_unchecked(buffer)?;
+    reject_empty(hostname.is_empty())?;
+    Ok(hostname)
+}
+
+#[inline]
+fn try_gethostname_into_unchecked(buffer: &mut [u8]) -> Result<&str, HostnameError> {
+    #[cfg(feature = "fixed-hostname")]
+    if let Some(hostname) = FIXED_HOSTNAME {
+        return fixed_hostname_into(hostname, buffer);
+    }
+    #[cfg(feature = "configure")]
+    if let Some(hostname) = config::configured_hostname() {
+        return config::copy_into(&hostname?, buffer);
+    }
+    gethostname_into_impl(buffer)
+}
+
+#[cfg(not(windows))]
+fn gethostname_into_impl(buffer: &mut [u8]) -> Result<&str, HostnameError> {
+    use crate::hostname::InvalidNameReason;
+
+    let name = gethostname_bytes_into(buffer)?;
+    std::str::from_utf8(name).map_err(|error| {
+        HostnameError::invalid_name(InvalidNameReason::NotUnicode, error.valid_up_to())
+    })
+}
+
+/// Get the raw bytes of the hostname into `buffer`.
+#[cfg(not(windows))]
+pub(crate) fn gethostname_bytes_into(buffer: &mut [u8]) -> Result<&[u8], HostnameError> {
+    #[cfg(feature = "fixed-hostname")]
+    if let Some(hostname) = FIXED_HOSTNAME {
+        return fixed_hostname_into(hostname, buffer).map(str::as_bytes);
+    }
+    #[cfg(feature = "configure")]
+    if let Some(hostname) = config::configured_hostname() {
+        return config::copy_into(&hostname?, buffer).map(str::as_bytes);
+    }
+    let returncode = sys::gethostname(buffer);
     if returncode != 0 {
-        // There are no reasonable failures, so lets panic
-        panic!(
-            "gethostname failed: {}
-    Please report an issue to <https://github.com/lunaryorn/gethostname.rs/issues>!",
-            Error::last_os_error()
-        );
+        let error = sys::last_error().in_call("gethostname", Some(buffer.len()));
+        // glibc fails with ENAMETOOLONG if it had to truncate the hostname
+        return match error {
+            HostnameError::SyscallFailed {
+                errno: libc::ENAMETOOLONG,
+                ..
+            } => Err(HostnameError::BufferTooSmall),
+            error => Err(error),
+        };
     }
-    // We explicitly search for the trailing NUL byte and cap at the buffer
-    // length: If the buffer's too small (which shouldn't happen since we
-    // explicitly use the max hostname size above but just in case) POSIX
-    // doesn't specify whether there's a NUL byte at the end, so if we didn't
-    // check we might read from memory that's not ours.
+    // Without a NUL byte we can't tell whether the hostname was truncated
     let end = buffer
         .iter()
         .position(|&b| b == 0)
-        .unwrap_or_else(|| buffer.len());
-    buffer.resize(end, 0);
-    OsString::from_vec(buffer)
+        .ok_or(HostnameError::BufferTooSmall)?;
+    reject_empty(end == 0)?;
+    Ok(&buffer[..end])
 }
 
-#[cfg(windows)]
+/// With the `strict` feature fail if the hostname is `empty`.
 #[inline]
-fn gethostname_impl() -> OsString {
-    use std::os::windows::ffi::OsStringExt;
-    use winapi::ctypes::{c_ulong, wchar_t};
-    use winapi::um::sysinfoapi::{ComputerNamePhysicalDnsHostname, GetComputerNameExW};
-
-    let mut buffer_size: c_ulong = 0;
+fn reject_empty(empty: bool) -> Result<(), HostnameError> {
+    if cfg!(feature = "strict") && empty {
+        Err(HostnameError::invalid_name(InvalidNameReason::Empty, 0))
+    } else {
+        Ok(())
+    }
+}
 
-    unsafe {
-        // This call always fails with ERROR_MORE_DATA, because we pass NULL to
-        // get the required buffer size.
-        GetComputerNameExW(
-            ComputerNamePhysicalDnsHostname,
-            std::ptr::null_mut(),
-            &mut buffer_size,
-        )
-    };
+#[cfg(windows)]
+fn gethostname_into_impl(buffer: &mut [u8]) -> Result<&str, HostnameError> {
+    use crate::hostname::InvalidNameReason;
+    use winapi::ctypes::c_ulong;
+    use winapi::shared::winerror::ERROR_MORE_DATA;
+    use winapi::um::sysinfoapi::ComputerNamePhysicalDnsHostname;
 
-    let mut buffer = vec![0 as wchar_t; buffer_size as usize];
+    // DNS hostnames have at most 255 characters
+    let mut wide = [0_u16; 256];
+    let mut size = wide.len() as c_ulong;
     let returncode = unsafe {
-        GetComputerNameExW(
+        sys::get_computer_name_ex_w(
             ComputerNamePhysicalDnsHostname,
-            buffer.as_mut_ptr() as *mut wchar_t,
-            &mut buffer_size,
+            wide.as_mut_ptr(),
+            &mut size,
         )
     };
-    // GetComputerNameExW returns a non-zero value on success!
     if returncode == 0 {
-        panic!(
-            "GetComputerNameExW failed to read hostname: {}
-Please report this issue to <https://github.com/lunaryorn/gethostname.rs/issues>!",
-            Error::last_os_error()
-        );
+        let error = sys::last_error().in_call("GetComputerNameExW", Some(wide.len()));
+        return match error {
+            HostnameError::SyscallFailed { errno, .. } if errno as u32 == ERROR_MORE_DATA => {
+                Err(HostnameError::BufferTooSmall)
+            }
+            error => Err(error),
+        };
     }
+    let mut len = 0;
+    for c in std::char::decode_utf16(wide.iter().take(size as usize).copied()) {
+        let c = c.map_err(|_| HostnameError::invalid_name(InvalidNameReason::NotUnicode, len))?;
+        let target = buffer
+            .get_mut(len..len + c.len_utf8())
+            .ok_or(HostnameError::BufferTooSmall)?;
+        c.encode_utf8(target);
+        len += c.len_utf8();
+    }
+    std::str::from_utf8(&buffer[..len]).map_err(|_| HostnameError::BufferTooSmall)
+}
 
-    let end = buffer
-        .iter()
-        .position(|&b| b == 0)
-        .unwrap_or_else(|| buffer.len());
-    OsString::from_wide(&buffer[0..end])
+/// The hostname fixed at compile time.
+#[cfg(feature = "fixed-hostname")]
+const FIXED_HOSTNAME: Option<&str> = option_env!("GETHOSTNAME_FIXED");
+
+/// Copy the fixed `hostname` into a new string.
+#[cfg(feature = "fixed-hostname")]
+fn fixed_hostname(hostname: &str) -> Result<OsString, HostnameError> {
+    let mut buffer = String::new();
+    buffer
+        .try_reserve_exact(hostname.len())
+        .map_err(|_| HostnameError::out_of_memory())?;
+    buffer.push_str(hostname);
+    Ok(OsString::from(buffer))
+}
+
+/// Copy the fixed `hostname` into `buffer`.
+#[cfg(feature = "fixed-hostname")]
+fn fixed_hostname_into<'a>(hostname: &str, buffer: &'a mut [u8]) -> Result<&'a str, HostnameError> {
+    let target = buffer
+        .get_mut(..hostname.len())
+        .ok_or(HostnameError::BufferTooSmall)?;
+    target.copy_from_slice(hostname.as_bytes());
+    std::str::from_utf8(target).map_err(|_| HostnameError::BufferTooSmall)
+}
+
+/// Allocate a buffer of `len` zeros for the operating system to write into.
+///
+/// Unlike `vec![0; len]` this function returns an error rather than panicking
+/// if allocation fails.
+fn zeroed_buffer<T: Copy>(len: usize) -> Result<Vec<T>, HostnameError> {
+    let mut buffer = Vec::new();
+    buffer
+        .try_reserve_exact(len)
+        .map_err(|_| HostnameError::out_of_memory())?;
+    // SAFETY: We reserved space for len elements above, and we only ever use
+    // this function for integers for which all zero bytes are a valid value.
+    unsafe {
+        std::ptr::write_bytes(buffer.as_mut_ptr(), 0, len);
+        buffer.set_len(len);
+    }
+    Ok(buffer)
+}
+
+/// The maximum hostname size if `sysconf` doesn't know.
+///
+/// This is `MAXHOSTNAMELEN` without the trailing NUL byte where the system
+/// defines it.
+#[cfg(any(target_os = "freebsd", target_os = "aix"))]
+const HOST_NAME_MAX_FALLBACK: usize = libc::MAXHOSTNAMELEN as usize - 1;
+
+/// The maximum hostname size if `sysconf` doesn't know.
+///
+/// This is `_POSIX_HOST_NAME_MAX`, the smallest maximum size POSIX permits.
+#[cfg(not(any(windows, target_os = "freebsd", target_os = "aix")))]
+const HOST_NAME_MAX_FALLBACK: usize = 255;
+
+/// Get the maximum length of hostnames on this system.
+///
+/// On Unix return the maximum size of hostnames in bytes as returned by
+/// [sysconf] via `sysconf(_SC_HOST_NAME_MAX)`.  If `sysconf` fails return
+/// `MAXHOSTNAMELEN` on systems which define it, and 255 bytes, the minimum
+/// maximum size POSIX permits, otherwise.  The limit excludes the trailing NUL
+/// byte.  Call `sysconf` only once per process, and cache its result.  On
+/// Windows return 63 characters, the maximum length of DNS host names.
+///
+/// On Unix this is the limit this crate uses to allocate buffers for the
+/// hostname, whereas on Windows this crate asks the system for the size of
+/// the hostname.  Use it to check the length of hostnames before setting
+/// them.
+///
+/// ```
+/// assert!(gethostname::gethostname().len() <= gethostname::max_hostname_len());
+/// ```
+///
+/// [sysconf]: http://pubs.opengroup.org/onlinepubs/9699919799/functions/sysconf.html
+#[inline]
+pub fn max_hostname_len() -> usize {
+    max_hostname_len_impl()
+}
+
+/// The cached result of `sysconf(_SC_HOST_NAME_MAX)`, or zero if not yet
+/// known.
+///
+/// We use an atomic rather than a `OnceLock`, because the latter can panic,
+/// which would break the `no-panic` guarantee of `try_gethostname()`.
+#[cfg(not(windows))]
+static HOST_NAME_MAX: std::sync::atomic::AtomicUsize = std::sync::atomic::AtomicUsize::new(0);
+
+#[cfg(not(windows))]
+#[inline]
+fn max_hostname_len_impl() -> usize {
+    use std::sync::atomic::Ordering;
+
+    let cached = HOST_NAME_MAX.load(Ordering::Relaxed);
+    if cached != 0 {
+        return cached;
+    }
+    // The limit never changes at runtime; if multiple threads race here they
+    // all store the same value.
+    let max = match sys::host_name_max() {
+        max if 0 < max => max as usize,
+        _ => {
+            warn!(
+                "sysconf(_SC_HOST_NAME_MAX) failed, assuming {} bytes: {}",
+                HOST_NAME_MAX_FALLBACK,
+                std::io::Error::last_os_error()
+            );
+            HOST_NAME_MAX_FALLBACK
+        }
+    };
+    HOST_NAME_MAX.store(max, Ordering::Relaxed);
+    max
+}
+
+/// The maximum length of DNS host names on Windows.
+///
+/// See [SetComputerNameExW].
+///
+/// [SetComputerNameExW]:
+///   https://docs.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-setcomputernameexw
+#[cfg(windows)]
+const MAX_DNS_HOSTNAME_LEN: usize = 63;
+
+#[cfg(windows)]
+#[inline]
+fn max_hostname_len_impl() -> usize {
+    MAX_DNS_HOSTNAME_LEN
+}
+
+#[cfg(not(windows))]
+#[inline]
+fn gethostname_impl() -> Result<OsString, HostnameError> {
+    use std::os::unix::ffi::OsStringExt;
+    // Get the maximum size of host names on this system, and account for the
+    // trailing NUL byte.
+    let mut buffer = zeroed_buffer::<u8>(max_hostname_len() + 1)?;
+    let returncode = sys::gethostname(&mut buffer);
+    if returncode != 0 {
+        return Err(sys::last_error().in_call("gethostname", Some(buffer.len())));
+    }
+    // We explicitly search for the trailing NUL byte and cap at the buffer
+    // length: If the buffer's too small (which shouldn't happen since we
+    // explicitly use the max hostname size above but just in case) POSIX
+    // doesn't specify whether there's a NUL byte at the end, so if we didn't
+    // check we might read from memory that's not ours.
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    buffer.truncate(end);
+    Ok(OsString::from_vec(buffer))
+}
+
+#[cfg(windows)]
+#[inline]
+fn gethostname_impl() -> Result<OsString, HostnameError> {
+    use winapi::um::sysinfoapi::ComputerNamePhysicalDnsHostname;
+    get_computer_name_ex(ComputerNamePhysicalDnsHostname)
+}
+
+/// Get the computer name of the given `name_type` with `GetComputerNameExW`.
+#[cfg(windows)]
+pub(crate) fn get_computer_name_ex(
+    name_type: winapi::um::sysinfoapi::COMPUTER_NAME_FORMAT,
+) -> Result<OsString, HostnameError> {
+    win32::computer_name_ex(name_type).map_err(HostnameError::from)
 }
 
 #[cfg(test)]
@@ -137,6 +685,79 @@ mod tests {
     use pretty_assertions::assert_eq;
     use std::process::Command;
 
+    #[test]
+    fn max_hostname_len() {
+        let max = super::max_hostname_len();
+        assert!(0 < max);
+        assert!(super::gethostname().len() <= max);
+    }
+
+    #[test]
+    fn reject_empty() {
+        assert_eq!(super::reject_empty(false), Ok(()));
+        if cfg!(feature = "strict") {
+            assert_eq!(
+                super::reject_empty(true),
+                Err(super::HostnameError::invalid_name(
+                    super::InvalidNameReason::Empty,
+                    0
+                ))
+            );
+        } else {
+            assert_eq!(super::reject_empty(true), Ok(()));
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "fixed-hostname")]
+    fn fixed_hostname() {
+        assert_eq!(super::fixed_hostname("firmware").unwrap(), "firmware");
+        let mut buffer = [0; 8];
+        assert_eq!(
+            super::fixed_hostname_into("firmware", &mut buffer).unwrap(),
+            "firmware"
+        );
+        assert_eq!(
+            super::fixed_hostname_into("firmware-1", &mut buffer),
+            Err(super::HostnameError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    fn wtf8_bytes() {
+        let utf16 = |s: &str| s.encode_utf16().collect::<Vec<_>>();
+        assert_eq!(super::wtf8_bytes(utf16("foo")), b"foo");
+        assert_eq!(super::wtf8_bytes(utf16("bücher")), "bücher".as_bytes());
+        assert_eq!(super::wtf8_bytes(utf16("🦀")), "🦀".as_bytes());
+        assert_eq!(
+            super::wtf8_bytes([0x61, 0xD800, 0x62]),
+            [0x61, 0xED, 0xA0, 0x80, 0x62]
+        );
+        assert_eq!(super::wtf8_bytes([0xDC00]), [0xED, 0xB0, 0x80]);
+    }
+
+    #[test]
+    fn gethostname_bytes() {
+        assert_eq!(
+            super::gethostname_bytes(),
+            super::gethostname().to_string_lossy().as_bytes()
+        );
+    }
+
+    #[test]
+    fn gethostname_into_buffer() {
+        let mut buffer = [0; 256];
+        assert_eq!(
+            super::try_gethostname_into(&mut buffer).unwrap(),
+            super::gethostname()
+        );
+        let mut buffer = [0; 1];
+        assert_eq!(
+            super::try_gethostname_into(&mut buffer),
+            Err(super::HostnameError::BufferTooSmall)
+        );
+    }
+
     #[test]
     fn gethostname_matches_system_hostname() {
         let output = Command::new("hostname")
@@ -151,6 +772,11 @@ mod tests {
         );
     }
 
+    #[test]
+    fn try_gethostname_matches_gethostname() {
+        assert_eq!(super::try_gethostname().unwrap(), super::gethostname());
+    }
+
     #[test]
     #[ignore]
     fn gethostname_matches_fixed_hostname() {
@@ -0,0 +1,282 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Candidate hostnames derived from MAC addresses.
+
+use crate::error::HostnameError;
+use crate::hostname::Hostname;
+
+/// Derive a candidate hostname from the MAC address of the primary interface.
+///
+/// Return `host-` followed by the last three bytes of the MAC address in
+/// lowercase hex, e.g. `host-a1b2c3`, like many embedded devices name
+/// themselves at first boot.  The name is stable as long as the network
+/// hardware doesn't change.
+///
+/// The primary interface is
+///
+/// * on Linux the interface of the default route if any, and otherwise the
+///   first interface by name with a MAC address,
+/// * on BSDs and macOS the first interface which is up and has a MAC address,
+/// * and on Windows the first adapter which is up and has a MAC address, in
+///   the binding order of the system.
+///
+/// Loopback interfaces and all-zero MAC addresses are skipped.  Fail with
+/// [`HostnameError::NotFound`] if there's no such interface, and with
+/// [`HostnameError::Unsupported`] on other systems.
+///
+/// ```
+/// match gethostname::candidate_from_mac() {
+///     Ok(hostname) => println!("Candidate hostname: {}", hostname),
+///     Err(error) => eprintln!("No MAC address: {}", error),
+/// }
+/// ```
+pub fn candidate_from_mac() -> Result<Hostname, HostnameError> {
+    primary_mac_address().map(|mac| candidate_name(&mac))
+}
+
+fn candidate_name(mac: &[u8; 6]) -> Hostname {
+    Hostname::new_unchecked(format!("host-{:02x}{:02x}{:02x}", mac[3], mac[4], mac[5]))
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    windows
+))]
+fn not_found() -> HostnameError {
+    HostnameError::NotFound {
+        what: "MAC address",
+    }
+}
+
+#[cfg(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    windows
+))]
+fn is_usable(mac: &[u8; 6]) -> bool {
+    mac.iter().any(|&b| b != 0)
+}
+
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn primary_mac_address() -> Result<[u8; 6], HostnameError> {
+    use std::fs;
+
+    let read_address = |interface: &str| {
+        fs::read_to_string(format!("/sys/class/net/{}/address", interface))
+            .ok()
+            .and_then(|address| parse_mac(address.trim()))
+            .filter(is_usable)
+    };
+    if let Some(mac) = default_route_interface().as_deref().and_then(read_address) {
+        return Ok(mac);
+    }
+    let mut interfaces = fs::read_dir("/sys/class/net")
+        .map_err(|error| HostnameError::from_io(&error))?
+        .filter_map(|entry| entry.ok()?.file_name().into_string().ok())
+        .filter(|interface| interface != "lo")
+        .collect::<Vec<_>>();
+    interfaces.sort();
+    interfaces
+        .iter()
+        .find_map(|interface| read_address(interface))
+        .ok_or_else(not_found)
+}
+
+/// Get the interface of the IPv4 default route from `/proc/net/route`.
+#[cfg(any(target_os = "linux", target_os = "android"))]
+fn default_route_interface() -> Option<String> {
+    let routes = std::fs::read_to_string("/proc/net/route").ok()?;
+    routes.lines().skip(1).find_map(|line| {
+        let mut fields = line.split_whitespace();
+        let interface = fields.next()?;
+        (fields.next()? == "00000000").then(|| interface.to_string())
+    })
+}
+
+/// Parse a MAC address in the form `a1:b2:c3:d4:e5:f6`.
+#[cfg(any(target_os = "linux", target_os = "android", test))]
+fn parse_mac(s: &str) -> Option<[u8; 6]> {
+    let mut mac = [0; 6];
+    let mut parts = s.split(':');
+    for byte in mac.iter_mut() {
+        *byte = u8::from_str_radix(parts.next()?, 16).ok()?;
+    }
+    parts.next().is_none().then_some(mac)
+}
+
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+fn primary_mac_address() -> Result<[u8; 6], HostnameError> {
+    use libc::{freeifaddrs, getifaddrs, ifaddrs, sockaddr_dl, AF_LINK, IFF_LOOPBACK, IFF_UP};
+
+    let mut addresses: *mut ifaddrs = std::ptr::null_mut();
+    if unsafe { getifaddrs(&mut addresses) } != 0 {
+        return Err(HostnameError::last_os_error());
+    }
+    let mut result = None;
+    let mut current = addresses;
+    while let Some(address) = unsafe { current.as_ref() } {
+        current = address.ifa_next;
+        let flags = address.ifa_flags as libc::c_int;
+        if address.ifa_addr.is_null()
+            || unsafe { (*address.ifa_addr).sa_family } as libc::c_int != AF_LINK
+            || flags & IFF_UP == 0
+            || flags & IFF_LOOPBACK != 0
+        {
+            continue;
+        }
+        let link = unsafe { &*(address.ifa_addr as *const sockaddr_dl) };
+        if link.sdl_alen != 6 {
+            continue;
+        }
+        // The link address follows the interface name in sdl_data, and may
+        // extend beyond the declared size of sdl_data, up to sdl_len.
+        let mut mac = [0; 6];
+        unsafe {
+            let data = (link.sdl_data.as_ptr() as *const u8).add(link.sdl_nlen as usize);
+            std::ptr::copy_nonoverlapping(data, mac.as_mut_ptr(), mac.len());
+        }
+        if is_usable(&mac) {
+            result = Some(mac);
+            break;
+        }
+    }
+    unsafe { freeifaddrs(addresses) };
+    result.ok_or_else(not_found)
+}
+
+#[cfg(windows)]
+fn primary_mac_address() -> Result<[u8; 6], HostnameError> {
+    use winapi::shared::ifdef::IfOperStatusUp;
+    use winapi::shared::ipifcons::IF_TYPE_SOFTWARE_LOOPBACK;
+    use winapi::shared::winerror::{ERROR_BUFFER_OVERFLOW, ERROR_SUCCESS};
+    use winapi::shared::ws2def::AF_UNSPEC;
+    use winapi::um::iphlpapi::GetAdaptersAddresses;
+    use winapi::um::iptypes::{
+        GAA_FLAG_SKIP_ANYCAST, GAA_FLAG_SKIP_DNS_SERVER, GAA_FLAG_SKIP_MULTICAST,
+        GAA_FLAG_SKIP_UNICAST, IP_ADAPTER_ADDRESSES,
+    };
+
+    let flags = GAA_FLAG_SKIP_UNICAST
+        | GAA_FLAG_SKIP_ANYCAST
+        | GAA_FLAG_SKIP_MULTICAST
+        | GAA_FLAG_SKIP_DNS_SERVER;
+    // Microsoft recommends to start with 15 KB to avoid calling twice
+    let mut size: u32 = 15 * 1024;
+    // Allocate u64 to align the buffer properly for IP_ADAPTER_ADDRESSES
+    let mut buffer = crate::zeroed_buffer::<u64>(size as usize / 8 + 1)?;
+    let mut returncode = unsafe {
+        GetAdaptersAddresses(
+            AF_UNSPEC as u32,
+            flags,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES,
+            &mut size,
+        )
+    };
+    if returncode == ERROR_BUFFER_OVERFLOW {
+        buffer = crate::zeroed_buffer::<u64>(size as usize / 8 + 1)?;
+        returncode = unsafe {
+            GetAdaptersAddresses(
+                AF_UNSPEC as u32,
+                flags,
+                std::ptr::null_mut(),
+                buffer.as_mut_ptr() as *mut IP_ADAPTER_ADDRESSES,
+                &mut size,
+            )
+        };
+    }
+    if returncode != ERROR_SUCCESS {
+        return Err(HostnameError::from_errno(returncode as i32)
+            .in_call("GetAdaptersAddresses", Some(size as usize)));
+    }
+    let mut current = buffer.as_ptr() as *const IP_ADAPTER_ADDRESSES;
+    while let Some(adapter) = unsafe { current.as_ref() } {
+        current = adapter.Next;
+        if adapter.IfType == IF_TYPE_SOFTWARE_LOOPBACK
+            || adapter.OperStatus != IfOperStatusUp
+            || adapter.PhysicalAddressLength != 6
+        {
+            continue;
+        }
+        let mut mac = [0; 6];
+        mac.copy_from_slice(&adapter.PhysicalAddress[..6]);
+        if is_usable(&mac) {
+            return Ok(mac);
+        }
+    }
+    Err(not_found())
+}
+
+#[cfg(not(any(
+    target_os = "linux",
+    target_os = "android",
+    target_vendor = "apple",
+    target_os = "freebsd",
+    target_os = "dragonfly",
+    target_os = "netbsd",
+    target_os = "openbsd",
+    windows
+)))]
+fn primary_mac_address() -> Result<[u8; 6], HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{candidate_name, parse_mac};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn candidate_name_from_last_three_bytes() {
+        let mac = [0x00, 0x1b, 0x44, 0xa1, 0xb2, 0xc3];
+        assert_eq!(candidate_name(&mac).as_str(), "host-a1b2c3");
+    }
+
+    #[test]
+    fn parse_mac_addresses() {
+        assert_eq!(
+            parse_mac("00:1b:44:A1:b2:c3"),
+            Some([0x00, 0x1b, 0x44, 0xa1, 0xb2, 0xc3])
+        );
+        assert_eq!(parse_mac("00:1b:44:a1:b2"), None);
+        assert_eq!(parse_mac("00:1b:44:a1:b2:c3:d4"), None);
+        assert_eq!(parse_mac("00:1b:44:a1:b2:zz"), None);
+    }
+
+    #[test]
+    fn candidate_from_mac_is_valid() {
+        if let Ok(hostname) = super::candidate_from_mac() {
+            assert!(hostname.as_str().starts_with("host-"));
+            assert_eq!(hostname.as_str().len(), 11);
+        }
+    }
+}
@@ -0,0 +1,208 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The unique ID of the current machine.
+
+use crate::error::HostnameError;
+
+/// Get the unique ID of the current machine.
+///
+/// * On macOS return the `IOPlatformUUID` of the platform expert device, as
+///   reported by `ioreg`.
+/// * On Windows return the `MachineGuid` from
+///   `HKEY_LOCAL_MACHINE\SOFTWARE\Microsoft\Cryptography`.
+/// * On other Unix systems return the contents of the first of
+///   [`/etc/machine-id`][machine-id] and `/var/lib/dbus/machine-id` which
+///   exists and isn't empty, or else the binary host ID in `/etc/hostid` as
+///   eight hex digits, like `hostid` prints it.  Skip files which can't be
+///   read.
+///
+/// These are the sources the [OpenTelemetry semantic conventions][otel]
+/// recommend for `host.id`.  Fail with [`HostnameError::Unsupported`] on iOS,
+/// tvOS, watchOS and visionOS, which don't let apps identify the machine.
+///
+/// [machine-id]: https://www.freedesktop.org/software/systemd/man/machine-id.html
+/// [otel]: https://opentelemetry.io/docs/specs/semconv/resource/host/
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn machine_id() -> Result<String, HostnameError> {
+    machine_id_impl()
+}
+
+#[cfg(all(unix, not(target_vendor = "apple")))]
+fn machine_id_impl() -> Result<String, HostnameError> {
+    ["/etc/machine-id", "/var/lib/dbus/machine-id"]
+        .iter()
+        .find_map(|path| read_candidate(path).and_then(parse_machine_id))
+        .or_else(|| read_candidate("/etc/hostid").and_then(parse_hostid))
+        .ok_or(HostnameError::NotFound { what: "machine ID" })
+}
+
+/// Read the file at `path` if it exists, and skip it if it can't be read.
+#[cfg(all(unix, not(target_vendor = "apple")))]
+fn read_candidate(path: &str) -> Option<Vec<u8>> {
+    match std::fs::read(path) {
+        Ok(contents) => Some(contents),
+        Err(error) => {
+            if error.kind() != std::io::ErrorKind::NotFound {
+                warn!("Skipping {}: {}", path, error);
+            }
+            None
+        }
+    }
+}
+
+/// Parse the text `contents` of a `machine-id` file.
+#[cfg(any(all(unix, not(target_vendor = "apple")), test))]
+fn parse_machine_id(contents: Vec<u8>) -> Option<String> {
+    let id = String::from_utf8(contents).ok()?;
+    Some(id.trim().to_string()).filter(|id| !id.is_empty())
+}
+
+/// Parse the binary `contents` of `/etc/hostid`, i.e. a native-endian 32-bit
+/// integer.
+#[cfg(any(all(unix, not(target_vendor = "apple")), test))]
+fn parse_hostid(contents: Vec<u8>) -> Option<String> {
+    use std::convert::TryInto;
+
+    let id = u32::from_ne_bytes(contents.try_into().ok()?);
+    Some(format!("{:08x}", id))
+}
+
+#[cfg(target_os = "macos")]
+fn machine_id_impl() -> Result<String, HostnameError> {
+    platform_expert_property("IOPlatformUUID")
+}
+
+#[cfg(all(target_vendor = "apple", not(target_os = "macos")))]
+fn machine_id_impl() -> Result<String, HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+/// Get a string property of the platform expert device from `ioreg`.
+#[cfg(target_os = "macos")]
+pub(crate) fn platform_expert_property(property: &'static str) -> Result<String, HostnameError> {
+    use std::process::Command;
+
+    let output = Command::new("ioreg")
+        .args(["-rd1", "-c", "IOPlatformExpertDevice"])
+        .output()
+        .map_err(|error| HostnameError::from_io(&error))?;
+    if !output.status.success() {
+        return Err(HostnameError::ServiceFailed {
+            service: "ioreg",
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    parse_ioreg_property(&String::from_utf8_lossy(&output.stdout), property)
+        .ok_or(HostnameError::NotFound { what: property })
+}
+
+/// Extract the string value of `property` from the output of `ioreg`.
+#[cfg(any(target_os = "macos", test))]
+fn parse_ioreg_property(output: &str, property: &str) -> Option<String> {
+    let key = format!("\"{}\" = \"", property);
+    output.lines().find_map(|line| {
+        let start = line.find(&key)? + key.len();
+        let value = &line[start..];
+        Some(value[..value.find('"')?].to_string())
+    })
+}
+
+#[cfg(windows)]
+fn machine_id_impl() -> Result<String, HostnameError> {
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::shared::winerror::ERROR_SUCCESS;
+    use winapi::um::winnt::WCHAR;
+    use winapi::um::winreg::{
+        RegGetValueW, HKEY_LOCAL_MACHINE, RRF_RT_REG_SZ, RRF_SUBKEY_WOW6464KEY,
+    };
+
+    let wide = |s: &str| s.encode_utf16().chain(Some(0)).collect::<Vec<WCHAR>>();
+    let subkey = wide("SOFTWARE\\Microsoft\\Cryptography");
+    let value = wide("MachineGuid");
+    // A GUID has 36 characters; leave plenty of room for the trailing NUL.
+    let mut buffer = vec![0 as WCHAR; 64];
+    let mut size = (buffer.len() * std::mem::size_of::<WCHAR>()) as DWORD;
+    let returncode = unsafe {
+        RegGetValueW(
+            HKEY_LOCAL_MACHINE,
+            subkey.as_ptr(),
+            value.as_ptr(),
+            RRF_RT_REG_SZ | RRF_SUBKEY_WOW6464KEY,
+            std::ptr::null_mut(),
+            buffer.as_mut_ptr() as *mut _,
+            &mut size,
+        )
+    };
+    if returncode as DWORD != ERROR_SUCCESS {
+        return Err(
+            HostnameError::from_errno(returncode).in_call("RegGetValueW", Some(buffer.len()))
+        );
+    }
+    let end = buffer.iter().position(|&c| c == 0).unwrap_or(buffer.len());
+    std::ffi::OsString::from_wide(&buffer[..end])
+        .into_string()
+        .map_err(|guid| {
+            HostnameError::invalid_name(
+                crate::InvalidNameReason::NotUnicode,
+                guid.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{parse_hostid, parse_ioreg_property, parse_machine_id};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn ioreg_platform_uuid() {
+        let output = r#"+-o Mac-Mini  <class IOPlatformExpertDevice, id 0x100000110, registered>
+    {
+      "IOPlatformSerialNumber" = "C07XXXXXXXXX"
+      "IOPlatformUUID" = "4C4C4544-0042-4A10-8048-B4C04F4D4E32"
+      "IOPolledInterface" = "AppleARMWatchdogTimerHibernateHandler is not serializable"
+    }"#;
+        assert_eq!(
+            parse_ioreg_property(output, "IOPlatformUUID").unwrap(),
+            "4C4C4544-0042-4A10-8048-B4C04F4D4E32"
+        );
+        assert_eq!(parse_ioreg_property(output, "IOPlatformFoo"), None);
+    }
+
+    #[test]
+    fn machine_id_files() {
+        assert_eq!(
+            parse_machine_id(b"4c4c45440042\n".to_vec()).unwrap(),
+            "4c4c45440042"
+        );
+        assert_eq!(parse_machine_id(b" \n".to_vec()), None);
+        assert_eq!(parse_machine_id(vec![0xff, 0x12]), None);
+    }
+
+    #[test]
+    fn hostid_file() {
+        let id = 0x007f_0101_u32;
+        assert_eq!(parse_hostid(id.to_ne_bytes().to_vec()).unwrap(), "007f0101");
+        assert_eq!(parse_hostid(b"007f0101\n".to_vec()), None);
+    }
+
+    #[test]
+    fn machine_id_is_not_empty() {
+        if let Ok(id) = super::machine_id() {
+            assert!(!id.is_empty());
+        }
+    }
+}
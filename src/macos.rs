@@ -0,0 +1,126 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Set the names of a Mac.
+//!
+//! macOS has three names:
+//!
+//! * The computer name, a user-friendly name like `Jane's MacBook Pro`, which
+//!   the Finder shows and file sharing uses.
+//! * The local hostname, under which Bonjour publishes the Mac in mDNS, e.g.
+//!   `Janes-MacBook-Pro` for `Janes-MacBook-Pro.local`.
+//! * The hostname, which overrides the hostname macOS otherwise derives from
+//!   DHCP, reverse DNS or the local hostname.
+//!
+//! A bare `sethostname` doesn't persist across reboots and doesn't update
+//! Bonjour, so the functions in this module change the system preferences
+//! like `scutil --set` does.  They all require root privileges.
+//!
+//! ```no_run
+//! use gethostname::{macos, Hostname};
+//!
+//! macos::set_computer_name("Build Agent 7").unwrap();
+//! macos::set_local_host_name(&"build-agent-7".parse().unwrap()).unwrap();
+//! ```
+
+use crate::dry_run::{self, Change};
+use crate::error::HostnameError;
+use crate::hostname::Hostname;
+use crate::system_configuration::{
+    last_error, Owned, SCPreferencesApplyChanges, SCPreferencesCommitChanges, SCPreferencesCreate,
+    SCPreferencesLock, SCPreferencesRef, SCPreferencesSetComputerName, SCPreferencesSetHostName,
+    SCPreferencesSetLocalHostName, SCPreferencesUnlock, COMPUTER_NAME_ENCODING,
+};
+
+/// Set the computer name, like `scutil --set ComputerName`.
+pub fn set_computer_name(name: &str) -> Result<(), HostnameError> {
+    let change = preference("ComputerName", Some(name));
+    let name = cf_string(name)?;
+    with_preferences(change, |prefs| unsafe {
+        SCPreferencesSetComputerName(prefs, name.as_ptr(), COMPUTER_NAME_ENCODING)
+    })
+}
+
+/// Set the local hostname, like `scutil --set LocalHostName`.
+///
+/// The local hostname must be a single label, so fail with
+/// [`HostnameError::InvalidName`] if `name` has more than one label.
+pub fn set_local_host_name(name: &Hostname) -> Result<(), HostnameError> {
+    if let Some(position) = name.as_str().find('.') {
+        return Err(HostnameError::invalid_name(
+            crate::InvalidNameReason::InvalidCharacter('.'),
+            position,
+        ));
+    }
+    let change = preference("LocalHostName", Some(name.as_str()));
+    let name = cf_string(name.as_str())?;
+    with_preferences(change, |prefs| unsafe {
+        SCPreferencesSetLocalHostName(prefs, name.as_ptr())
+    })
+}
+
+/// Set or remove the hostname, like `scutil --set HostName`.
+///
+/// With `None` remove the hostname, so that macOS derives it automatically
+/// again.
+pub fn set_host_name(name: Option<&Hostname>) -> Result<(), HostnameError> {
+    let change = preference("HostName", name.map(Hostname::as_str));
+    let name = name.map(|name| cf_string(name.as_str())).transpose()?;
+    with_preferences(change, |prefs| unsafe {
+        SCPreferencesSetHostName(prefs, name.as_ref().map_or(std::ptr::null(), Owned::as_ptr))
+    })
+}
+
+fn cf_string(s: &str) -> Result<Owned, HostnameError> {
+    Owned::string(s).ok_or_else(HostnameError::out_of_memory)
+}
+
+fn preference(key: &'static str, value: Option<&str>) -> Change {
+    Change::Preference {
+        key,
+        value: value.map(str::to_string),
+    }
+}
+
+/// Change the system preferences with `set`, and apply the changes.
+///
+/// Only record `change` in dry-run mode.
+fn with_preferences<F>(change: Change, set: F) -> Result<(), HostnameError>
+where
+    F: FnOnce(SCPreferencesRef) -> u8,
+{
+    // We can't easily tell the old value of preferences
+    dry_run::perform(change, || None, || apply_preferences(set))
+}
+
+fn apply_preferences<F>(set: F) -> Result<(), HostnameError>
+where
+    F: FnOnce(SCPreferencesRef) -> u8,
+{
+    let name = cf_string("gethostname")?;
+    let prefs = Owned::new(unsafe {
+        SCPreferencesCreate(std::ptr::null(), name.as_ptr(), std::ptr::null())
+    })
+    .ok_or_else(last_error)?;
+    if unsafe { SCPreferencesLock(prefs.as_ptr(), 1) } == 0 {
+        return Err(last_error());
+    }
+    let succeeded = set(prefs.as_ptr()) != 0
+        && unsafe { SCPreferencesCommitChanges(prefs.as_ptr()) } != 0
+        && unsafe { SCPreferencesApplyChanges(prefs.as_ptr()) } != 0;
+    // Get the error before unlocking overwrites it
+    let result = if succeeded { Ok(()) } else { Err(last_error()) };
+    unsafe { SCPreferencesUnlock(prefs.as_ptr()) };
+    result
+}
@@ -0,0 +1,295 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Names in multicast DNS.
+//!
+//! ```
+//! match gethostname::mdns::local_name() {
+//!     Ok(name) => println!("Published as {}", name),
+//!     Err(error) => eprintln!("Not published: {}", error),
+//! }
+//! ```
+
+use std::net::{Ipv4Addr, SocketAddr, UdpSocket};
+use std::time::{Duration, Instant};
+
+use crate::error::HostnameError;
+use crate::hostname::Hostname;
+
+/// The mDNS multicast group and port.
+const MDNS_GROUP: (Ipv4Addr, u16) = (Ipv4Addr::new(224, 0, 0, 251), 5353);
+
+/// The interval between probes, as per RFC 6762.
+const PROBE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The number of probes, as per RFC 6762.
+const PROBE_COUNT: u32 = 3;
+
+/// Get the name under which the current machine is published in mDNS.
+///
+/// Return the fully qualified `.local` name, e.g. `foo.local`.  On conflicts
+/// mDNS responders rename the machine, e.g. to `foo-2.local`, so this name
+/// may differ from the hostname.
+///
+/// * On Linux ask the Avahi daemon over D-Bus.
+/// * On macOS ask the dynamic store of the system configuration for the local
+///   hostname which Bonjour publishes.
+///
+/// Fail with [`HostnameError::ServiceFailed`] if Avahi isn't running or the
+/// D-Bus call fails, with [`HostnameError::NotFound`] if macOS has no local
+/// hostname, and with [`HostnameError::Unsupported`] on other systems.
+pub fn local_name() -> Result<String, HostnameError> {
+    local_name_impl()
+}
+
+#[cfg(target_os = "linux")]
+fn local_name_impl() -> Result<String, HostnameError> {
+    let avahi_failed = |error: zbus::Error| HostnameError::ServiceFailed {
+        service: "avahi-daemon",
+        message: error.to_string(),
+    };
+    let connection = zbus::blocking::Connection::system().map_err(avahi_failed)?;
+    let reply = connection
+        .call_method(
+            Some("org.freedesktop.Avahi"),
+            "/",
+            Some("org.freedesktop.Avahi.Server"),
+            "GetHostNameFqdn",
+            &(),
+        )
+        .map_err(avahi_failed)?;
+    reply.body::<String>().map_err(avahi_failed)
+}
+
+#[cfg(target_os = "macos")]
+fn local_name_impl() -> Result<String, HostnameError> {
+    use crate::system_configuration::{Owned, SCDynamicStoreCopyLocalHostName};
+
+    let name = Owned::new(unsafe { SCDynamicStoreCopyLocalHostName(std::ptr::null()) }).ok_or(
+        HostnameError::NotFound {
+            what: "local hostname",
+        },
+    )?;
+    let name = name
+        .to_string_lossy()
+        .ok_or(HostnameError::BufferTooSmall)?;
+    Ok(format!("{}.local", name))
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "macos")))]
+fn local_name_impl() -> Result<String, HostnameError> {
+    Err(HostnameError::Unsupported)
+}
+
+/// Probe whether `name` is already claimed in mDNS on the local network.
+///
+/// `name` is a hostname with or without `.local`.  Send three mDNS probes for
+/// `name.local`, 250 milliseconds apart, as [RFC 6762] requires before
+/// claiming a name, and return `true` if any host responds with a record for
+/// the name within 250 milliseconds after the last probe.  Note that this
+/// includes the current machine, if it already publishes the name.
+///
+/// Device setup can use this to pick a name that's free before setting it,
+/// rather than having the mDNS responder rename the machine to `name-2`.
+///
+/// Fail with [`HostnameError::InvalidName`] if `name` is not a valid hostname,
+/// or if sending probes fails.
+///
+/// [RFC 6762]: https://tools.ietf.org/html/rfc6762#section-8.1
+pub fn probe_mdns_conflict(name: &str) -> Result<bool, HostnameError> {
+    let name = name.trim_end_matches('.');
+    let name = if crate::fold_case(name).ends_with(".local") {
+        name.to_string()
+    } else {
+        format!("{}.local", name)
+    };
+    let name: Hostname = name.parse()?;
+    let io_error = |error: std::io::Error| HostnameError::from_io(&error);
+    // Query from an ephemeral port; responders reply to such "legacy unicast"
+    // queries directly.
+    let socket = UdpSocket::bind((Ipv4Addr::UNSPECIFIED, 0)).map_err(io_error)?;
+    let query = encode_query(name.as_str());
+    let mut buffer = [0; 9000];
+    for _ in 0..PROBE_COUNT {
+        socket
+            .send_to(&query, SocketAddr::from(MDNS_GROUP))
+            .map_err(io_error)?;
+        let deadline = Instant::now() + PROBE_INTERVAL;
+        while let Some(remaining) = deadline.checked_duration_since(Instant::now()) {
+            if remaining.is_zero() {
+                break;
+            }
+            socket.set_read_timeout(Some(remaining)).map_err(io_error)?;
+            match socket.recv_from(&mut buffer) {
+                Ok((size, _)) if response_claims(&buffer[..size], name.as_str()) => {
+                    return Ok(true)
+                }
+                Ok(_) => {}
+                Err(error)
+                    if error.kind() == std::io::ErrorKind::WouldBlock
+                        || error.kind() == std::io::ErrorKind::TimedOut =>
+                {
+                    break
+                }
+                Err(error) => return Err(io_error(error)),
+            }
+        }
+    }
+    Ok(false)
+}
+
+/// Encode an mDNS query for any record of `name`.
+fn encode_query(name: &str) -> Vec<u8> {
+    // Header: ID 0, no flags, one question, no other records
+    let mut query = vec![0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0];
+    for label in name.split('.') {
+        query.push(label.len() as u8);
+        query.extend_from_slice(label.as_bytes());
+    }
+    query.push(0);
+    // QTYPE ANY, and QCLASS IN with the unicast-response bit
+    query.extend_from_slice(&[0, 255, 0x80, 1]);
+    query
+}
+
+/// Whether the DNS `response` has a record for `name`.
+fn response_claims(response: &[u8], name: &str) -> bool {
+    let count = |offset: usize| {
+        response
+            .get(offset..offset + 2)
+            .map_or(0, |b| u16::from_be_bytes([b[0], b[1]]))
+    };
+    // Only look at responses, i.e. with the QR bit set
+    if response.len() < 12 || response[2] & 0x80 == 0 {
+        return false;
+    }
+    let questions = count(4);
+    let records = u32::from(count(6)) + u32::from(count(8)) + u32::from(count(10));
+    let mut offset = 12;
+    for _ in 0..questions {
+        match read_name(response, offset) {
+            Some((_, next)) => offset = next + 4,
+            None => return false,
+        }
+    }
+    for _ in 0..records {
+        let (record_name, next) = match read_name(response, offset) {
+            Some(result) => result,
+            None => return false,
+        };
+        if crate::case::eq_folded(&record_name, name) {
+            return true;
+        }
+        // Skip type, class, TTL and the data
+        offset = next + 10 + usize::from(count(next + 8));
+    }
+    false
+}
+
+/// Read a possibly compressed domain name at `offset` in `message`.
+///
+/// Return the name and the offset after the name.
+fn read_name(message: &[u8], mut offset: usize) -> Option<(String, usize)> {
+    let mut labels = Vec::new();
+    let mut end = None;
+    // Bound the number of jumps to avoid loops in malicious messages
+    for _ in 0..128 {
+        let length = *message.get(offset)?;
+        match length {
+            0 => {
+                return Some((labels.join("."), end.unwrap_or(offset + 1)));
+            }
+            length if length & 0xc0 == 0xc0 => {
+                let pointer =
+                    usize::from(length & 0x3f) << 8 | usize::from(*message.get(offset + 1)?);
+                end.get_or_insert(offset + 2);
+                offset = pointer;
+            }
+            length => {
+                let label = message.get(offset + 1..offset + 1 + usize::from(length))?;
+                labels.push(String::from_utf8_lossy(label).into_owned());
+                offset += 1 + usize::from(length);
+            }
+        }
+    }
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{encode_query, read_name, response_claims};
+    use pretty_assertions::assert_eq;
+
+    /// A response to a query for `foo.local` with an A record for `name`.
+    fn response(name: &str) -> Vec<u8> {
+        let mut response = encode_query("foo.local");
+        // Set the QR and AA bits, and the answer count
+        response[2] = 0x84;
+        response[7] = 1;
+        // Compress the name, if it's the same as in the question
+        if name == "foo.local" {
+            response.extend_from_slice(&[0xc0, 12]);
+        } else {
+            response.extend_from_slice(&encode_query(name)[12..12 + name.len() + 2]);
+        }
+        response.extend_from_slice(&[0, 1, 0x80, 1, 0, 0, 0, 120, 0, 4, 192, 0, 2, 1]);
+        response
+    }
+
+    #[test]
+    fn encode_query_for_name() {
+        assert_eq!(
+            encode_query("foo.local"),
+            vec![
+                0, 0, 0, 0, 0, 1, 0, 0, 0, 0, 0, 0, 3, b'f', b'o', b'o', 5, b'l', b'o', b'c', b'a',
+                b'l', 0, 0, 255, 0x80, 1
+            ]
+        );
+    }
+
+    #[test]
+    fn read_compressed_names() {
+        let message = response("foo.local");
+        assert_eq!(
+            read_name(&message, 12).unwrap(),
+            ("foo.local".to_string(), 23)
+        );
+        assert_eq!(
+            read_name(&message, 27).unwrap(),
+            ("foo.local".to_string(), 29)
+        );
+        assert_eq!(read_name(&[0xc0, 0], 0), None);
+    }
+
+    #[test]
+    fn responses_claiming_names() {
+        assert!(response_claims(&response("foo.local"), "foo.local"));
+        assert!(response_claims(&response("FOO.local"), "foo.local"));
+        assert!(!response_claims(&response("bar.local"), "foo.local"));
+        // Queries don't claim anything
+        assert!(!response_claims(&encode_query("foo.local"), "foo.local"));
+    }
+
+    #[test]
+    fn probe_invalid_name() {
+        assert!(super::probe_mdns_conflict("foo_bar").is_err());
+    }
+
+    #[test]
+    fn local_name_ends_with_local() {
+        if let Ok(name) = super::local_name() {
+            assert!(name.ends_with(".local"));
+        }
+    }
+}
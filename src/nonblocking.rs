@@ -0,0 +1,117 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Async variants of functions which may block.
+//!
+//! These functions run their blocking counterparts on the thread pool of the
+//! [blocking] crate, so they work with any async runtime, e.g. smol,
+//! async-std or tokio, and don't block the executor.
+//!
+//! ```
+//! # futures_lite::future::block_on(async {
+//! let fqdn = gethostname::nonblocking::getfqdn().await;
+//! println!("FQDN: {:?}", fqdn);
+//! # });
+//! ```
+//!
+//! # Cancellation
+//!
+//! All futures are cancellation-safe: dropping a future before it completes
+//! has no effect other than discarding the result.  The system resolver
+//! itself can't be interrupted, so a thread which already waits for the
+//! resolver keeps waiting until the resolver returns, and then goes back to
+//! the pool; it doesn't hold on to any sockets of its own.
+//!
+//! [`resolve()`] goes further, and cancels the [`Resolver`] when dropped, so
+//! that it stops retrying and releases its helper thread right away.
+//!
+//! [blocking]: https://docs.rs/blocking
+
+use std::ffi::OsString;
+use std::time::Duration;
+
+use crate::cancel::CancellationToken;
+use crate::error::HostnameError;
+use crate::fqdn::Fqdn;
+use crate::resolver::Resolver;
+
+/// Cancel a token when dropped.
+struct CancelOnDrop(CancellationToken);
+
+impl Drop for CancelOnDrop {
+    fn drop(&mut self) {
+        self.0.cancel();
+    }
+}
+
+/// Get the fully qualified domain name of the current machine.
+///
+/// Like [`getfqdn()`](crate::getfqdn), but without blocking the current task
+/// while resolving the hostname.
+pub async fn getfqdn() -> Result<OsString, HostnameError> {
+    blocking::unblock(crate::getfqdn).await
+}
+
+/// Get the fully qualified domain name of the current machine within `timeout`.
+///
+/// Like [`getfqdn_timeout()`](crate::getfqdn_timeout), but without blocking
+/// the current task while resolving the hostname.
+pub async fn getfqdn_timeout(timeout: Duration) -> Result<OsString, HostnameError> {
+    blocking::unblock(move || crate::getfqdn_timeout(timeout)).await
+}
+
+/// Get the fully qualified domain name of the current machine as [`Fqdn`].
+///
+/// Like [`Fqdn::current()`], but without blocking the current task while
+/// resolving the hostname.
+pub async fn current_fqdn() -> Result<Fqdn, HostnameError> {
+    blocking::unblock(Fqdn::current).await
+}
+
+/// Get the fully qualified domain name of the current machine with `resolver`.
+///
+/// Like [`Resolver::fqdn()`], but without blocking the current task.  Dropping
+/// the future cancels the lookup as with [`Resolver::fqdn_cancellable()`].
+pub async fn resolve(resolver: Resolver) -> Result<OsString, HostnameError> {
+    let token = CancellationToken::new();
+    let _guard = CancelOnDrop(token.clone());
+    blocking::unblock(move || resolver.fqdn_cancellable(&token)).await
+}
+
+#[cfg(test)]
+mod tests {
+    use futures_lite::future::block_on;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn getfqdn_without_blocking() {
+        assert_eq!(block_on(super::getfqdn()), crate::getfqdn());
+        assert_eq!(block_on(super::current_fqdn()), crate::Fqdn::current());
+    }
+
+    #[test]
+    fn getfqdn_timeout_without_blocking() {
+        let timeout = std::time::Duration::from_secs(30);
+        assert_eq!(
+            block_on(super::getfqdn_timeout(timeout)),
+            crate::getfqdn_timeout(timeout)
+        );
+    }
+
+    #[test]
+    fn resolve_without_blocking() {
+        let resolver = crate::Resolver::new();
+        assert_eq!(block_on(super::resolve(resolver)), crate::getfqdn());
+    }
+}
@@ -0,0 +1,137 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Observe the changes which setters of this crate make.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use crate::dry_run::Change;
+
+/// A change which a setter of this crate made to the system.
+///
+/// See [`on_hostname_set()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HostnameSet {
+    change: Change,
+    old: Option<String>,
+}
+
+impl HostnameSet {
+    /// The change, i.e. the mechanism the setter used, and the new value.
+    pub fn change(&self) -> &Change {
+        &self.change
+    }
+
+    /// The new value, see [`Change::value()`].
+    pub fn new_value(&self) -> Option<&str> {
+        self.change.value()
+    }
+
+    /// The previous value, if known.
+    pub fn old_value(&self) -> Option<&str> {
+        self.old.as_deref()
+    }
+}
+
+type Observer = Arc<dyn Fn(&HostnameSet) + Send + Sync>;
+
+static OBSERVERS: Mutex<Vec<Observer>> = Mutex::new(Vec::new());
+
+fn observers() -> MutexGuard<'static, Vec<Observer>> {
+    OBSERVERS.lock().unwrap_or_else(|error| error.into_inner())
+}
+
+/// Call `observer` after every change which a setter of this crate makes.
+///
+/// Setters such as `set_hostname_persistent()` on Linux or
+/// `rename_computer()` on Windows call `observer` on the thread which called
+/// the setter, once for each change they make, e.g. the `sethostname` call,
+/// the write to `/etc/hostname` and the D-Bus call to systemd-hostnamed,
+/// including changes which roll back failed steps.  Each call gets the
+/// mechanism and the new value, and the old value if known, so that audit
+/// logs and compliance agents can record every rename made through this
+/// crate.
+///
+/// Setters only call observers for changes which succeeded, and not in
+/// [dry-run mode](crate::DryRun::record).  Observers can't be removed; keep
+/// them fast, since setters wait for them.
+///
+/// ```
+/// gethostname::on_hostname_set(|set| {
+///     eprintln!(
+///         "audit: {} (was {:?})",
+///         set.change(),
+///         set.old_value()
+///     );
+/// });
+/// ```
+pub fn on_hostname_set(observer: impl Fn(&HostnameSet) + Send + Sync + 'static) {
+    observers().push(Arc::new(observer));
+}
+
+/// Whether any observers are registered.
+pub(crate) fn has_observers() -> bool {
+    !observers().is_empty()
+}
+
+/// Tell all observers about `change` from `old`.
+pub(crate) fn notify(change: Change, old: Option<String>) {
+    let set = HostnameSet { change, old };
+    // Don't hold the lock while calling observers, which may register more
+    let observers = observers().clone();
+    for observer in observers {
+        observer(&set);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{on_hostname_set, HostnameSet};
+    use crate::dry_run::{perform, Change, DryRun};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::sync::{Arc, Mutex};
+
+    fn change(argument: &str) -> Change {
+        Change::Syscall {
+            call: "observe-test",
+            argument: argument.to_string(),
+        }
+    }
+
+    #[test]
+    fn observers_see_successful_changes() {
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        let observer = seen.clone();
+        on_hostname_set(move |set: &HostnameSet| {
+            if let Change::Syscall {
+                call: "observe-test",
+                ..
+            } = set.change()
+            {
+                observer.lock().unwrap().push(set.clone());
+            }
+        });
+        let old = || Some("old".to_string());
+        perform(change("new"), old, || Ok(())).unwrap();
+        perform(change("failed"), old, || Err(HostnameError::Unsupported)).unwrap_err();
+        DryRun::record(|| perform(change("planned"), old, || Ok(())).unwrap());
+
+        let seen = seen.lock().unwrap();
+        assert_eq!(seen.len(), 1);
+        assert_eq!(seen[0].change(), &change("new"));
+        assert_eq!(seen[0].new_value(), Some("new"));
+        assert_eq!(seen[0].old_value(), Some("old"));
+    }
+}
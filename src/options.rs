@@ -0,0 +1,218 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Options for parsing hostnames.
+
+use crate::error::HostnameError;
+use crate::hostname::{Hostname, InvalidNameReason};
+
+/// How to treat non-ASCII characters in hostnames.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum AsciiMode {
+    /// Accept non-ASCII letters and digits in hostnames.
+    #[default]
+    Allow,
+    /// Reject hostnames with non-ASCII characters.
+    Reject,
+    /// Convert hostnames with non-ASCII characters to their ASCII form with
+    /// [punycode], e.g. `bücher.example` to `xn--bcher-kva.example`.
+    ///
+    /// Requires the `idna` feature.
+    ///
+    /// [punycode]: https://tools.ietf.org/html/rfc3492
+    #[cfg(feature = "idna")]
+    Punycode,
+}
+
+/// Which names to accept as hostnames.
+///
+/// Different ecosystems disagree about what a valid hostname is; pick the
+/// rules of the systems the hostnames go to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum Strictness {
+    /// The rules of [RFC 952]: like [`Strictness::Rfc1123`], but labels must
+    /// not start with a digit.
+    ///
+    /// [RFC 952]: https://tools.ietf.org/html/rfc952
+    Rfc952,
+    /// The rules of [RFC 1123], which relaxed RFC 952 to allow labels which
+    /// start with a digit, e.g. `3com.com`.
+    ///
+    /// [RFC 1123]: https://tools.ietf.org/html/rfc1123#section-2.1
+    #[default]
+    Rfc1123,
+    /// Like [`Strictness::Rfc1123`], but also allow underscores in labels, as
+    /// Active Directory and many internal DNS setups do.
+    Liberal,
+}
+
+/// Options for parsing hostnames.
+///
+/// ```
+/// use gethostname::{AsciiMode, Hostname, ParseOptions};
+///
+/// let options = ParseOptions::new().ascii(AsciiMode::Reject);
+/// assert!(Hostname::parse_with("foo.example", &options).is_ok());
+/// assert!(Hostname::parse_with("bücher.example", &options).is_err());
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct ParseOptions {
+    ascii: AsciiMode,
+    strictness: Strictness,
+}
+
+impl ParseOptions {
+    /// Create default options.
+    ///
+    /// By default non-ASCII hostnames are allowed, and hostnames are
+    /// validated as per [`Strictness::Rfc1123`].
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    /// Set how to treat non-ASCII characters.
+    pub fn ascii(mut self, mode: AsciiMode) -> ParseOptions {
+        self.ascii = mode;
+        self
+    }
+
+    /// Set which names to accept as hostnames.
+    pub fn strictness(mut self, strictness: Strictness) -> ParseOptions {
+        self.strictness = strictness;
+        self
+    }
+}
+
+impl Hostname {
+    /// Parse a hostname from `s` with the given `options`.
+    ///
+    /// `s.parse()` is equivalent to this function with default options.
+    pub fn parse_with(s: &str, options: &ParseOptions) -> Result<Hostname, HostnameError> {
+        let s = match options.ascii {
+            AsciiMode::Allow => s.to_string(),
+            AsciiMode::Reject => match s.char_indices().find(|(_, c)| !c.is_ascii()) {
+                Some((position, c)) => {
+                    return Err(HostnameError::invalid_name(
+                        InvalidNameReason::InvalidCharacter(c),
+                        position,
+                    ))
+                }
+                None => s.to_string(),
+            },
+            #[cfg(feature = "idna")]
+            AsciiMode::Punycode if s.is_ascii() => s.to_string(),
+            #[cfg(feature = "idna")]
+            AsciiMode::Punycode => idna::domain_to_ascii(s)
+                .map_err(|_| HostnameError::invalid_name(InvalidNameReason::InvalidIdn, 0))?,
+        };
+        Hostname::validated(s, options.strictness)
+    }
+
+    /// Get the hostname of the current machine with the given `options`.
+    ///
+    /// Like [`Hostname::current()`], but parse the hostname with `options`.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn current_with(options: &ParseOptions) -> Result<Hostname, HostnameError> {
+        let hostname = crate::try_gethostname()?;
+        let result = match hostname.to_str() {
+            Some(s) => Hostname::parse_with(s, options),
+            None => Err(HostnameError::invalid_name(
+                InvalidNameReason::NotUnicode,
+                hostname.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+            )),
+        };
+        crate::zeroize::wipe_os_string(hostname);
+        result
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{AsciiMode, ParseOptions, Strictness};
+    use crate::{Hostname, HostnameError, InvalidNameReason};
+    use pretty_assertions::assert_eq;
+
+    fn parse(s: &str, mode: AsciiMode) -> Result<String, HostnameError> {
+        Hostname::parse_with(s, &ParseOptions::new().ascii(mode)).map(Hostname::into_string)
+    }
+
+    #[test]
+    fn allow_non_ascii() {
+        assert_eq!(
+            parse("bücher.example", AsciiMode::Allow).unwrap(),
+            "bücher.example"
+        );
+    }
+
+    #[test]
+    fn reject_non_ascii() {
+        assert_eq!(
+            parse("foo.example", AsciiMode::Reject).unwrap(),
+            "foo.example"
+        );
+        assert_eq!(
+            parse("foo.bücher", AsciiMode::Reject).unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::InvalidCharacter('ü'), 5)
+        );
+    }
+
+    #[test]
+    #[cfg(feature = "idna")]
+    fn punycode_non_ascii() {
+        assert_eq!(
+            parse("bücher.example", AsciiMode::Punycode).unwrap(),
+            "xn--bcher-kva.example"
+        );
+        assert_eq!(
+            parse("Foo.example", AsciiMode::Punycode).unwrap(),
+            "Foo.example"
+        );
+    }
+
+    #[test]
+    fn strictness_levels() {
+        let parse = |s: &str, strictness| {
+            Hostname::parse_with(s, &ParseOptions::new().strictness(strictness))
+                .map(Hostname::into_string)
+        };
+        assert_eq!(parse("3com.com", Strictness::Rfc1123).unwrap(), "3com.com");
+        assert_eq!(
+            parse("foo.3com.com", Strictness::Rfc952).unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::LeadingDigit, 4)
+        );
+        assert_eq!(
+            parse("x-1.example", Strictness::Rfc952).unwrap(),
+            "x-1.example"
+        );
+        assert_eq!(
+            parse("dc_01.corp", Strictness::Rfc1123).unwrap_err(),
+            HostnameError::invalid_name(InvalidNameReason::InvalidCharacter('_'), 2)
+        );
+        assert_eq!(
+            parse("dc_01.corp", Strictness::Liberal).unwrap(),
+            "dc_01.corp"
+        );
+        assert!(parse("foo.-bar", Strictness::Liberal).is_err());
+    }
+
+    #[test]
+    fn current_with_default_options() {
+        assert_eq!(
+            Hostname::current_with(&ParseOptions::new()).unwrap(),
+            Hostname::current().unwrap()
+        );
+    }
+}
@@ -0,0 +1,96 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! [OpenTelemetry] resource attributes for the current host.
+//!
+//! ```
+//! for attribute in gethostname::otel::resource_attributes() {
+//!     println!("{} = {}", attribute.key, attribute.value);
+//! }
+//! ```
+//!
+//! [OpenTelemetry]: https://opentelemetry.io
+
+use opentelemetry::KeyValue;
+
+/// The `host.name` attribute.
+pub const HOST_NAME: &str = "host.name";
+
+/// The `host.id` attribute.
+pub const HOST_ID: &str = "host.id";
+
+/// The `os.type` attribute.
+pub const OS_TYPE: &str = "os.type";
+
+/// Get the [semantic convention][semconv] resource attributes of the current
+/// host.
+///
+/// Return `host.name` from [`try_gethostname()`](crate::try_gethostname),
+/// `host.id` from [`machine_id()`](crate::machine_id), and `os.type`.  Omit
+/// `host.name` or `host.id` if not available, e.g. `host.id` in containers
+/// without `/etc/machine-id`.
+///
+/// [semconv]: https://opentelemetry.io/docs/specs/semconv/resource/host/
+pub fn resource_attributes() -> Vec<KeyValue> {
+    let mut attributes = Vec::with_capacity(3);
+    match crate::try_gethostname() {
+        Ok(hostname) => attributes.push(KeyValue::new(
+            HOST_NAME,
+            hostname.to_string_lossy().into_owned(),
+        )),
+        Err(error) => warn!("Omitting {}, hostname not available: {}", HOST_NAME, error),
+    }
+    match crate::machine_id() {
+        Ok(id) => attributes.push(KeyValue::new(HOST_ID, id)),
+        Err(error) => warn!("Omitting {}, machine ID not available: {}", HOST_ID, error),
+    }
+    attributes.push(KeyValue::new(OS_TYPE, os_type()));
+    attributes
+}
+
+/// Get the `os.type` of the current operating system.
+///
+/// Map Rust's [`std::env::consts::OS`] to the well-known values of the
+/// semantic conventions, and fall back to the Rust name for operating systems
+/// the conventions don't know.
+pub fn os_type() -> &'static str {
+    match std::env::consts::OS {
+        "macos" | "ios" => "darwin",
+        "dragonfly" => "dragonflybsd",
+        "illumos" => "solaris",
+        other => other,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{os_type, resource_attributes, HOST_NAME, OS_TYPE};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn resource_attributes_contain_hostname_and_os_type() {
+        let attributes = resource_attributes();
+        let value = |key: &str| {
+            attributes
+                .iter()
+                .find(|attribute| attribute.key.as_str() == key)
+                .map(|attribute| attribute.value.to_string())
+        };
+        assert_eq!(
+            value(HOST_NAME).unwrap(),
+            crate::gethostname().to_string_lossy()
+        );
+        assert_eq!(value(OS_TYPE).unwrap(), os_type());
+    }
+}
@@ -0,0 +1,186 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Check whether the current process may set the hostname.
+
+/// Whether the current process may set the hostname, see
+/// [`can_set_hostname()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum PrivilegeStatus {
+    /// The process may set the hostname right away.
+    Allowed,
+    /// The process may set the hostname once the user authorizes it, e.g.
+    /// through a polkit prompt on Linux, or after the process restarts with
+    /// elevated rights on Windows.
+    RequiresAuthorization,
+    /// The process may not set the hostname.
+    Denied,
+}
+
+/// Check whether the current process may set the hostname.
+///
+/// Check the privileges which the setters of this crate need, without
+/// attempting a change, e.g. to grey out a rename button in a UI:
+///
+/// * On Linux return [`PrivilegeStatus::Allowed`] with `CAP_SYS_ADMIN`.
+///   Without it [`set_hostname_persistent()`](crate::set_hostname_persistent)
+///   goes through systemd-hostnamed and polkit if the system runs systemd and
+///   the `dbus` feature is enabled, so return
+///   [`PrivilegeStatus::RequiresAuthorization`].
+/// * On Windows return [`PrivilegeStatus::Allowed`] if the process runs
+///   elevated, and [`PrivilegeStatus::RequiresAuthorization`] if it runs as
+///   administrator with a limited token under UAC.
+/// * On macOS and other Unix systems return [`PrivilegeStatus::Allowed`] for
+///   root, and on macOS [`PrivilegeStatus::RequiresAuthorization`] for
+///   members of the `admin` group, who may authenticate as administrator.
+///
+/// Return [`PrivilegeStatus::Denied`] otherwise.  If the privileges of the
+/// process can't be determined assume
+/// [`PrivilegeStatus::RequiresAuthorization`], so that callers only refuse
+/// to try when it's bound to fail.
+///
+/// ```
+/// use gethostname::{can_set_hostname, PrivilegeStatus};
+///
+/// if can_set_hostname() == PrivilegeStatus::Denied {
+///     println!("Ask your administrator to rename this machine");
+/// }
+/// ```
+pub fn can_set_hostname() -> PrivilegeStatus {
+    privilege_status()
+}
+
+#[cfg(target_os = "linux")]
+fn privilege_status() -> PrivilegeStatus {
+    if crate::set::has_cap_sys_admin() {
+        PrivilegeStatus::Allowed
+    } else if cfg!(feature = "dbus") && std::path::Path::new("/run/systemd/system").exists() {
+        PrivilegeStatus::RequiresAuthorization
+    } else {
+        PrivilegeStatus::Denied
+    }
+}
+
+#[cfg(target_os = "macos")]
+fn privilege_status() -> PrivilegeStatus {
+    // The gid of the admin group on every macOS
+    const ADMIN_GID: libc::gid_t = 80;
+
+    if unsafe { libc::geteuid() } == 0 {
+        return PrivilegeStatus::Allowed;
+    }
+    let count = unsafe { libc::getgroups(0, std::ptr::null_mut()) };
+    if count < 0 {
+        return PrivilegeStatus::RequiresAuthorization;
+    }
+    let mut groups = vec![0; count as usize];
+    let count = unsafe { libc::getgroups(count, groups.as_mut_ptr()) };
+    if count < 0 {
+        return PrivilegeStatus::RequiresAuthorization;
+    }
+    if groups[..count as usize].contains(&ADMIN_GID) {
+        PrivilegeStatus::RequiresAuthorization
+    } else {
+        PrivilegeStatus::Denied
+    }
+}
+
+#[cfg(all(unix, not(any(target_os = "linux", target_os = "macos"))))]
+fn privilege_status() -> PrivilegeStatus {
+    if unsafe { libc::geteuid() } == 0 {
+        PrivilegeStatus::Allowed
+    } else {
+        PrivilegeStatus::Denied
+    }
+}
+
+#[cfg(windows)]
+fn privilege_status() -> PrivilegeStatus {
+    use winapi::um::winnt::TokenElevationTypeLimited;
+
+    match token_elevation() {
+        Ok((true, _)) => PrivilegeStatus::Allowed,
+        Ok((false, elevation_type)) if elevation_type == TokenElevationTypeLimited => {
+            PrivilegeStatus::RequiresAuthorization
+        }
+        Ok((false, _)) => PrivilegeStatus::Denied,
+        Err(error) => {
+            warn!("Failed to query token elevation: {}", error);
+            PrivilegeStatus::RequiresAuthorization
+        }
+    }
+}
+
+/// Whether the token of the current process is elevated, and its elevation
+/// type.
+#[cfg(windows)]
+pub(crate) fn token_elevation(
+) -> Result<(bool, winapi::um::winnt::TOKEN_ELEVATION_TYPE), crate::HostnameError> {
+    use crate::HostnameError;
+    use std::mem::size_of;
+    use winapi::shared::minwindef::DWORD;
+    use winapi::um::handleapi::CloseHandle;
+    use winapi::um::processthreadsapi::{GetCurrentProcess, OpenProcessToken};
+    use winapi::um::securitybaseapi::GetTokenInformation;
+    use winapi::um::winnt::{
+        TokenElevation, TokenElevationType, HANDLE, TOKEN_ELEVATION, TOKEN_ELEVATION_TYPE,
+        TOKEN_QUERY,
+    };
+
+    let mut token: HANDLE = std::ptr::null_mut();
+    if unsafe { OpenProcessToken(GetCurrentProcess(), TOKEN_QUERY, &mut token) } == 0 {
+        return Err(HostnameError::last_os_error().in_call("OpenProcessToken", None));
+    }
+    let mut elevation = TOKEN_ELEVATION { TokenIsElevated: 0 };
+    let mut elevation_type: TOKEN_ELEVATION_TYPE = 0;
+    let mut size: DWORD = 0;
+    let result = unsafe {
+        if GetTokenInformation(
+            token,
+            TokenElevation,
+            &mut elevation as *mut _ as *mut _,
+            size_of::<TOKEN_ELEVATION>() as DWORD,
+            &mut size,
+        ) == 0
+            || GetTokenInformation(
+                token,
+                TokenElevationType,
+                &mut elevation_type as *mut _ as *mut _,
+                size_of::<TOKEN_ELEVATION_TYPE>() as DWORD,
+                &mut size,
+            ) == 0
+        {
+            Err(HostnameError::last_os_error().in_call("GetTokenInformation", None))
+        } else {
+            Ok((elevation.TokenIsElevated != 0, elevation_type))
+        }
+    };
+    unsafe { CloseHandle(token) };
+    result
+}
+
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use super::{can_set_hostname, PrivilegeStatus};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn allowed_with_cap_sys_admin() {
+        assert_eq!(
+            can_set_hostname() == PrivilegeStatus::Allowed,
+            crate::set::has_cap_sys_admin()
+        );
+    }
+}
@@ -0,0 +1,172 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Tell machines apart at a glance by color and emoji.
+
+use std::fmt;
+
+use crate::case::fold_case;
+use crate::error::HostnameError;
+
+/// Colors of the ANSI 256 color palette which are readable on both dark and
+/// light backgrounds.
+const PALETTE: [u8; 24] = [
+    31, 32, 33, 37, 38, 39, 67, 68, 69, 70, 71, 72, 97, 98, 99, 130, 131, 132, 133, 134, 136, 160,
+    166, 172,
+];
+
+/// Emojis which look clearly different from each other.
+const EMOJIS: [&str; 32] = [
+    "🦊", "🐙", "🐢", "🦉", "🐝", "🦄", "🐳", "🦀", "🐧", "🦒", "🦔", "🐞", "🦜", "🐬", "🦩", "🐌",
+    "🌵", "🍄", "🌻", "🍀", "🍒", "🍋", "🥑", "🌶️", "🚀", "⚓", "🎈", "🔔", "💎", "🎲", "🧭", "🪁",
+];
+
+/// A color of the ANSI 256 color palette, see [`host_color()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct HostColor(u8);
+
+impl HostColor {
+    /// The index of this color in the ANSI 256 color palette.
+    pub fn ansi256(&self) -> u8 {
+        self.0
+    }
+
+    /// The escape sequence which sets the foreground to this color.
+    ///
+    /// This is also what `Display` prints.
+    pub fn foreground(&self) -> String {
+        format!("\x1b[38;5;{}m", self.0)
+    }
+
+    /// The escape sequence which sets the background to this color.
+    pub fn background(&self) -> String {
+        format!("\x1b[48;5;{}m", self.0)
+    }
+
+    /// Paint `text` in this color, and reset all attributes afterwards.
+    pub fn paint(&self, text: &str) -> String {
+        format!("{}{}\x1b[0m", self, text)
+    }
+}
+
+impl fmt::Display for HostColor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "\x1b[38;5;{}m", self.0)
+    }
+}
+
+/// Pick a stable color for the current machine.
+///
+/// Hash the hostname into one of 24 colors of the ANSI 256 color palette
+/// which are readable on dark and light backgrounds, e.g. to color the
+/// hostname in a shell prompt, so that users can tell at a glance which
+/// machine a terminal is attached to.  See [`color_for()`] for details.
+///
+/// Fail if [`try_gethostname()`](crate::try_gethostname) fails.
+///
+/// ```
+/// let color = gethostname::host_color().unwrap();
+/// let hostname = gethostname::gethostname();
+/// println!("{} $ ", color.paint(&hostname.to_string_lossy()));
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn host_color() -> Result<HostColor, HostnameError> {
+    let hostname = crate::try_gethostname()?;
+    Ok(color_for(&hostname.to_string_lossy()))
+}
+
+/// Pick a stable emoji for the current machine.
+///
+/// Hash the hostname into one of 32 emojis, like [`host_color()`] into a
+/// color.  See [`emoji_for()`] for details.
+///
+/// Fail if [`try_gethostname()`](crate::try_gethostname) fails.
+///
+/// ```
+/// println!("{} $ ", gethostname::host_emoji().unwrap());
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn host_emoji() -> Result<&'static str, HostnameError> {
+    let hostname = crate::try_gethostname()?;
+    Ok(emoji_for(&hostname.to_string_lossy()))
+}
+
+/// Pick a stable color for `hostname`.
+///
+/// Pick the same color for the same hostname in every process, on every
+/// platform, and in every version of this crate, ignoring case and a
+/// trailing dot.  Different hostnames may get the same color.
+///
+/// ```
+/// use gethostname::color_for;
+///
+/// assert_eq!(color_for("web-01.example.com"), color_for("Web-01.example.com."));
+/// ```
+pub fn color_for(hostname: &str) -> HostColor {
+    let hash = hash(hostname);
+    HostColor(PALETTE[(hash % PALETTE.len() as u64) as usize])
+}
+
+/// Pick a stable emoji for `hostname`.
+///
+/// Pick the same emoji for the same hostname like [`color_for()`] does.  The
+/// emoji doesn't depend on the color, so that together they tell more
+/// machines apart.
+pub fn emoji_for(hostname: &str) -> &'static str {
+    let hash = hash(hostname) >> 32;
+    EMOJIS[(hash % EMOJIS.len() as u64) as usize]
+}
+
+/// Hash `hostname` with 64-bit FNV-1a, which never changes, unlike the hasher
+/// of the standard library.
+fn hash(hostname: &str) -> u64 {
+    let name = fold_case(hostname.strip_suffix('.').unwrap_or(hostname));
+    name.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{color_for, emoji_for, hash, HostColor};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn fnv1a() {
+        assert_eq!(hash(""), 0xcbf2_9ce4_8422_2325);
+        assert_eq!(hash("a"), 0xaf63_dc4c_8601_ec8c);
+        assert_eq!(hash("foobar"), 0x8594_4171_f739_67e8);
+    }
+
+    #[test]
+    fn stable_picks() {
+        assert_eq!(color_for("web-01.example.com").ansi256(), 97);
+        assert_eq!(emoji_for("web-01.example.com"), "🐌");
+    }
+
+    #[test]
+    fn ignores_case_and_trailing_dot() {
+        assert_eq!(color_for("foo.example.com"), color_for("FOO.example.com."));
+        assert_eq!(emoji_for("foo.example.com"), emoji_for("FOO.example.com."));
+    }
+
+    #[test]
+    fn escape_sequences() {
+        let color = HostColor(33);
+        assert_eq!(color.to_string(), "\x1b[38;5;33m");
+        assert_eq!(color.foreground(), color.to_string());
+        assert_eq!(color.background(), "\x1b[48;5;33m");
+        assert_eq!(color.paint("foo"), "\x1b[38;5;33mfoo\x1b[0m");
+    }
+}
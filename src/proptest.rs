@@ -0,0 +1,135 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Strategies for property tests with the [proptest] crate.
+//!
+//! ```
+//! use gethostname::Hostname;
+//! use proptest::test_runner::TestRunner;
+//!
+//! let mut runner = TestRunner::default();
+//! runner
+//!     .run(&gethostname::proptest::valid_hostname(), |hostname| {
+//!         assert_eq!(hostname.as_str().parse::<Hostname>().unwrap(), hostname);
+//!         Ok(())
+//!     })
+//!     .unwrap();
+//! ```
+//!
+//! [proptest]: https://docs.rs/proptest
+
+use ::proptest::prelude::*;
+
+use crate::hostname::{Hostname, MAX_HOSTNAME_LEN};
+
+/// A label of `len` letters, digits and hyphens, which starts and ends with
+/// a letter or a digit.
+fn label_of_len(len: usize) -> impl Strategy<Value = String> {
+    let pattern = match len {
+        1 => "[a-zA-Z0-9]".to_string(),
+        _ => format!("[a-zA-Z0-9][a-zA-Z0-9-]{{{}}}[a-zA-Z0-9]", len - 2),
+    };
+    prop::string::string_regex(&pattern).expect("invalid label pattern")
+}
+
+/// Generate valid ASCII hostnames as per RFC 1123.
+///
+/// Generate names with one to four labels of up to 63 bytes.
+pub fn valid_hostname() -> impl Strategy<Value = Hostname> {
+    prop::collection::vec("[a-zA-Z0-9]([a-zA-Z0-9-]{0,61}[a-zA-Z0-9])?", 1..=4)
+        .prop_filter_map("numeric top label", |labels| labels.join(".").parse().ok())
+}
+
+/// Generate valid hostnames of the maximum length of 253 bytes.
+///
+/// Generate three labels of the maximum length of 63 bytes, followed by a
+/// label of 61 bytes.
+pub fn maximal_hostname() -> impl Strategy<Value = Hostname> {
+    (
+        label_of_len(63),
+        label_of_len(63),
+        label_of_len(63),
+        label_of_len(61),
+    )
+        .prop_filter_map("numeric top label", |(a, b, c, d)| {
+            let name = [a, b, c, d].join(".");
+            debug_assert_eq!(name.len(), MAX_HOSTNAME_LEN);
+            name.parse().ok()
+        })
+}
+
+/// Generate internationalized hostnames in their unicode form.
+///
+/// Every generated name contains at least one non-ASCII letter, from Latin,
+/// Greek, Cyrillic or CJK scripts.
+pub fn idn_hostname() -> impl Strategy<Value = Hostname> {
+    prop::collection::vec("[a-z0-9äöüßéèçñøåæα-ωа-я\u{4e00}-\u{4e80}]{1,15}", 1..=4)
+        .prop_filter_map("ASCII or invalid name", |labels| {
+            let name = labels.join(".");
+            if name.is_ascii() {
+                None
+            } else {
+                name.parse().ok()
+            }
+        })
+}
+
+/// Generate names which are almost, but not quite, valid hostnames.
+///
+/// Take a valid hostname and break exactly one rule: add a leading or trailing
+/// hyphen, an empty label, an invalid character, an overlong label, a numeric
+/// top label, or make the name one byte too long.
+pub fn invalid_hostname() -> impl Strategy<Value = String> {
+    let valid = || valid_hostname().prop_map(Hostname::into_string);
+    prop_oneof![
+        valid().prop_map(|name| format!("-{}", name)),
+        valid().prop_map(|name| format!("{}-", name)),
+        valid().prop_map(|name| format!("{}..x", name)),
+        valid().prop_map(|name| format!(".{}", name)),
+        (valid(), "[_ !@*/]").prop_map(|(name, c)| format!("{}{}", name, c)),
+        valid().prop_map(|name| format!("{}.{}", "a".repeat(64), name)),
+        (valid(), 0..256_u32).prop_map(|(name, n)| format!("{}.{}", name, n)),
+        maximal_hostname().prop_map(|name| format!("{}x", name)),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{idn_hostname, invalid_hostname, maximal_hostname, valid_hostname};
+    use crate::Hostname;
+    use ::proptest::prelude::*;
+
+    proptest! {
+        #[test]
+        fn valid_hostnames_parse(hostname in valid_hostname()) {
+            prop_assert!(hostname.as_str().is_ascii());
+            prop_assert_eq!(hostname.as_str().parse::<Hostname>().unwrap(), hostname);
+        }
+
+        #[test]
+        fn maximal_hostnames_have_maximal_length(hostname in maximal_hostname()) {
+            prop_assert_eq!(hostname.as_str().len(), 253);
+        }
+
+        #[test]
+        fn idn_hostnames_are_not_ascii(hostname in idn_hostname()) {
+            prop_assert!(!hostname.as_str().is_ascii());
+        }
+
+        #[test]
+        fn invalid_hostnames_do_not_parse(name in invalid_hostname()) {
+            prop_assert!(name.parse::<Hostname>().is_err());
+        }
+    }
+}
@@ -0,0 +1,236 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A builder for queries of the name of the current machine.
+
+use std::ffi::OsString;
+use std::time::Duration;
+
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+
+/// Which name of the current machine to query.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+enum Source {
+    #[default]
+    Hostname,
+    Short,
+    Fqdn,
+}
+
+/// A query for the name of the current machine.
+///
+/// Select the name to query, how to normalize it, and what to return if the
+/// query fails, and then [`get()`](HostnameQuery::get) the name.  By default
+/// query the hostname as returned by [`try_gethostname()`](crate::try_gethostname)
+/// without any normalization and without fallback.
+///
+/// ```
+/// use gethostname::HostnameQuery;
+///
+/// let name = HostnameQuery::new()
+///     .fqdn()
+///     .lowercase()
+///     .strip_trailing_dot()
+///     .fallback("unknown")
+///     .get()
+///     .unwrap();
+/// println!("FQDN: {}", name);
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct HostnameQuery {
+    source: Source,
+    lowercase: bool,
+    strip_trailing_dot: bool,
+    fallback: Option<String>,
+    timeout: Option<Duration>,
+}
+
+impl HostnameQuery {
+    /// Create a query for the hostname of the current machine.
+    pub fn new() -> HostnameQuery {
+        HostnameQuery::default()
+    }
+
+    /// Query the fully qualified domain name, as returned by
+    /// [`getfqdn()`](crate::getfqdn).
+    pub fn fqdn(mut self) -> HostnameQuery {
+        self.source = Source::Fqdn;
+        self
+    }
+
+    /// Query the short hostname, i.e. the hostname up to the first dot.
+    pub fn short(mut self) -> HostnameQuery {
+        self.source = Source::Short;
+        self
+    }
+
+    /// Convert the name to lowercase, with [`fold_case()`](crate::fold_case).
+    pub fn lowercase(mut self) -> HostnameQuery {
+        self.lowercase = true;
+        self
+    }
+
+    /// Remove a trailing dot from the name, e.g. `foo.example.com.` to
+    /// `foo.example.com`.
+    pub fn strip_trailing_dot(mut self) -> HostnameQuery {
+        self.strip_trailing_dot = true;
+        self
+    }
+
+    /// Return `fallback` if the query fails or returns an empty name.
+    ///
+    /// Don't normalize `fallback`.
+    pub fn fallback<S: Into<String>>(mut self, fallback: S) -> HostnameQuery {
+        self.fallback = Some(fallback.into());
+        self
+    }
+
+    /// Give up resolving the [fully qualified domain name](HostnameQuery::fqdn)
+    /// after `timeout`, as with [`getfqdn_timeout()`](crate::getfqdn_timeout).
+    pub fn timeout(mut self, timeout: Duration) -> HostnameQuery {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Get the name of the current machine.
+    ///
+    /// Fail if the query fails, or if the name is not valid unicode, unless
+    /// this query has a [`fallback()`](HostnameQuery::fallback).
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn get(&self) -> Result<String, HostnameError> {
+        let name = match self.source {
+            Source::Hostname | Source::Short => crate::try_gethostname(),
+            Source::Fqdn => crate::fqdn::getfqdn_within(self.timeout),
+        };
+        self.normalize(name)
+    }
+
+    /// Normalize the result of the query, and apply the fallback.
+    fn normalize(&self, name: Result<OsString, HostnameError>) -> Result<String, HostnameError> {
+        let result = name.and_then(|name| {
+            name.into_string().map_err(|name| {
+                HostnameError::invalid_name(
+                    InvalidNameReason::NotUnicode,
+                    name.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+                )
+            })
+        });
+        let mut name = match (result, &self.fallback) {
+            (Ok(name), Some(fallback)) if name.is_empty() => return Ok(fallback.clone()),
+            (Ok(name), _) => name,
+            (Err(error), Some(fallback)) => {
+                warn!("Name query failed, using fallback {}: {}", fallback, error);
+                return Ok(fallback.clone());
+            }
+            (Err(error), None) => return Err(error),
+        };
+        if self.source == Source::Short {
+            if let Some(dot) = name.find('.') {
+                name.truncate(dot);
+            }
+        }
+        if self.strip_trailing_dot && name.ends_with('.') {
+            name.pop();
+        }
+        if self.lowercase {
+            name = crate::fold_case(&name);
+        }
+        Ok(name)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostnameQuery;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    fn name(s: &str) -> Result<OsString, HostnameError> {
+        Ok(OsString::from(s))
+    }
+
+    #[test]
+    fn default_query_returns_name_as_is() {
+        let query = HostnameQuery::new();
+        assert_eq!(
+            query.normalize(name("Foo.Example.com.")).unwrap(),
+            "Foo.Example.com."
+        );
+        assert_eq!(
+            query.normalize(Err(HostnameError::Unsupported)),
+            Err(HostnameError::Unsupported)
+        );
+    }
+
+    #[test]
+    fn normalize_name() {
+        let query = HostnameQuery::new().lowercase().strip_trailing_dot();
+        assert_eq!(
+            query.normalize(name("Foo.Example.COM.")).unwrap(),
+            "foo.example.com"
+        );
+        assert_eq!(query.normalize(name("BÜCHER")).unwrap(), "bücher");
+    }
+
+    #[test]
+    fn short_name() {
+        let query = HostnameQuery::new().short().lowercase();
+        assert_eq!(query.normalize(name("Foo.example.com")).unwrap(), "foo");
+        assert_eq!(query.normalize(name("foo")).unwrap(), "foo");
+    }
+
+    #[test]
+    fn fallback() {
+        let query = HostnameQuery::new().lowercase().fallback("Unknown");
+        assert_eq!(query.normalize(name("Foo")).unwrap(), "foo");
+        assert_eq!(query.normalize(name("")).unwrap(), "Unknown");
+        assert_eq!(
+            query.normalize(Err(HostnameError::Unsupported)).unwrap(),
+            "Unknown"
+        );
+    }
+
+    #[test]
+    #[cfg(unix)]
+    fn non_unicode_name() {
+        use crate::InvalidNameReason;
+        use std::os::unix::ffi::OsStringExt;
+        let invalid = || Ok(OsString::from_vec(b"foo\xff".to_vec()));
+        assert_eq!(
+            HostnameQuery::new().normalize(invalid()),
+            Err(HostnameError::invalid_name(
+                InvalidNameReason::NotUnicode,
+                3
+            ))
+        );
+        assert_eq!(
+            HostnameQuery::new()
+                .fallback("x")
+                .normalize(invalid())
+                .unwrap(),
+            "x"
+        );
+    }
+
+    #[test]
+    fn query_current_machine() {
+        assert_eq!(
+            HostnameQuery::new().get().unwrap(),
+            crate::gethostname().to_str().unwrap()
+        );
+        assert!(!HostnameQuery::new().fqdn().get().unwrap().is_empty());
+    }
+}
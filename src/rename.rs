@@ -0,0 +1,248 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Rename Windows computers, including domain members.
+
+use std::fmt;
+
+use crate::dry_run::{self, Change};
+use crate::error::HostnameError;
+use crate::hostname::{Hostname, InvalidNameReason};
+
+/// Credentials of a domain account which may rename computer accounts.
+#[derive(Clone)]
+pub struct DomainCredentials<'a> {
+    account: &'a str,
+    password: &'a str,
+}
+
+impl<'a> DomainCredentials<'a> {
+    /// Create credentials from an `account`, e.g. `EXAMPLE\admin`, and its
+    /// `password`.
+    pub fn new(account: &'a str, password: &'a str) -> DomainCredentials<'a> {
+        DomainCredentials { account, password }
+    }
+}
+
+impl fmt::Debug for DomainCredentials<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("DomainCredentials")
+            .field("account", &self.account)
+            .field("password", &"***")
+            .finish()
+    }
+}
+
+/// The outcome of [`rename_computer()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RenameOutcome {
+    domain_joined: bool,
+    reboot_required: bool,
+}
+
+impl RenameOutcome {
+    /// Whether the computer is joined to a domain, and we renamed its
+    /// computer account in the domain as well.
+    pub fn domain_joined(&self) -> bool {
+        self.domain_joined
+    }
+
+    /// Whether the new name only takes effect after a reboot.
+    pub fn reboot_required(&self) -> bool {
+        self.reboot_required
+    }
+}
+
+/// Rename the current computer.
+///
+/// If the computer is joined to a domain rename it with
+/// [NetRenameMachineInDomain], which renames the computer account in the
+/// domain as well, with the given `credentials`, or with the credentials of
+/// the current user if `None`.  Otherwise set the DNS hostname and the NetBIOS
+/// name with [SetComputerNameExW], and ignore `credentials`.
+///
+/// Use [`DryRun::record()`](crate::DryRun::record) to see which call this
+/// makes without renaming the computer.
+///
+/// Windows applies the new name on the next restart; check
+/// [`RenameOutcome::reboot_required()`].  Renaming requires administrator
+/// privileges: fail with [`HostnameError::ElevationRequired`] right away if
+/// the current process isn't elevated, instead of a generic access denied
+/// error from Windows.  With the `runas` feature use
+/// `relaunch_elevated()` to restart the program as administrator.
+///
+/// Fail with [`HostnameError::InvalidName`] if `name` has more than one
+/// label; a computer name is a single label, and the domain comes from the
+/// network configuration.  Use
+/// [`validate_for_current_platform()`](crate::validate_for_current_platform)
+/// to check a name against all limits of Windows beforehand.
+///
+/// [NetRenameMachineInDomain]: https://learn.microsoft.com/en-us/windows/win32/api/lmjoin/nf-lmjoin-netrenamemachineindomain
+/// [SetComputerNameExW]: https://learn.microsoft.com/en-us/windows/win32/api/sysinfoapi/nf-sysinfoapi-setcomputernameexw
+pub fn rename_computer(
+    name: &Hostname,
+    credentials: Option<&DomainCredentials>,
+) -> Result<RenameOutcome, HostnameError> {
+    use winapi::um::lmjoin::{NetRenameMachineInDomain, NETSETUP_ACCT_CREATE};
+    use winapi::um::sysinfoapi::{ComputerNamePhysicalDnsHostname, SetComputerNameExW};
+
+    if let Some(position) = name.as_str().find('.') {
+        return Err(HostnameError::invalid_name(
+            InvalidNameReason::InvalidCharacter('.'),
+            position,
+        ));
+    }
+    // If we can't tell, let Windows decide
+    if let Ok((false, _)) = crate::privilege::token_elevation() {
+        return Err(HostnameError::ElevationRequired);
+    }
+    let domain_joined = is_domain_joined()?;
+    let wide_name = wide(name.as_str());
+    let old_name = || {
+        crate::try_gethostname()
+            .ok()
+            .map(|name| name.to_string_lossy().into_owned())
+    };
+    if domain_joined {
+        let change = Change::Syscall {
+            call: "NetRenameMachineInDomain",
+            argument: name.to_string(),
+        };
+        dry_run::perform(change, old_name, || {
+            let mut account = credentials.map(|c| wide(c.account));
+            let mut password = credentials.map(|c| wide(c.password));
+            let as_ptr = |s: &Option<Vec<u16>>| s.as_ref().map_or(std::ptr::null(), |s| s.as_ptr());
+            let status = unsafe {
+                NetRenameMachineInDomain(
+                    std::ptr::null(),
+                    wide_name.as_ptr(),
+                    as_ptr(&account),
+                    as_ptr(&password),
+                    NETSETUP_ACCT_CREATE,
+                )
+            };
+            // Don't leave the password lying around in memory
+            for buffer in account.iter_mut().chain(password.iter_mut()) {
+                buffer.iter_mut().for_each(|c| *c = 0);
+            }
+            if status != 0 {
+                return Err(HostnameError::from_errno(status as i32)
+                    .in_call("NetRenameMachineInDomain", None));
+            }
+            Ok(())
+        })?;
+    } else {
+        let change = Change::Syscall {
+            call: "SetComputerNameExW",
+            argument: name.to_string(),
+        };
+        dry_run::perform(change, old_name, || {
+            let returncode =
+                unsafe { SetComputerNameExW(ComputerNamePhysicalDnsHostname, wide_name.as_ptr()) };
+            if returncode == 0 {
+                return Err(HostnameError::last_os_error());
+            }
+            Ok(())
+        })?;
+    }
+    // The active name only changes on restart
+    let active = crate::get_computer_name_ex(ComputerNamePhysicalDnsHostname)?;
+    let reboot_required = !active
+        .to_str()
+        .is_some_and(|active| crate::case::eq_folded(active, name.as_str()));
+    Ok(RenameOutcome {
+        domain_joined,
+        reboot_required,
+    })
+}
+
+/// Relaunch the current executable as administrator with `parameters`.
+///
+/// Start the current executable with [ShellExecuteW] and the `runas` verb,
+/// which shows the UAC prompt, and pass `parameters` as its command line,
+/// e.g. to rename the computer after [`rename_computer()`] failed with
+/// [`HostnameError::ElevationRequired`].  Don't wait for the new process.
+///
+/// Fail if the user declines the UAC prompt.
+///
+/// [ShellExecuteW]: https://learn.microsoft.com/en-us/windows/win32/api/shellapi/nf-shellapi-shellexecutew
+#[cfg(feature = "runas")]
+pub fn relaunch_elevated(parameters: &str) -> Result<(), HostnameError> {
+    use std::os::windows::ffi::OsStrExt;
+    use winapi::um::shellapi::ShellExecuteW;
+    use winapi::um::winuser::SW_SHOWNORMAL;
+
+    let executable = std::env::current_exe().map_err(|error| HostnameError::from_io(&error))?;
+    let executable = executable
+        .as_os_str()
+        .encode_wide()
+        .chain(Some(0))
+        .collect::<Vec<_>>();
+    let verb = wide("runas");
+    let parameters = wide(parameters);
+    let instance = unsafe {
+        ShellExecuteW(
+            std::ptr::null_mut(),
+            verb.as_ptr(),
+            executable.as_ptr(),
+            parameters.as_ptr(),
+            std::ptr::null(),
+            SW_SHOWNORMAL,
+        )
+    };
+    // ShellExecuteW returns a value greater than 32 on success
+    if (instance as usize) <= 32 {
+        return Err(HostnameError::last_os_error().in_call("ShellExecuteW", None));
+    }
+    Ok(())
+}
+
+/// Whether the current computer is joined to a domain.
+fn is_domain_joined() -> Result<bool, HostnameError> {
+    use winapi::um::lmapibuf::NetApiBufferFree;
+    use winapi::um::lmjoin::{NetGetJoinInformation, NetSetupDomainName};
+
+    let mut buffer = std::ptr::null_mut();
+    let mut status = 0;
+    let returncode = unsafe { NetGetJoinInformation(std::ptr::null(), &mut buffer, &mut status) };
+    if !buffer.is_null() {
+        unsafe { NetApiBufferFree(buffer as *mut _) };
+    }
+    if returncode != 0 {
+        return Err(
+            HostnameError::from_errno(returncode as i32).in_call("NetGetJoinInformation", None)
+        );
+    }
+    Ok(status == NetSetupDomainName)
+}
+
+/// Convert `s` to a NUL-terminated wide string.
+fn wide(s: &str) -> Vec<u16> {
+    s.encode_utf16().chain(Some(0)).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::DomainCredentials;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn debug_hides_password() {
+        let credentials = DomainCredentials::new("EXAMPLE\\admin", "secret");
+        assert_eq!(
+            format!("{:?}", credentials),
+            r#"DomainCredentials { account: "EXAMPLE\\admin", password: "***" }"#
+        );
+    }
+}
@@ -0,0 +1,741 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Resolve the fully qualified domain name with different strategies.
+
+use std::ffi::{OsStr, OsString};
+use std::net::{IpAddr, SocketAddr};
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::backend::{ResolverBackend, SystemBackend};
+use crate::backoff::Backoff;
+use crate::cancel::{CancellationToken, Outcome};
+use crate::error::HostnameError;
+use crate::hostname::InvalidNameReason;
+use crate::hosts::HostsFile;
+
+/// How to find the fully qualified domain name of a hostname.
+///
+/// The strategies often disagree, e.g. if the forward and reverse DNS zones
+/// of a network are maintained separately, or if `/etc/hosts` maps the
+/// hostname to a loopback address.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[non_exhaustive]
+pub enum FqdnStrategy {
+    /// Ask the system for the canonical name, like [`getfqdn()`](crate::getfqdn).
+    ///
+    /// On Unix resolve the hostname with `getaddrinfo` and `AI_CANONNAME`, on
+    /// Windows get the DNS name Windows has configured for the computer.
+    #[default]
+    CanonicalName,
+    /// Resolve the hostname to its addresses, and look up the name of each
+    /// address in reverse DNS with `getnameinfo`.
+    ///
+    /// Return the name of the first address which has a name, i.e. the name
+    /// of its `PTR` record or its first name in `/etc/hosts`.
+    ReverseLookup,
+}
+
+/// A resolver for the fully qualified domain name.
+///
+/// Optionally consult a [`HostsFile`] before asking the system, e.g. to get
+/// deterministic results in tests and hermetic build environments, or ask a
+/// custom [`ResolverBackend`] instead of the system.
+///
+/// ```
+/// use gethostname::{FqdnStrategy, Resolver};
+///
+/// let resolver = Resolver::new().strategy(FqdnStrategy::ReverseLookup);
+/// println!("FQDN: {:?}", resolver.fqdn());
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Resolver {
+    strategy: FqdnStrategy,
+    hosts: Option<HostsFile>,
+    backend: Option<Arc<dyn ResolverBackend>>,
+    retry: Option<Backoff>,
+    timeout: Option<Duration>,
+}
+
+impl PartialEq for Resolver {
+    fn eq(&self, other: &Resolver) -> bool {
+        // Backends are equal only if they're the same backend
+        let same_backend = match (&self.backend, &other.backend) {
+            (Some(a), Some(b)) => Arc::ptr_eq(a, b),
+            (a, b) => a.is_none() && b.is_none(),
+        };
+        self.strategy == other.strategy
+            && self.hosts == other.hosts
+            && same_backend
+            && self.retry == other.retry
+            && self.timeout == other.timeout
+    }
+}
+
+impl Eq for Resolver {}
+
+impl Resolver {
+    /// Create a resolver with the default strategy,
+    /// [`FqdnStrategy::CanonicalName`].
+    pub fn new() -> Resolver {
+        Resolver::default()
+    }
+
+    /// Set the strategy to find the FQDN with.
+    pub fn strategy(mut self, strategy: FqdnStrategy) -> Resolver {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Consult `hosts` before asking the system.
+    ///
+    /// Use [`HostsFile::read()`] for a custom hosts file, or
+    /// [`HostsFile::add()`] to build a map of names in memory.
+    ///
+    /// ```
+    /// use gethostname::{HostsFile, Resolver};
+    ///
+    /// let hostname = gethostname::gethostname().into_string().unwrap();
+    /// let mut hosts = HostsFile::new();
+    /// hosts.add("192.0.2.1".parse().unwrap(), ["test.example.com", &hostname]);
+    /// let resolver = Resolver::new().hosts_file(hosts);
+    /// assert_eq!(resolver.fqdn().unwrap(), "test.example.com");
+    /// ```
+    pub fn hosts_file(mut self, hosts: HostsFile) -> Resolver {
+        self.hosts = Some(hosts);
+        self
+    }
+
+    /// Ask `backend` instead of the resolver of the system.
+    ///
+    /// With [`FqdnStrategy::CanonicalName`] return the
+    /// [canonical name](ResolverBackend::canonical_name) of the hostname,
+    /// with [`FqdnStrategy::ReverseLookup`] look up the name of its addresses.
+    /// Consult the [hosts file](Resolver::hosts_file) first, if any, and apply
+    /// [retries](Resolver::retry) and [timeouts](Resolver::timeout) to
+    /// `backend` just like to the system resolver.
+    ///
+    /// Note that without a backend [`FqdnStrategy::CanonicalName`] returns
+    /// the DNS name Windows has configured for the computer, whereas
+    /// [`SystemBackend`] asks the resolver on Windows, too.
+    pub fn backend(mut self, backend: impl ResolverBackend + 'static) -> Resolver {
+        self.backend = Some(Arc::new(backend));
+        self
+    }
+
+    /// Retry transient failures of the system resolver with `backoff`.
+    ///
+    /// Early during boot the resolver often fails with `EAI_AGAIN` until the
+    /// network is up; retry such failures, and timeouts, according to
+    /// `backoff`.  Don't retry other failures.  See
+    /// [`HostnameError::is_transient()`].
+    pub fn retry(mut self, backoff: Backoff) -> Resolver {
+        self.retry = Some(backoff);
+        self
+    }
+
+    /// Give up on the system resolver after `timeout`.
+    ///
+    /// The system resolver has no timeout of its own, and `getaddrinfo` may
+    /// hang for a long time if the DNS server is unreachable.  With a timeout
+    /// ask the system resolver on a helper thread, and fail with a
+    /// [transient](HostnameError::is_transient) error if it doesn't answer
+    /// within `timeout`.  The timeout applies to each attempt if this resolver
+    /// also [retries](Resolver::retry).
+    ///
+    /// The system resolver can't be cancelled, so the helper thread keeps
+    /// running after a timeout until the system resolver returns.
+    ///
+    /// ```
+    /// use std::time::Duration;
+    /// use gethostname::Resolver;
+    ///
+    /// let resolver = Resolver::new().timeout(Duration::from_secs(2));
+    /// println!("FQDN: {:?}", resolver.fqdn());
+    /// ```
+    pub fn timeout(mut self, timeout: Duration) -> Resolver {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Get the fully qualified domain name of the current machine.
+    ///
+    /// If this resolver has a hosts file which has the hostname return the
+    /// canonical name of the hostname from the hosts file for
+    /// [`FqdnStrategy::CanonicalName`], or the name of the first address of
+    /// the hostname for [`FqdnStrategy::ReverseLookup`].  Otherwise ask the
+    /// system, within the [timeout](Resolver::timeout) of this resolver if
+    /// any, and retry transient failures if this resolver has a
+    /// [backoff](Resolver::retry).
+    ///
+    /// Fail if getting the hostname fails, or if the strategy finds no name.
+    #[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+    pub fn fqdn(&self) -> Result<OsString, HostnameError> {
+        self.lookup(None)
+    }
+
+    /// Get the fully qualified domain name, unless `token` gets cancelled.
+    ///
+    /// Like [`fqdn()`](Resolver::fqdn), but always ask the system resolver on
+    /// a helper thread, and fail with [`HostnameError::Cancelled`] as soon as
+    /// `token` gets cancelled, e.g. from another thread.  Stop retrying once
+    /// cancelled, too.
+    ///
+    /// The system resolver can't be cancelled, so the helper thread keeps
+    /// running until the system resolver returns, and then ends; it holds no
+    /// sockets or other resources of its own.
+    #[cfg_attr(
+        feature = "tracing",
+        tracing::instrument(level = "debug", err, skip(token))
+    )]
+    pub fn fqdn_cancellable(&self, token: &CancellationToken) -> Result<OsString, HostnameError> {
+        self.lookup(Some(token))
+    }
+
+    fn lookup(&self, token: Option<&CancellationToken>) -> Result<OsString, HostnameError> {
+        let hostname = crate::try_gethostname()?;
+        if let Some(fqdn) = self.hosts_fqdn(&hostname) {
+            return Ok(OsString::from(fqdn));
+        }
+        let strategy = self.strategy;
+        let lookup = || {
+            let token = match (token, self.timeout) {
+                (Some(token), _) if token.is_cancelled() => return Err(HostnameError::Cancelled),
+                (Some(token), _) => token.clone(),
+                (None, Some(_)) => CancellationToken::new(),
+                (None, None) => return system_fqdn(strategy, self.backend.as_deref(), &hostname),
+            };
+            let hostname = hostname.clone();
+            let backend = self.backend.clone();
+            on_helper_thread(self.timeout, &token, move || {
+                system_fqdn(strategy, backend.as_deref(), &hostname)
+            })
+        };
+        match (&self.retry, token) {
+            (Some(backoff), Some(token)) => backoff.retry_with(lookup, |delay| {
+                token.sleep(delay);
+            }),
+            (Some(backoff), None) => backoff.retry(lookup),
+            (None, _) => lookup(),
+        }
+    }
+
+    /// Find the FQDN of `hostname` in the hosts file of this resolver.
+    fn hosts_fqdn(&self, hostname: &OsStr) -> Option<&str> {
+        let hosts = self.hosts.as_ref()?;
+        let hostname = hostname.to_str()?;
+        match self.strategy {
+            FqdnStrategy::CanonicalName => hosts.canonical_name(hostname),
+            FqdnStrategy::ReverseLookup => hosts
+                .addresses_of(hostname)
+                .into_iter()
+                .find_map(|address| hosts.name_of(address)),
+        }
+    }
+}
+
+/// Ask `backend`, or the system, for the FQDN of `hostname` with `strategy`.
+fn system_fqdn(
+    strategy: FqdnStrategy,
+    backend: Option<&dyn ResolverBackend>,
+    hostname: &OsStr,
+) -> Result<OsString, HostnameError> {
+    match (strategy, backend) {
+        (FqdnStrategy::CanonicalName, None) => crate::getfqdn(),
+        (FqdnStrategy::CanonicalName, Some(backend)) => backend
+            .canonical_name(unicode_hostname(hostname)?)
+            .map(OsString::from),
+        (FqdnStrategy::ReverseLookup, backend) => {
+            reverse_lookup(backend.unwrap_or(&SystemBackend), hostname)
+        }
+    }
+}
+
+/// Get `hostname` as `str`, or fail if it isn't unicode.
+fn unicode_hostname(hostname: &OsStr) -> Result<&str, HostnameError> {
+    hostname.to_str().ok_or_else(|| {
+        HostnameError::invalid_name(
+            InvalidNameReason::NotUnicode,
+            hostname.to_string_lossy().find('\u{FFFD}').unwrap_or(0),
+        )
+    })
+}
+
+/// Run `lookup`, and give up after `timeout`, if any.
+///
+/// With a timeout run `lookup` on a helper thread, like
+/// [`Resolver::timeout()`].
+pub(crate) fn within<T, F>(timeout: Option<Duration>, lookup: F) -> Result<T, HostnameError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, HostnameError> + Send + 'static,
+{
+    match timeout {
+        None => lookup(),
+        Some(_) => on_helper_thread(timeout, &CancellationToken::new(), lookup),
+    }
+}
+
+/// Run `lookup` on a helper thread, and give up after `timeout` or when
+/// `token` gets cancelled.
+fn on_helper_thread<T, F>(
+    timeout: Option<Duration>,
+    token: &CancellationToken,
+    lookup: F,
+) -> Result<T, HostnameError>
+where
+    T: Send + 'static,
+    F: FnOnce() -> Result<T, HostnameError> + Send + 'static,
+{
+    let outcome = token
+        .run(timeout, lookup)
+        .map_err(|error| HostnameError::from_io(&error))?;
+    match outcome {
+        Outcome::Done(result) => result,
+        Outcome::TimedOut => {
+            warn!("Resolver did not answer within {:?}", timeout);
+            Err(HostnameError::timed_out())
+        }
+        Outcome::Cancelled => Err(HostnameError::Cancelled),
+        Outcome::Panicked => Err(HostnameError::ServiceFailed {
+            service: "resolver",
+            message: "lookup panicked".to_string(),
+        }),
+    }
+}
+
+/// Resolve `hostname` with `backend`, and look up the name of its addresses.
+fn reverse_lookup(
+    backend: &dyn ResolverBackend,
+    hostname: &OsStr,
+) -> Result<OsString, HostnameError> {
+    let addresses = backend.addresses(unicode_hostname(hostname)?)?;
+    let mut result = Err(HostnameError::NotFound { what: "address" });
+    for address in addresses {
+        result = backend.name_of(address).map(OsString::from);
+        if result.is_ok() {
+            break;
+        }
+    }
+    result
+}
+
+/// Set the length field of socket addresses on BSDs.
+#[cfg(any(
+    target_vendor = "apple",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+))]
+macro_rules! set_len {
+    ($field:expr, $len:expr) => {
+        $field = $len as u8
+    };
+}
+
+#[cfg(not(any(
+    windows,
+    target_vendor = "apple",
+    target_os = "dragonfly",
+    target_os = "freebsd",
+    target_os = "netbsd",
+    target_os = "openbsd"
+)))]
+macro_rules! set_len {
+    ($field:expr, $len:expr) => {};
+}
+
+/// Look up the name of `address` with getnameinfo.
+#[cfg(not(windows))]
+pub(crate) fn name_info(address: &SocketAddr) -> Result<OsString, HostnameError> {
+    use libc::{
+        getnameinfo, sockaddr_in, sockaddr_in6, sockaddr_storage, AF_INET, AF_INET6, NI_NAMEREQD,
+    };
+    use std::ffi::CStr;
+    use std::mem::size_of;
+    use std::os::unix::ffi::OsStringExt;
+
+    let mut storage: sockaddr_storage = unsafe { std::mem::zeroed() };
+    let len = match address {
+        SocketAddr::V4(address) => {
+            let sin = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in) };
+            set_len!(sin.sin_len, size_of::<sockaddr_in>());
+            sin.sin_family = AF_INET as _;
+            sin.sin_addr.s_addr = u32::from_ne_bytes(address.ip().octets());
+            size_of::<sockaddr_in>()
+        }
+        SocketAddr::V6(address) => {
+            let sin6 = unsafe { &mut *(&mut storage as *mut _ as *mut sockaddr_in6) };
+            set_len!(sin6.sin6_len, size_of::<sockaddr_in6>());
+            sin6.sin6_family = AF_INET6 as _;
+            sin6.sin6_addr.s6_addr = address.ip().octets();
+            sin6.sin6_scope_id = address.scope_id();
+            size_of::<sockaddr_in6>()
+        }
+    };
+    let mut host = [0 as libc::c_char; NI_MAXHOST];
+    let returncode = unsafe {
+        getnameinfo(
+            &storage as *const _ as *const _,
+            len as _,
+            host.as_mut_ptr(),
+            host.len() as _,
+            std::ptr::null_mut(),
+            0,
+            NI_NAMEREQD,
+        )
+    };
+    if returncode != 0 {
+        return Err(gai_error(returncode));
+    }
+    let name = unsafe { CStr::from_ptr(host.as_ptr()) };
+    Ok(OsString::from_vec(name.to_bytes().to_vec()))
+}
+
+/// Create an error for a `returncode` of getaddrinfo or getnameinfo.
+///
+/// Keep the `EAI_*` code, so that [`HostnameError::is_transient()`] can tell
+/// temporary failures.
+#[cfg(not(windows))]
+pub(crate) fn gai_error(returncode: libc::c_int) -> HostnameError {
+    if returncode == libc::EAI_SYSTEM {
+        return HostnameError::last_os_error();
+    }
+    let message = unsafe { std::ffi::CStr::from_ptr(libc::gai_strerror(returncode)) };
+    HostnameError::ResolutionFailed {
+        code: returncode,
+        message: message.to_string_lossy().into_owned(),
+    }
+}
+
+/// Resolve the addresses of `name` with getaddrinfo.
+#[cfg(not(windows))]
+pub(crate) fn address_info(name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+    use libc::{
+        addrinfo, freeaddrinfo, getaddrinfo, sockaddr_in, sockaddr_in6, AF_INET, AF_INET6,
+        AF_UNSPEC, SOCK_STREAM,
+    };
+    use std::ffi::CString;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+
+    let name = CString::new(name).map_err(|error| {
+        HostnameError::invalid_name(
+            InvalidNameReason::InvalidCharacter('\0'),
+            error.nul_position(),
+        )
+    })?;
+    let mut hints: addrinfo = unsafe { std::mem::zeroed() };
+    hints.ai_family = AF_UNSPEC;
+    // Get every address once, rather than once per socket type
+    hints.ai_socktype = SOCK_STREAM;
+    let mut result: *mut addrinfo = std::ptr::null_mut();
+    let returncode = unsafe { getaddrinfo(name.as_ptr(), std::ptr::null(), &hints, &mut result) };
+    if returncode != 0 {
+        return Err(gai_error(returncode));
+    }
+    let mut addresses = Vec::new();
+    let mut current = result;
+    while !current.is_null() {
+        let info = unsafe { &*current };
+        match info.ai_family {
+            AF_INET => {
+                let sin = unsafe { &*(info.ai_addr as *const sockaddr_in) };
+                let octets = sin.sin_addr.s_addr.to_ne_bytes();
+                addresses.push(IpAddr::V4(Ipv4Addr::from(octets)));
+            }
+            AF_INET6 => {
+                let sin6 = unsafe { &*(info.ai_addr as *const sockaddr_in6) };
+                addresses.push(IpAddr::V6(Ipv6Addr::from(sin6.sin6_addr.s6_addr)));
+            }
+            _ => {}
+        }
+        current = info.ai_next;
+    }
+    unsafe { freeaddrinfo(result) };
+    Ok(addresses)
+}
+
+/// Resolve the addresses of `name` with getaddrinfo.
+#[cfg(windows)]
+pub(crate) fn address_info(name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+    use std::ffi::CString;
+    use std::net::{Ipv4Addr, Ipv6Addr};
+    use winapi::shared::ws2def::{
+        ADDRINFOA, AF_INET, AF_INET6, AF_UNSPEC, SOCKADDR_IN, SOCK_STREAM,
+    };
+    use winapi::shared::ws2ipdef::SOCKADDR_IN6_LH;
+    use winapi::um::ws2tcpip::{freeaddrinfo, getaddrinfo};
+
+    init_winsock();
+    let name = CString::new(name).map_err(|error| {
+        HostnameError::invalid_name(
+            InvalidNameReason::InvalidCharacter('\0'),
+            error.nul_position(),
+        )
+    })?;
+    let mut hints: ADDRINFOA = unsafe { std::mem::zeroed() };
+    hints.ai_family = AF_UNSPEC;
+    // Get every address once, rather than once per socket type
+    hints.ai_socktype = SOCK_STREAM;
+    let mut result: *mut ADDRINFOA = std::ptr::null_mut();
+    let returncode = unsafe { getaddrinfo(name.as_ptr(), std::ptr::null(), &hints, &mut result) };
+    if returncode != 0 {
+        return Err(HostnameError::ResolutionFailed {
+            code: returncode,
+            message: std::io::Error::from_raw_os_error(returncode).to_string(),
+        });
+    }
+    let mut addresses = Vec::new();
+    let mut current = result;
+    while !current.is_null() {
+        let info = unsafe { &*current };
+        match info.ai_family {
+            AF_INET => {
+                let sin = unsafe { &*(info.ai_addr as *const SOCKADDR_IN) };
+                let octets = unsafe { *sin.sin_addr.S_un.S_addr() }.to_ne_bytes();
+                addresses.push(IpAddr::V4(Ipv4Addr::from(octets)));
+            }
+            AF_INET6 => {
+                let sin6 = unsafe { &*(info.ai_addr as *const SOCKADDR_IN6_LH) };
+                let octets = unsafe { *sin6.sin6_addr.u.Byte() };
+                addresses.push(IpAddr::V6(Ipv6Addr::from(octets)));
+            }
+            _ => {}
+        }
+        current = info.ai_next;
+    }
+    unsafe { freeaddrinfo(result) };
+    Ok(addresses)
+}
+
+/// Look up the name of `address` with getnameinfo.
+#[cfg(windows)]
+pub(crate) fn name_info(address: &SocketAddr) -> Result<OsString, HostnameError> {
+    use std::ffi::CStr;
+    use std::mem::size_of;
+    use winapi::shared::ws2def::{AF_INET, AF_INET6, NI_NAMEREQD, SOCKADDR_IN};
+    use winapi::shared::ws2ipdef::SOCKADDR_IN6_LH;
+    use winapi::um::ws2tcpip::getnameinfo;
+
+    init_winsock();
+    let mut sin: SOCKADDR_IN = unsafe { std::mem::zeroed() };
+    let mut sin6: SOCKADDR_IN6_LH = unsafe { std::mem::zeroed() };
+    let (pointer, len) = match address {
+        SocketAddr::V4(address) => {
+            sin.sin_family = AF_INET as _;
+            unsafe { *sin.sin_addr.S_un.S_addr_mut() = u32::from_ne_bytes(address.ip().octets()) };
+            (&sin as *const _ as *const _, size_of::<SOCKADDR_IN>())
+        }
+        SocketAddr::V6(address) => {
+            sin6.sin6_family = AF_INET6 as _;
+            unsafe {
+                *sin6.sin6_addr.u.Byte_mut() = address.ip().octets();
+                *sin6.u.sin6_scope_id_mut() = address.scope_id();
+            }
+            (&sin6 as *const _ as *const _, size_of::<SOCKADDR_IN6_LH>())
+        }
+    };
+    let mut host = [0_i8; NI_MAXHOST];
+    let returncode = unsafe {
+        getnameinfo(
+            pointer,
+            len as _,
+            host.as_mut_ptr(),
+            host.len() as _,
+            std::ptr::null_mut(),
+            0,
+            NI_NAMEREQD,
+        )
+    };
+    if returncode != 0 {
+        return Err(HostnameError::ResolutionFailed {
+            code: returncode,
+            message: std::io::Error::from_raw_os_error(returncode).to_string(),
+        });
+    }
+    let name = unsafe { CStr::from_ptr(host.as_ptr()) };
+    Ok(OsString::from(name.to_string_lossy().into_owned()))
+}
+
+/// Initialize Winsock for this process, once.
+///
+/// The standard library only initializes Winsock when it first uses sockets,
+/// and getnameinfo fails with `WSANOTINITIALISED` before.  We never clean up,
+/// like the standard library, since Winsock must stay initialized for all
+/// other users in the process.
+#[cfg(windows)]
+pub(crate) fn init_winsock() {
+    use std::sync::Once;
+    use winapi::um::winsock2::{WSAStartup, WSADATA};
+
+    static INIT: Once = Once::new();
+    INIT.call_once(|| {
+        let mut data: WSADATA = unsafe { std::mem::zeroed() };
+        // Request Winsock 2.2; failures surface in the calls which need it
+        let _ = unsafe { WSAStartup(0x202, &mut data) };
+    });
+}
+
+/// The maximum length of host names from getnameinfo, including the NUL byte.
+const NI_MAXHOST: usize = 1025;
+
+#[cfg(test)]
+mod tests {
+    use super::{name_info, on_helper_thread, reverse_lookup, FqdnStrategy, Resolver};
+    use crate::{
+        Backoff, CancellationToken, HostnameError, HostsFile, ResolverBackend, SystemBackend,
+    };
+    use pretty_assertions::assert_eq;
+    use std::ffi::{OsStr, OsString};
+    use std::net::{IpAddr, Ipv4Addr, SocketAddr};
+    use std::time::Duration;
+
+    #[test]
+    fn name_of_loopback_address() {
+        let address = SocketAddr::from((Ipv4Addr::LOCALHOST, 0));
+        let name = name_info(&address).unwrap();
+        assert!(name.to_str().unwrap().starts_with("localhost"));
+    }
+
+    #[test]
+    fn reverse_lookup_of_localhost() {
+        let name = reverse_lookup(&SystemBackend, OsStr::new("localhost")).unwrap();
+        assert!(name.to_str().unwrap().starts_with("localhost"));
+    }
+
+    #[test]
+    fn canonical_name_strategy_is_getfqdn() {
+        assert_eq!(Resolver::new().fqdn(), crate::getfqdn());
+    }
+
+    #[test]
+    fn hosts_file_before_system() {
+        let hosts = HostsFile::parse(
+            "192.0.2.1 first.example foo\n192.0.2.2 second.example foo\n192.0.2.1 other.example",
+        );
+        let resolver = Resolver::new().hosts_file(hosts);
+        assert_eq!(
+            resolver.hosts_fqdn(OsStr::new("foo")),
+            Some("first.example")
+        );
+        assert_eq!(resolver.hosts_fqdn(OsStr::new("bar")), None);
+        let resolver = resolver.strategy(FqdnStrategy::ReverseLookup);
+        assert_eq!(
+            resolver.hosts_fqdn(OsStr::new("foo")),
+            Some("first.example")
+        );
+    }
+
+    #[test]
+    fn hosts_file_without_hostname_falls_back_to_system() {
+        let resolver = Resolver::new().hosts_file(HostsFile::new());
+        assert_eq!(resolver.fqdn(), crate::getfqdn());
+    }
+
+    #[test]
+    fn reverse_lookup_strategy() {
+        let resolver = Resolver::new().strategy(FqdnStrategy::ReverseLookup);
+        // The current machine need not have a reverse DNS entry
+        if let Ok(fqdn) = resolver.fqdn() {
+            assert!(!fqdn.is_empty());
+        }
+    }
+
+    #[test]
+    fn lookup_within_timeout() {
+        let token = CancellationToken::new();
+        let timeout = Some(Duration::from_secs(10));
+        assert_eq!(
+            on_helper_thread(timeout, &token, || Ok(OsString::from("foo"))),
+            Ok(OsString::from("foo"))
+        );
+        let resolver = Resolver::new().timeout(Duration::from_secs(30));
+        assert_eq!(resolver.fqdn(), crate::getfqdn());
+    }
+
+    #[test]
+    fn lookup_times_out() {
+        let token = CancellationToken::new();
+        let result = on_helper_thread(Some(Duration::from_millis(10)), &token, || {
+            std::thread::sleep(Duration::from_secs(1));
+            Ok(OsString::from("foo"))
+        });
+        assert_eq!(result, Err(HostnameError::timed_out()));
+        assert!(result.unwrap_err().is_transient());
+    }
+
+    #[test]
+    fn within_timeout() {
+        assert_eq!(super::within(None, || Ok(42)), Ok(42));
+        assert_eq!(
+            super::within(Some(Duration::from_secs(30)), || Ok(42)),
+            Ok(42)
+        );
+        let result = super::within(Some(Duration::from_millis(10)), || {
+            std::thread::sleep(Duration::from_secs(1));
+            Ok(42)
+        });
+        assert_eq!(result, Err(HostnameError::timed_out()));
+    }
+
+    #[test]
+    fn cancelled_lookup() {
+        let token = CancellationToken::new();
+        let resolver = Resolver::new().retry(Backoff::new());
+        assert_eq!(resolver.fqdn_cancellable(&token), crate::getfqdn());
+        token.cancel();
+        assert_eq!(
+            resolver.fqdn_cancellable(&token),
+            Err(HostnameError::Cancelled)
+        );
+    }
+
+    #[derive(Debug)]
+    struct Backend;
+
+    impl ResolverBackend for Backend {
+        fn addresses(&self, _name: &str) -> Result<Vec<IpAddr>, HostnameError> {
+            Ok(vec![IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1))])
+        }
+
+        fn name_of(&self, _address: IpAddr) -> Result<String, HostnameError> {
+            Ok("reverse.example.com".to_string())
+        }
+
+        fn canonical_name(&self, _name: &str) -> Result<String, HostnameError> {
+            Ok("canonical.example.com".to_string())
+        }
+    }
+
+    #[test]
+    fn custom_backend() {
+        let resolver = Resolver::new().backend(Backend);
+        assert_eq!(resolver.fqdn().unwrap(), "canonical.example.com");
+        let resolver = resolver.strategy(FqdnStrategy::ReverseLookup);
+        assert_eq!(resolver.fqdn().unwrap(), "reverse.example.com");
+        let resolver = resolver.timeout(Duration::from_secs(10));
+        assert_eq!(resolver.fqdn().unwrap(), "reverse.example.com");
+    }
+
+    #[test]
+    fn resolvers_with_same_backend_are_equal() {
+        let resolver = Resolver::new().backend(Backend);
+        assert_eq!(resolver.clone(), resolver);
+        assert_ne!(Resolver::new().backend(Backend), resolver);
+        assert_ne!(Resolver::new(), resolver);
+        assert_eq!(Resolver::new(), Resolver::new());
+    }
+}
@@ -0,0 +1,198 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Turn arbitrary strings into valid hostnames.
+
+use crate::hostname::{is_numeric, Hostname, MAX_HOSTNAME_LEN, MAX_LABEL_LEN};
+
+/// The hostname to use if nothing remains of the input.
+const FALLBACK: &str = "host";
+
+/// Sanitize an arbitrary string into a valid hostname.
+///
+/// Lowercase `input`, transliterate common accented latin letters to ASCII,
+/// replace whitespace, underscores and punctuation with hyphens, and strip all
+/// other characters.  Dots separate labels; empty labels are dropped.  Collapse
+/// runs of hyphens, trim hyphens from both ends of every label, and truncate
+/// labels to 63 bytes and the whole name to 253 bytes, dropping labels which
+/// don't fit.
+///
+/// If the last label is entirely numeric replace the dots with hyphens, and if
+/// the result is still numeric prefix it with `host-`.  If nothing remains
+/// return `host`.
+///
+/// The result contains only ASCII letters, digits, hyphens and dots.
+///
+/// ```
+/// use gethostname::sanitize_to_hostname;
+///
+/// assert_eq!(
+///     sanitize_to_hostname("Jürgen's Build Server (2nd floor)").as_str(),
+///     "jurgen-s-build-server-2nd-floor"
+/// );
+/// assert_eq!(sanitize_to_hostname("10.0.0.1").as_str(), "10-0-0-1");
+/// assert_eq!(sanitize_to_hostname("!!!").as_str(), "host");
+/// ```
+pub fn sanitize_to_hostname(input: &str) -> Hostname {
+    let mut labels: Vec<String> = input
+        .split('.')
+        .map(sanitize_label)
+        .filter(|label| !label.is_empty())
+        .collect();
+    // Keep as many labels as fit into the maximum length
+    let mut len = 0;
+    let count = labels
+        .iter()
+        .take_while(|label| {
+            len += label.len() + usize::from(len != 0);
+            len <= MAX_HOSTNAME_LEN
+        })
+        .count();
+    labels.truncate(count);
+    // Truncating may leave a numeric last label, so check afterwards
+    if labels.last().is_some_and(|label| is_numeric(label)) {
+        labels = vec![sanitize_label(&labels.join("-"))];
+    }
+    if labels.last().is_some_and(|label| is_numeric(label)) {
+        labels = vec![sanitize_label(&format!("{}-{}", FALLBACK, labels[0]))];
+    }
+    let name = if labels.is_empty() {
+        FALLBACK.to_string()
+    } else {
+        labels.join(".")
+    };
+    Hostname::new_unchecked(name)
+}
+
+/// Sanitize a single label.
+fn sanitize_label(input: &str) -> String {
+    let mut label = String::with_capacity(input.len());
+    for c in input.chars().flat_map(char::to_lowercase) {
+        if c.is_ascii_alphanumeric() {
+            label.push(c);
+        } else if let Some(s) = transliterate(c) {
+            label.push_str(s);
+        } else if (c.is_whitespace() || c.is_ascii_punctuation() || c == '-')
+            && !label.ends_with('-')
+        {
+            label.push('-');
+        }
+    }
+    truncate_label(label.trim_start_matches('-'), MAX_LABEL_LEN).to_string()
+}
+
+/// Truncate `label` to at most `max` bytes at a character boundary, and trim
+/// trailing hyphens.
+pub(crate) fn truncate_label(label: &str, max: usize) -> &str {
+    let mut end = label.len().min(max);
+    while !label.is_char_boundary(end) {
+        end -= 1;
+    }
+    label[..end].trim_end_matches('-')
+}
+
+/// Transliterate a lowercase latin letter with diacritics to ASCII.
+fn transliterate(c: char) -> Option<&'static str> {
+    let s = match c {
+        'à' | 'á' | 'â' | 'ã' | 'ä' | 'å' | 'ā' | 'ă' | 'ą' => "a",
+        'æ' => "ae",
+        'ç' | 'ć' | 'č' => "c",
+        'ď' | 'đ' | 'ð' => "d",
+        'è' | 'é' | 'ê' | 'ë' | 'ē' | 'ė' | 'ę' | 'ě' => "e",
+        'ğ' => "g",
+        'ì' | 'í' | 'î' | 'ï' | 'ī' | 'į' | 'ı' => "i",
+        'ł' => "l",
+        'ñ' | 'ń' | 'ň' => "n",
+        'ò' | 'ó' | 'ô' | 'õ' | 'ö' | 'ø' | 'ō' | 'ő' => "o",
+        'œ' => "oe",
+        'ř' => "r",
+        'ś' | 'š' | 'ş' => "s",
+        'ß' => "ss",
+        'ť' | 'ţ' => "t",
+        'þ' => "th",
+        'ù' | 'ú' | 'û' | 'ü' | 'ū' | 'ů' | 'ű' | 'ų' => "u",
+        'ý' | 'ÿ' => "y",
+        'ź' | 'ż' | 'ž' => "z",
+        _ => return None,
+    };
+    Some(s)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::sanitize_to_hostname;
+    use pretty_assertions::assert_eq;
+
+    fn sanitize(s: &str) -> String {
+        sanitize_to_hostname(s).into_string()
+    }
+
+    #[test]
+    fn lowercases_and_replaces_separators() {
+        assert_eq!(sanitize("Web Server 01"), "web-server-01");
+        assert_eq!(sanitize("db_primary.Example.COM"), "db-primary.example.com");
+        assert_eq!(sanitize("a -- b__c"), "a-b-c");
+    }
+
+    #[test]
+    fn transliterates_and_strips() {
+        assert_eq!(sanitize("Bücherei Straße"), "bucherei-strasse");
+        assert_eq!(sanitize("Łódź"), "lodz");
+        assert_eq!(sanitize("☃ snow ☃"), "snow");
+        assert_eq!(sanitize("東京"), "host");
+    }
+
+    #[test]
+    fn trims_hyphens_and_empty_labels() {
+        assert_eq!(sanitize("-foo-.-bar-"), "foo.bar");
+        assert_eq!(sanitize("..foo...bar.."), "foo.bar");
+        assert_eq!(sanitize("foo.---.bar"), "foo.bar");
+    }
+
+    #[test]
+    fn numeric_names() {
+        assert_eq!(sanitize("10.0.0.1"), "10-0-0-1");
+        assert_eq!(sanitize("42"), "host-42");
+        assert_eq!(sanitize("rack.42"), "rack-42");
+        assert_eq!(sanitize("3com.com"), "3com.com");
+    }
+
+    #[test]
+    fn truncates_long_names() {
+        let label = format!("{}-{}", "a".repeat(62), "b".repeat(10));
+        assert_eq!(sanitize(&label), "a".repeat(62));
+        let name = vec!["a".repeat(63); 5].join(".");
+        assert_eq!(sanitize(&name), vec!["a".repeat(63); 3].join("."));
+    }
+
+    #[test]
+    fn truncation_leaves_numeric_label() {
+        let name = format!(
+            "abc.{}.1.{}",
+            vec!["a".repeat(63); 3].join("."),
+            "b".repeat(63)
+        );
+        let hostname = sanitize_to_hostname(&name);
+        assert_eq!(hostname.as_str(), format!("abc-{}", "a".repeat(59)));
+        assert!(hostname.as_str().parse::<crate::Hostname>().is_ok());
+    }
+
+    #[test]
+    fn results_are_valid() {
+        for input in &["", "   ", "-", ".", "x", "Ünïcödé Ⅻ", "a.1", "1.a"] {
+            let hostname = sanitize_to_hostname(input);
+            assert!(hostname.as_str().parse::<crate::Hostname>().is_ok());
+        }
+    }
+}
@@ -0,0 +1,118 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! JSON schema for [`Hostname`] and [`Fqdn`] with the [schemars] crate.
+//!
+//! [schemars]: https://docs.rs/schemars
+
+use std::borrow::Cow;
+
+use ::schemars::{json_schema, JsonSchema, Schema, SchemaGenerator};
+
+use crate::fqdn::Fqdn;
+use crate::hostname::{Hostname, MAX_HOSTNAME_LEN};
+
+/// A pattern for ASCII hostnames, as per RFC 1123.
+///
+/// Labels are at most 63 characters long, and start and end with a letter or
+/// digit.
+const HOSTNAME_PATTERN: &str = r"^[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?)*$";
+
+/// A pattern for ASCII FQDNs, i.e. hostnames with an optional trailing dot.
+const FQDN_PATTERN: &str = r"^[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?(\.[A-Za-z0-9]([A-Za-z0-9-]{0,61}[A-Za-z0-9])?)*\.?$";
+
+/// Describe hostnames as strings in the `hostname` format.
+///
+/// The `hostname` format of JSON schema only covers ASCII hostnames, so the
+/// schema rejects internationalized hostnames in their unicode form; use
+/// their punycode form in documents instead, see
+/// [`AsciiMode::Punycode`](crate::AsciiMode).
+impl JsonSchema for Hostname {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Hostname".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "gethostname::Hostname".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "format": "hostname",
+            "minLength": 1,
+            "maxLength": MAX_HOSTNAME_LEN,
+            "pattern": HOSTNAME_PATTERN,
+        })
+    }
+}
+
+/// Describe FQDNs as strings in the `hostname` format.
+///
+/// Like the schema of [`Hostname`], but allow a trailing dot, and thus one
+/// more byte.
+impl JsonSchema for Fqdn {
+    fn inline_schema() -> bool {
+        true
+    }
+
+    fn schema_name() -> Cow<'static, str> {
+        "Fqdn".into()
+    }
+
+    fn schema_id() -> Cow<'static, str> {
+        "gethostname::Fqdn".into()
+    }
+
+    fn json_schema(_: &mut SchemaGenerator) -> Schema {
+        json_schema!({
+            "type": "string",
+            "format": "hostname",
+            "minLength": 1,
+            "maxLength": MAX_HOSTNAME_LEN + 1,
+            "pattern": FQDN_PATTERN,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::{Fqdn, Hostname};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn hostname_schema() {
+        let schema = ::schemars::schema_for!(Hostname);
+        let value = |key| schema.get(key).unwrap();
+        assert_eq!(value("type").as_str(), Some("string"));
+        assert_eq!(value("format").as_str(), Some("hostname"));
+        assert_eq!(value("minLength").as_u64(), Some(1));
+        assert_eq!(value("maxLength").as_u64(), Some(253));
+        assert!(value("pattern").as_str().unwrap().starts_with('^'));
+    }
+
+    #[test]
+    fn fqdn_schema() {
+        let schema = ::schemars::schema_for!(Fqdn);
+        let value = |key| schema.get(key).unwrap();
+        assert_eq!(value("type").as_str(), Some("string"));
+        assert_eq!(value("format").as_str(), Some("hostname"));
+        assert_eq!(value("maxLength").as_u64(), Some(254));
+        assert!(value("pattern").as_str().unwrap().ends_with(r"\.?$"));
+    }
+}
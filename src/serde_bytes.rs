@@ -0,0 +1,161 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Serialize `OsString` hostnames as bytes, losslessly.
+//!
+//! Use this module with `#[serde(with = "gethostname::serde_bytes")]` on
+//! fields of type [`OsString`] to serialize them as the same bytes as
+//! [`gethostname_bytes()`](crate::gethostname_bytes): the raw bytes on Unix,
+//! and WTF-8 on Windows.  Deserialize from bytes, sequences of bytes, or
+//! strings.
+//!
+//! On Windows deserialization fails if the bytes are not valid UTF-8, i.e.
+//! for hostnames with unpaired surrogates; Windows doesn't permit these in
+//! hostnames anyway.
+//!
+//! ```
+//! use std::ffi::OsString;
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Report {
+//!     #[serde(with = "gethostname::serde_bytes")]
+//!     hostname: OsString,
+//! }
+//!
+//! let report = Report { hostname: gethostname::gethostname() };
+//! ```
+
+use std::ffi::{OsStr, OsString};
+use std::fmt;
+
+use serde::de::{self, Deserializer, SeqAccess, Visitor};
+use serde::Serializer;
+
+/// Serialize `hostname` as bytes.
+pub fn serialize<T, S>(hostname: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<OsStr>,
+    S: Serializer,
+{
+    serializer.serialize_bytes(&to_bytes(hostname.as_ref()))
+}
+
+/// Deserialize a hostname from bytes.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<OsString, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_byte_buf(HostnameVisitor)
+}
+
+#[cfg(not(windows))]
+fn to_bytes(hostname: &OsStr) -> Vec<u8> {
+    use std::os::unix::ffi::OsStrExt;
+    hostname.as_bytes().to_vec()
+}
+
+#[cfg(windows)]
+fn to_bytes(hostname: &OsStr) -> Vec<u8> {
+    use std::os::windows::ffi::OsStrExt;
+    crate::wtf8_bytes(hostname.encode_wide())
+}
+
+#[cfg(not(windows))]
+fn from_bytes<E: de::Error>(bytes: Vec<u8>) -> Result<OsString, E> {
+    use std::os::unix::ffi::OsStringExt;
+    Ok(OsString::from_vec(bytes))
+}
+
+#[cfg(windows)]
+fn from_bytes<E: de::Error>(bytes: Vec<u8>) -> Result<OsString, E> {
+    String::from_utf8(bytes)
+        .map(OsString::from)
+        .map_err(|error| {
+            E::invalid_value(de::Unexpected::Bytes(error.as_bytes()), &HostnameVisitor)
+        })
+}
+
+struct HostnameVisitor;
+
+impl<'de> Visitor<'de> for HostnameVisitor {
+    type Value = OsString;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("hostname bytes")
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<Self::Value, E> {
+        Ok(OsString::from(v))
+    }
+
+    fn visit_bytes<E: de::Error>(self, v: &[u8]) -> Result<Self::Value, E> {
+        from_bytes(v.to_vec())
+    }
+
+    fn visit_byte_buf<E: de::Error>(self, v: Vec<u8>) -> Result<Self::Value, E> {
+        from_bytes(v)
+    }
+
+    fn visit_seq<A: SeqAccess<'de>>(self, mut seq: A) -> Result<Self::Value, A::Error> {
+        let mut bytes = Vec::with_capacity(seq.size_hint().unwrap_or(0));
+        while let Some(byte) = seq.next_element()? {
+            bytes.push(byte);
+        }
+        from_bytes(bytes)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Report {
+        #[serde(with = "crate::serde_bytes")]
+        hostname: OsString,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let report = Report {
+            hostname: OsString::from("bü"),
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(json, r#"{"hostname":[98,195,188]}"#);
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    }
+
+    #[test]
+    fn deserialize_from_string() {
+        assert_eq!(
+            serde_json::from_str::<Report>(r#"{"hostname":"foo"}"#).unwrap(),
+            Report {
+                hostname: OsString::from("foo")
+            }
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn keeps_invalid_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+        let report = Report {
+            hostname: OsString::from_vec(b"a\xFF".to_vec()),
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(json, r#"{"hostname":[97,255]}"#);
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    }
+}
@@ -0,0 +1,89 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Serialize `OsString` hostnames as strings, lossily.
+//!
+//! Use this module with `#[serde(with = "gethostname::serde_lossy")]` on
+//! fields of type [`OsString`], e.g. for the result of
+//! [`gethostname()`](crate::gethostname), to serialize them as plain strings.
+//! Replace invalid unicode with `U+FFFD REPLACEMENT CHARACTER`; use
+//! [`serde_bytes`](crate::serde_bytes) to keep such hostnames intact.
+//!
+//! ```
+//! use std::ffi::OsString;
+//!
+//! #[derive(serde::Serialize, serde::Deserialize)]
+//! struct Report {
+//!     #[serde(with = "gethostname::serde_lossy")]
+//!     hostname: OsString,
+//! }
+//!
+//! let report = Report { hostname: gethostname::gethostname() };
+//! ```
+
+use std::ffi::{OsStr, OsString};
+
+use serde::{Deserialize, Deserializer, Serializer};
+
+/// Serialize `hostname` as a string, lossily.
+pub fn serialize<T, S>(hostname: &T, serializer: S) -> Result<S::Ok, S::Error>
+where
+    T: AsRef<OsStr>,
+    S: Serializer,
+{
+    serializer.serialize_str(&hostname.as_ref().to_string_lossy())
+}
+
+/// Deserialize a hostname from a string.
+pub fn deserialize<'de, D>(deserializer: D) -> Result<OsString, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    String::deserialize(deserializer).map(OsString::from)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    #[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+    struct Report {
+        #[serde(with = "crate::serde_lossy")]
+        hostname: OsString,
+    }
+
+    #[test]
+    fn roundtrip() {
+        let report = Report {
+            hostname: OsString::from("bücher"),
+        };
+        let json = serde_json::to_string(&report).unwrap();
+        assert_eq!(json, r#"{"hostname":"bücher"}"#);
+        assert_eq!(serde_json::from_str::<Report>(&json).unwrap(), report);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn replaces_invalid_unicode() {
+        use std::os::unix::ffi::OsStringExt;
+        let report = Report {
+            hostname: OsString::from_vec(b"foo\xFFbar".to_vec()),
+        };
+        assert_eq!(
+            serde_json::to_string(&report).unwrap(),
+            "{\"hostname\":\"foo\u{FFFD}bar\"}"
+        );
+    }
+}
@@ -0,0 +1,732 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Set the hostname of the current machine persistently.
+
+use std::ffi::OsStr;
+use std::fmt;
+use std::fs;
+use std::io::Write;
+use std::os::unix::ffi::OsStrExt;
+use std::path::Path;
+
+use crate::dry_run::{self, Change};
+use crate::error::HostnameError;
+use crate::hostname::Hostname;
+
+/// The outcome of [`set_hostname_persistent()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SetHostnameReport {
+    hostnamed: bool,
+    via_hostnamed: bool,
+}
+
+impl SetHostnameReport {
+    /// Whether we notified systemd-hostnamed of the new static hostname.
+    ///
+    /// `false` if the system doesn't run systemd, if the `dbus` feature is
+    /// disabled, or if the hostname was set [via
+    /// hostnamed](SetHostnameReport::via_hostnamed) in the first place.
+    pub fn hostnamed(&self) -> bool {
+        self.hostnamed
+    }
+
+    /// Whether the hostname was set through systemd-hostnamed.
+    pub fn via_hostnamed(&self) -> bool {
+        self.via_hostnamed
+    }
+}
+
+/// A step of [`set_hostname_persistent()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum SetHostnameStep {
+    /// Set the hostname of the kernel, or the transient hostname via
+    /// systemd-hostnamed.
+    Kernel,
+    /// Write `/etc/hostname`, or set the static hostname via
+    /// systemd-hostnamed.
+    EtcHostname,
+    /// Notify systemd-hostnamed of the new static hostname.
+    Hostnamed,
+}
+
+impl fmt::Display for SetHostnameStep {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            SetHostnameStep::Kernel => "set kernel hostname",
+            SetHostnameStep::EtcHostname => "write /etc/hostname",
+            SetHostnameStep::Hostnamed => "notify systemd-hostnamed",
+        })
+    }
+}
+
+/// A failed step of [`set_hostname_persistent()`], and the outcome of
+/// rolling back the steps before.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SetHostnameError {
+    step: SetHostnameStep,
+    error: HostnameError,
+    rollback: Result<(), HostnameError>,
+}
+
+impl SetHostnameError {
+    /// The step which failed.
+    pub fn step(&self) -> SetHostnameStep {
+        self.step
+    }
+
+    /// Why the step failed.
+    pub fn error(&self) -> &HostnameError {
+        &self.error
+    }
+
+    /// Whether rolling back the steps before succeeded.
+    ///
+    /// If rolling back fails the machine may be left half-renamed; the error
+    /// is the first failure among all steps we rolled back.
+    pub fn rollback(&self) -> Result<(), &HostnameError> {
+        self.rollback.as_ref().copied()
+    }
+}
+
+impl fmt::Display for SetHostnameError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to {}: {}", self.step, self.error)?;
+        match &self.rollback {
+            Ok(()) => Ok(()),
+            Err(error) => write!(f, ", and failed to roll back: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for SetHostnameError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+/// Set the hostname of the current machine, and make it persistent.
+///
+/// Do what `hostnamectl set-hostname` does, in three steps:
+///
+/// 1. Set the hostname of the kernel with [sethostname].
+/// 2. Atomically replace `/etc/hostname` with [`write_etc_hostname()`], so
+///    that the hostname persists across reboots.
+/// 3. If the system runs systemd, set the static hostname via
+///    systemd-hostnamed over D-Bus, so that hostnamed and its clients see the
+///    new name right away.  This step requires the `dbus` feature.
+///
+/// All steps require root privileges.  Use
+/// [`DryRun::record()`](crate::DryRun::record) to see the steps without
+/// performing them.
+///
+/// Without `CAP_SYS_ADMIN` set the transient and the static hostname via
+/// systemd-hostnamed instead, if the system runs systemd and the `dbus`
+/// feature is enabled.  hostnamed asks polkit, which may prompt the user to
+/// authenticate, so desktop applications can rename the machine without
+/// running privileged.
+///
+/// Setting the hostname is transactional: if a step fails, restore the
+/// previous kernel hostname and `/etc/hostname`, as far as earlier steps
+/// changed them, and fail with the failed step and the outcome of the
+/// rollback, so that the machine isn't left half-renamed.
+///
+/// The kernel rejects names longer than 64 bytes; check `hostname` with
+/// [`validate_for_current_platform()`](crate::validate_for_current_platform)
+/// first.
+///
+/// [sethostname]: https://man7.org/linux/man-pages/man2/sethostname.2.html
+pub fn set_hostname_persistent(hostname: &Hostname) -> Result<SetHostnameReport, SetHostnameError> {
+    let not_started = |step| {
+        move |error| SetHostnameError {
+            step,
+            error,
+            rollback: Ok(()),
+        }
+    };
+    // Restore the name of the kernel, not the one which the `configure` or
+    // `fixed-hostname` features make try_gethostname() return
+    let previous = crate::gethostname_impl().map_err(not_started(SetHostnameStep::Kernel))?;
+    let runs_systemd = Path::new("/run/systemd/system").exists();
+    if runs_systemd && !has_cap_sys_admin() {
+        if let Some(result) = set_via_hostnamed(hostname, &previous) {
+            return result;
+        }
+    }
+    let path = Path::new("/etc/hostname");
+    let previous_file = read_if_exists(path).map_err(not_started(SetHostnameStep::EtcHostname))?;
+    #[allow(unused_mut)]
+    let mut steps = vec![
+        Step {
+            step: SetHostnameStep::Kernel,
+            apply: Box::new(|| set_kernel_hostname(hostname.as_str().as_bytes())),
+            undo: Box::new(|| set_kernel_hostname(previous.as_bytes())),
+        },
+        Step {
+            step: SetHostnameStep::EtcHostname,
+            apply: Box::new(|| write_etc_hostname(hostname)),
+            undo: Box::new(|| restore_file(path, previous_file.as_deref())),
+        },
+    ];
+    let hostnamed = runs_systemd && cfg!(feature = "dbus");
+    #[cfg(feature = "dbus")]
+    if hostnamed {
+        steps.push(Step {
+            step: SetHostnameStep::Hostnamed,
+            // Don't ask for interactive authorization
+            apply: Box::new(|| crate::hostnamed::set_static_hostname(hostname, false)),
+            undo: Box::new(|| Ok(())),
+        });
+    }
+    transaction(steps)?;
+    Ok(SetHostnameReport {
+        hostnamed,
+        via_hostnamed: false,
+    })
+}
+
+/// A step of setting the hostname, and how to undo it.
+struct Step<'a> {
+    step: SetHostnameStep,
+    apply: Box<dyn FnOnce() -> Result<(), HostnameError> + 'a>,
+    undo: Box<dyn FnOnce() -> Result<(), HostnameError> + 'a>,
+}
+
+/// Apply `steps` in order, or none of them.
+///
+/// If a step fails undo all steps before in reverse order.
+fn transaction(steps: Vec<Step>) -> Result<(), SetHostnameError> {
+    let mut undo: Vec<Box<dyn FnOnce() -> _>> = Vec::new();
+    for step in steps {
+        if let Err(error) = (step.apply)() {
+            warn!("Failed to {}: {}", step.step, error);
+            // Undo all steps, even if undoing one of them fails
+            let mut rollback: Result<(), HostnameError> = Ok(());
+            for undo in undo.into_iter().rev() {
+                let undone = undo();
+                rollback = rollback.and(undone);
+            }
+            if let Err(error) = &rollback {
+                warn!("Failed to roll back hostname: {}", error);
+            }
+            return Err(SetHostnameError {
+                step: step.step,
+                error,
+                rollback,
+            });
+        }
+        undo.push(step.undo);
+    }
+    Ok(())
+}
+
+/// Whether the current process has `CAP_SYS_ADMIN`, which `sethostname`
+/// needs.
+///
+/// Assume that root has it if the effective capabilities are unknown.
+pub(crate) fn has_cap_sys_admin() -> bool {
+    fs::read_to_string("/proc/self/status")
+        .ok()
+        .and_then(|status| cap_sys_admin_in_status(&status))
+        .unwrap_or_else(|| unsafe { libc::geteuid() } == 0)
+}
+
+/// Whether the effective capabilities in `/proc/self/status` include
+/// `CAP_SYS_ADMIN`.
+fn cap_sys_admin_in_status(status: &str) -> Option<bool> {
+    const CAP_SYS_ADMIN: u32 = 21;
+    let capabilities = status
+        .lines()
+        .find_map(|line| line.strip_prefix("CapEff:"))?;
+    let capabilities = u64::from_str_radix(capabilities.trim(), 16).ok()?;
+    Some(capabilities & (1 << CAP_SYS_ADMIN) != 0)
+}
+
+fn set_kernel_hostname(name: &[u8]) -> Result<(), HostnameError> {
+    let change = Change::Syscall {
+        call: "sethostname",
+        argument: String::from_utf8_lossy(name).into_owned(),
+    };
+    let old = || {
+        crate::gethostname_impl()
+            .ok()
+            .map(|name| name.to_string_lossy().into_owned())
+    };
+    dry_run::perform(change, old, || {
+        let returncode =
+            unsafe { libc::sethostname(name.as_ptr() as *const libc::c_char, name.len()) };
+        if returncode == 0 {
+            Ok(())
+        } else {
+            Err(HostnameError::last_os_error())
+        }
+    })
+}
+
+/// Atomically replace `/etc/hostname` with `hostname`.
+///
+/// Write to a temporary file next to `/etc/hostname` first, flush it to disk,
+/// and then rename it over `/etc/hostname`, so that the file always contains
+/// either the old or the new name, even if the system crashes.  If
+/// `/etc/hostname` is a symlink replace its target instead of the link.
+///
+/// Give the new file the permissions, owner and SELinux context of the old
+/// file, if any, or mode `0644` otherwise.  Only warn if copying the owner or
+/// the SELinux context fails.
+///
+/// This is step 2 of [`set_hostname_persistent()`], for tools which manage
+/// the kernel hostname and systemd-hostnamed themselves.  It requires root
+/// privileges.
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn write_etc_hostname(hostname: &Hostname) -> Result<(), HostnameError> {
+    let path = Path::new("/etc/hostname");
+    let change = Change::WriteFile {
+        path: path.to_path_buf(),
+        contents: format!("{}\n", hostname),
+    };
+    dry_run::perform(
+        change,
+        || read_lossy(path),
+        || write_hostname_file(path, hostname.as_str()),
+    )
+}
+
+/// Read the file at `path`, or return `None` if it doesn't exist.
+fn read_if_exists(path: &Path) -> Result<Option<Vec<u8>>, HostnameError> {
+    match fs::read(path) {
+        Ok(contents) => Ok(Some(contents)),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(error) => Err(HostnameError::from_io(&error)),
+    }
+}
+
+/// Read the file at `path` as string, if it exists.
+fn read_lossy(path: &Path) -> Option<String> {
+    let contents = fs::read(path).ok()?;
+    Some(String::from_utf8_lossy(&contents).into_owned())
+}
+
+/// Restore the file at `path` to its `previous` contents, or remove it if
+/// it didn't exist.
+fn restore_file(path: &Path, previous: Option<&[u8]>) -> Result<(), HostnameError> {
+    match previous {
+        Some(contents) => {
+            let change = Change::WriteFile {
+                path: path.to_path_buf(),
+                contents: String::from_utf8_lossy(contents).into_owned(),
+            };
+            dry_run::perform(change, || read_lossy(path), || replace_file(path, contents))
+        }
+        None => {
+            let change = Change::RemoveFile {
+                path: path.to_path_buf(),
+            };
+            dry_run::perform(
+                change,
+                || read_lossy(path),
+                || fs::remove_file(path).map_err(|error| HostnameError::from_io(&error)),
+            )
+        }
+    }
+}
+
+fn write_hostname_file(path: &Path, name: &str) -> Result<(), HostnameError> {
+    replace_file(path, format!("{}\n", name).as_bytes())
+}
+
+fn replace_file(path: &Path, contents: &[u8]) -> Result<(), HostnameError> {
+    use std::io::ErrorKind;
+    use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    let io_error = |error: std::io::Error| HostnameError::from_io(&error);
+    let path = match fs::canonicalize(path) {
+        Ok(path) => path,
+        Err(error) if error.kind() == ErrorKind::NotFound => path.to_path_buf(),
+        Err(error) => return Err(io_error(error)),
+    };
+    let existing = match fs::metadata(&path) {
+        Ok(metadata) => Some(metadata),
+        Err(error) if error.kind() == ErrorKind::NotFound => None,
+        Err(error) => return Err(io_error(error)),
+    };
+    let mode = existing
+        .as_ref()
+        .map_or(0o644, |metadata| metadata.permissions().mode() & 0o7777);
+    let directory = path.parent().unwrap_or_else(|| Path::new("/"));
+    // Concurrent calls must not share a temporary file
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let temp_path = directory.join(format!(
+        ".hostname.{}.{}.tmp",
+        std::process::id(),
+        COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .mode(mode)
+            .open(&temp_path)?;
+        // Set the mode explicitly, since the umask applies at creation
+        file.set_permissions(fs::Permissions::from_mode(mode))?;
+        if let Some(metadata) = &existing {
+            copy_owner(&file, metadata);
+            copy_selinux_context(&path, &file);
+        }
+        file.write_all(contents)?;
+        file.sync_all()?;
+        fs::rename(&temp_path, &path)?;
+        // Make sure the rename itself hits the disk
+        fs::File::open(directory)?.sync_all()
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&temp_path);
+    }
+    result.map_err(io_error)
+}
+
+/// Give `file` the owner and group of `metadata`.
+fn copy_owner(file: &fs::File, metadata: &fs::Metadata) {
+    use std::os::unix::fs::MetadataExt;
+    use std::os::unix::io::AsRawFd;
+
+    if unsafe { libc::fchown(file.as_raw_fd(), metadata.uid(), metadata.gid()) } != 0 {
+        warn!(
+            "Failed to copy owner of hostname file: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Give `file` the SELinux context of the file at `path`, if any.
+fn copy_selinux_context(path: &Path, file: &fs::File) {
+    use std::ffi::CString;
+    use std::os::unix::ffi::OsStrExt;
+    use std::os::unix::io::AsRawFd;
+
+    let name = b"security.selinux\0".as_ptr() as *const libc::c_char;
+    let path = match CString::new(path.as_os_str().as_bytes()) {
+        Ok(path) => path,
+        Err(_) => return,
+    };
+    // SELinux contexts are short; 256 bytes leave plenty of room
+    let mut context = [0_u8; 256];
+    let size = unsafe {
+        libc::getxattr(
+            path.as_ptr(),
+            name,
+            context.as_mut_ptr() as *mut libc::c_void,
+            context.len(),
+        )
+    };
+    // Most likely the file system or the kernel doesn't support SELinux
+    if size < 0 {
+        return;
+    }
+    let returncode = unsafe {
+        libc::fsetxattr(
+            file.as_raw_fd(),
+            name,
+            context.as_ptr() as *const libc::c_void,
+            size as usize,
+            0,
+        )
+    };
+    if returncode != 0 {
+        warn!(
+            "Failed to copy SELinux context of hostname file: {}",
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+/// Set the transient and the static hostname via systemd-hostnamed, with
+/// interactive authorization.
+///
+/// Restore the `previous` transient hostname if setting the static hostname
+/// fails.
+#[cfg(feature = "dbus")]
+fn set_via_hostnamed(
+    hostname: &Hostname,
+    previous: &OsStr,
+) -> Option<Result<SetHostnameReport, SetHostnameError>> {
+    use crate::hostnamed::{set_static_hostname, set_transient_hostname};
+
+    let previous = previous.to_string_lossy().parse::<Hostname>();
+    let steps = vec![
+        Step {
+            step: SetHostnameStep::Kernel,
+            apply: Box::new(|| set_transient_hostname(hostname, true)),
+            undo: Box::new(|| {
+                previous.and_then(|previous| set_transient_hostname(&previous, true))
+            }),
+        },
+        Step {
+            step: SetHostnameStep::EtcHostname,
+            apply: Box::new(|| set_static_hostname(hostname, true)),
+            undo: Box::new(|| Ok(())),
+        },
+    ];
+    Some(transaction(steps).map(|()| SetHostnameReport {
+        hostnamed: false,
+        via_hostnamed: true,
+    }))
+}
+
+#[cfg(not(feature = "dbus"))]
+fn set_via_hostnamed(
+    _hostname: &Hostname,
+    _previous: &OsStr,
+) -> Option<Result<SetHostnameReport, SetHostnameError>> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        cap_sys_admin_in_status, restore_file, transaction, write_hostname_file, SetHostnameStep,
+        Step,
+    };
+    use crate::{Change, DryRun, HostnameError};
+    use pretty_assertions::assert_eq;
+    use std::cell::RefCell;
+    use std::fs;
+    use std::os::unix::fs::PermissionsExt;
+
+    #[test]
+    fn write_hostname_file_replaces_contents() {
+        let directory =
+            std::env::temp_dir().join(format!("gethostname-set-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("hostname");
+        fs::write(&path, "old\n").unwrap();
+        write_hostname_file(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new\n");
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn write_hostname_file_preserves_permissions() {
+        let directory =
+            std::env::temp_dir().join(format!("gethostname-set-mode-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("hostname");
+        write_hostname_file(&path, "new").unwrap();
+        let mode = |path| fs::metadata(path).unwrap().permissions().mode() & 0o7777;
+        assert_eq!(mode(&path), 0o644);
+        fs::set_permissions(&path, fs::Permissions::from_mode(0o600)).unwrap();
+        write_hostname_file(&path, "newer").unwrap();
+        assert_eq!(mode(&path), 0o600);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "newer\n");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn write_hostname_file_follows_symlinks() {
+        let directory =
+            std::env::temp_dir().join(format!("gethostname-set-link-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let target = directory.join("target");
+        let link = directory.join("hostname");
+        fs::write(&target, "old\n").unwrap();
+        std::os::unix::fs::symlink(&target, &link).unwrap();
+        write_hostname_file(&link, "new").unwrap();
+        assert!(fs::symlink_metadata(&link)
+            .unwrap()
+            .file_type()
+            .is_symlink());
+        assert_eq!(fs::read_to_string(&target).unwrap(), "new\n");
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn write_hostname_file_concurrently() {
+        let directory =
+            std::env::temp_dir().join(format!("gethostname-set-threads-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("hostname");
+        let threads = (0..8)
+            .map(|i| {
+                let path = path.clone();
+                std::thread::spawn(move || {
+                    for j in 0..20 {
+                        write_hostname_file(&path, &format!("host-{}-{}", i, j)).unwrap();
+                    }
+                })
+            })
+            .collect::<Vec<_>>();
+        for thread in threads {
+            thread.join().unwrap();
+        }
+        assert!(fs::read_to_string(&path).unwrap().starts_with("host-"));
+        assert_eq!(fs::read_dir(&directory).unwrap().count(), 1);
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    /// A step which logs to `log`, and fails to apply or undo if asked to.
+    fn step<'a>(
+        log: &'a RefCell<Vec<String>>,
+        step: SetHostnameStep,
+        apply: Result<(), HostnameError>,
+        undo: Result<(), HostnameError>,
+    ) -> Step<'a> {
+        Step {
+            step,
+            apply: Box::new(move || {
+                log.borrow_mut().push(format!("{}", step));
+                apply
+            }),
+            undo: Box::new(move || {
+                log.borrow_mut().push(format!("undo {}", step));
+                undo
+            }),
+        }
+    }
+
+    #[test]
+    fn transaction_applies_all_steps() {
+        let log = RefCell::new(Vec::new());
+        let steps = vec![
+            step(&log, SetHostnameStep::Kernel, Ok(()), Ok(())),
+            step(&log, SetHostnameStep::EtcHostname, Ok(()), Ok(())),
+        ];
+        assert_eq!(transaction(steps), Ok(()));
+        assert_eq!(
+            log.into_inner(),
+            vec!["set kernel hostname", "write /etc/hostname"]
+        );
+    }
+
+    #[test]
+    fn transaction_rolls_back_previous_steps() {
+        let failed = HostnameError::from_errno(libc::EACCES);
+        let log = RefCell::new(Vec::new());
+        let steps = vec![
+            step(&log, SetHostnameStep::Kernel, Ok(()), Ok(())),
+            step(&log, SetHostnameStep::EtcHostname, Ok(()), Ok(())),
+            step(
+                &log,
+                SetHostnameStep::Hostnamed,
+                Err(failed.clone()),
+                Ok(()),
+            ),
+            step(&log, SetHostnameStep::Hostnamed, Ok(()), Ok(())),
+        ];
+        let error = transaction(steps).unwrap_err();
+        assert_eq!(error.step(), SetHostnameStep::Hostnamed);
+        assert_eq!(error.error(), &failed);
+        assert_eq!(error.rollback(), Ok(()));
+        assert_eq!(
+            log.into_inner(),
+            vec![
+                "set kernel hostname",
+                "write /etc/hostname",
+                "notify systemd-hostnamed",
+                "undo write /etc/hostname",
+                "undo set kernel hostname"
+            ]
+        );
+    }
+
+    #[test]
+    fn transaction_reports_failed_rollback() {
+        let failed = HostnameError::from_errno(libc::EACCES);
+        let log = RefCell::new(Vec::new());
+        let steps = vec![
+            step(&log, SetHostnameStep::Kernel, Ok(()), Err(failed.clone())),
+            step(
+                &log,
+                SetHostnameStep::EtcHostname,
+                Err(failed.clone()),
+                Ok(()),
+            ),
+        ];
+        let error = transaction(steps).unwrap_err();
+        assert_eq!(error.step(), SetHostnameStep::EtcHostname);
+        assert_eq!(error.rollback(), Err(&failed));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "failed to write /etc/hostname: {}, and failed to roll back: {}",
+                failed, failed
+            )
+        );
+    }
+
+    #[test]
+    fn dry_run() {
+        let before = crate::gethostname();
+        let hostname = "foo".parse().unwrap();
+        let (result, dry_run) = DryRun::record(|| super::set_hostname_persistent(&hostname));
+        if !result.unwrap().via_hostnamed() {
+            assert_eq!(
+                dry_run.changes()[..2],
+                [
+                    Change::Syscall {
+                        call: "sethostname",
+                        argument: "foo".to_string()
+                    },
+                    Change::WriteFile {
+                        path: "/etc/hostname".into(),
+                        contents: "foo\n".to_string()
+                    }
+                ]
+            );
+        }
+        assert_eq!(crate::gethostname(), before);
+    }
+
+    #[test]
+    fn restore_file_removes_created_file() {
+        let directory =
+            std::env::temp_dir().join(format!("gethostname-set-restore-{}", std::process::id()));
+        fs::create_dir_all(&directory).unwrap();
+        let path = directory.join("hostname");
+        fs::write(&path, "new\n").unwrap();
+        let (result, dry_run) = DryRun::record(|| restore_file(&path, None));
+        result.unwrap();
+        assert_eq!(
+            dry_run.changes(),
+            [Change::RemoveFile { path: path.clone() }]
+        );
+        assert_eq!(
+            dry_run.changes()[0].to_string(),
+            format!("remove {}", path.display())
+        );
+        assert!(path.exists());
+        restore_file(&path, None).unwrap();
+        assert!(!path.exists());
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn cap_sys_admin() {
+        let status = |caps: &str| format!("Name:\tcat\nCapInh:\t0\nCapEff:\t{}\n", caps);
+        assert_eq!(
+            cap_sys_admin_in_status(&status("000001ffffffffff")),
+            Some(true)
+        );
+        assert_eq!(
+            cap_sys_admin_in_status(&status("0000000000200000")),
+            Some(true)
+        );
+        assert_eq!(
+            cap_sys_admin_in_status(&status("0000000000000000")),
+            Some(false)
+        );
+        assert_eq!(cap_sys_admin_in_status("Name:\tcat\n"), None);
+    }
+}
@@ -0,0 +1,148 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The client identity for the SMTP `EHLO` and `HELO` commands.
+//!
+//! ```
+//! println!("EHLO {}", gethostname::smtp::ehlo_name());
+//! ```
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr, UdpSocket};
+use std::time::Duration;
+
+/// Get the identity of the current machine for the SMTP `EHLO` command.
+///
+/// [RFC 5321] requires the client to identify itself with its fully qualified
+/// domain name, or with an address literal if it has no meaningful domain
+/// name.  Return the result of [`getfqdn()`](crate::getfqdn) if it is a fully
+/// qualified domain name, i.e. contains a dot.  Otherwise return an address
+/// literal like `[192.0.2.1]` or `[IPv6:2001:db8::1]` for the first address of
+/// the current machine which is neither loopback nor unspecified.
+///
+/// If all else fails return the loopback address literal `[127.0.0.1]`.
+///
+/// [RFC 5321]: https://tools.ietf.org/html/rfc5321#section-4.1.4
+pub fn ehlo_name() -> String {
+    ehlo_name_within(None)
+}
+
+/// Get the identity of the current machine for the SMTP `EHLO` command within
+/// `timeout`.
+///
+/// Like [`ehlo_name()`], but give up resolving the FQDN or the addresses of
+/// the hostname after `timeout`, as with
+/// [`getfqdn_timeout()`](crate::getfqdn_timeout).
+pub fn ehlo_name_timeout(timeout: Duration) -> String {
+    ehlo_name_within(Some(timeout))
+}
+
+fn ehlo_name_within(timeout: Option<Duration>) -> String {
+    match crate::fqdn::getfqdn_within(timeout)
+        .ok()
+        .and_then(|fqdn| fqdn.into_string().ok())
+    {
+        Some(fqdn) if is_fqdn(&fqdn) => fqdn.trim_end_matches('.').to_string(),
+        _ => {
+            warn!("No fully qualified domain name, using address literal for EHLO");
+            let address = local_address(timeout).unwrap_or_else(|| {
+                warn!("No address for EHLO, using loopback address");
+                IpAddr::V4(Ipv4Addr::LOCALHOST)
+            });
+            address_literal(address)
+        }
+    }
+}
+
+fn is_fqdn(name: &str) -> bool {
+    let name = name.trim_end_matches('.');
+    name.contains('.') && name.is_ascii()
+}
+
+/// Format `address` as an RFC 5321 address literal.
+pub fn address_literal(address: IpAddr) -> String {
+    match address {
+        IpAddr::V4(address) => format!("[{}]", address),
+        IpAddr::V6(address) => format!("[IPv6:{}]", address),
+    }
+}
+
+fn is_global(address: &IpAddr) -> bool {
+    !(address.is_loopback() || address.is_unspecified())
+}
+
+/// Find an address of the current machine.
+///
+/// Try the addresses the hostname resolves to first, and then the source
+/// address of an unconnected UDP socket routed towards the documentation
+/// networks; the latter doesn't send any packets, but makes the system pick the
+/// address of the interface of the default route.
+fn local_address(timeout: Option<Duration>) -> Option<IpAddr> {
+    let hostname = crate::try_gethostname().ok()?.into_string().ok()?;
+    let resolved =
+        crate::resolver::within(timeout, move || crate::resolver::address_info(&hostname))
+            .ok()
+            .and_then(|addresses| addresses.into_iter().find(is_global));
+    resolved.or_else(|| {
+        let targets = [
+            (
+                IpAddr::V4(Ipv4Addr::UNSPECIFIED),
+                IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1)),
+            ),
+            (
+                IpAddr::V6(Ipv6Addr::UNSPECIFIED),
+                IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1)),
+            ),
+        ];
+        targets.iter().find_map(|&(bind, target)| {
+            let socket = UdpSocket::bind((bind, 0)).ok()?;
+            socket.connect((target, 25)).ok()?;
+            Some(socket.local_addr().ok()?.ip()).filter(is_global)
+        })
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{address_literal, is_fqdn};
+    use pretty_assertions::assert_eq;
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    #[test]
+    fn ipv4_address_literal() {
+        let address = IpAddr::V4(Ipv4Addr::new(192, 0, 2, 1));
+        assert_eq!(address_literal(address), "[192.0.2.1]");
+    }
+
+    #[test]
+    fn ipv6_address_literal() {
+        let address = IpAddr::V6(Ipv6Addr::new(0x2001, 0xdb8, 0, 0, 0, 0, 0, 1));
+        assert_eq!(address_literal(address), "[IPv6:2001:db8::1]");
+    }
+
+    #[test]
+    fn fully_qualified_names() {
+        assert!(is_fqdn("mail.example.com"));
+        assert!(is_fqdn("mail.example.com."));
+        assert!(!is_fqdn("mail"));
+        assert!(!is_fqdn("mail."));
+    }
+
+    #[test]
+    fn ehlo_name_is_fqdn_or_address_literal() {
+        let name = super::ehlo_name();
+        assert!(is_fqdn(&name) || (name.starts_with('[') && name.ends_with(']')));
+        let name = super::ehlo_name_timeout(std::time::Duration::from_secs(30));
+        assert!(is_fqdn(&name) || (name.starts_with('[') && name.ends_with(']')));
+    }
+}
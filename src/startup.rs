@@ -0,0 +1,83 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Detect changes of the hostname during the lifetime of the process.
+
+use std::ffi::OsString;
+use std::sync::OnceLock;
+
+static STARTUP_HOSTNAME: OnceLock<OsString> = OnceLock::new();
+
+/// Check whether the hostname changed since the process started.
+///
+/// Return the hostname at startup and the current hostname if they differ,
+/// and `None` otherwise, or if getting the hostname fails.
+///
+/// Remember the hostname from the first successful call of this function as
+/// hostname at startup; call this function once early in `main` so that later
+/// calls notice when cloud-init or an administrator rename the machine.  Each
+/// call gets the hostname from the system, but does no other work, so long
+/// running daemons can cheaply call it periodically, e.g. to re-register with
+/// service discovery under the new name.
+///
+/// ```
+/// // Early in main
+/// gethostname::hostname_changed_since_start();
+///
+/// // Later
+/// if let Some((old, new)) = gethostname::hostname_changed_since_start() {
+///     println!("Renamed from {:?} to {:?}", old, new);
+/// }
+/// ```
+pub fn hostname_changed_since_start() -> Option<(OsString, OsString)> {
+    let current = crate::try_gethostname()
+        .map_err(|error| warn!("Failed to get hostname: {}", error))
+        .ok()?;
+    changed_since(&STARTUP_HOSTNAME, current)
+}
+
+fn changed_since(startup: &OnceLock<OsString>, current: OsString) -> Option<(OsString, OsString)> {
+    let startup = startup.get_or_init(|| current.clone());
+    if *startup == current {
+        None
+    } else {
+        Some((startup.clone(), current))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::changed_since;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+    use std::sync::OnceLock;
+
+    #[test]
+    fn detect_change() {
+        let startup = OnceLock::new();
+        assert_eq!(changed_since(&startup, OsString::from("foo")), None);
+        assert_eq!(changed_since(&startup, OsString::from("foo")), None);
+        assert_eq!(
+            changed_since(&startup, OsString::from("bar")),
+            Some((OsString::from("foo"), OsString::from("bar")))
+        );
+        assert_eq!(changed_since(&startup, OsString::from("foo")), None);
+    }
+
+    #[test]
+    fn unchanged_hostname() {
+        assert_eq!(super::hostname_changed_since_start(), None);
+        assert_eq!(super::hostname_changed_since_start(), None);
+    }
+}
@@ -0,0 +1,477 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The system calls which get the hostname.
+//!
+//! Under Miri, and in tests which inject a hostname with [`with_fake_hostname()`],
+//! these functions don't call into the operating system, but emulate the system
+//! calls in pure Rust.  This lets Miri and our tests check how we scan for NUL
+//! bytes, size buffers and convert wide strings, for hostnames of any length
+//! and content.  Tests can also make these system calls fail with any error
+//! code with [`with_failing_syscalls()`], to check our error handling.
+//!
+//! Miri can't run the other tests of this crate, so run just these tests with
+//! `cargo +nightly miri test --lib sys::`.
+
+use crate::error::HostnameError;
+
+#[cfg(not(windows))]
+use libc::{c_int, c_long};
+#[cfg(windows)]
+use winapi::{
+    ctypes::{c_ulong, wchar_t},
+    shared::minwindef::BOOL,
+    um::sysinfoapi::COMPUTER_NAME_FORMAT,
+};
+
+/// Call `gethostname` with `buffer`.
+///
+/// On iOS and its siblings get the hostname from `sysctl` instead.
+#[cfg(not(windows))]
+#[inline]
+pub(crate) fn gethostname(buffer: &mut [u8]) -> c_int {
+    #[cfg(any(test, miri))]
+    if let Some(returncode) = fake::gethostname(buffer) {
+        return returncode;
+    }
+    #[cfg(all(target_vendor = "apple", not(target_os = "macos")))]
+    let returncode = crate::ios::kern_hostname(buffer);
+    #[cfg(not(all(target_vendor = "apple", not(target_os = "macos"))))]
+    let returncode =
+        unsafe { libc::gethostname(buffer.as_mut_ptr() as *mut libc::c_char, buffer.len()) };
+    #[cfg(target_os = "android")]
+    if returncode == 0 {
+        crate::android::replace_localhost(buffer);
+    }
+    returncode
+}
+
+/// Write `hostname` into `buffer` like `gethostname` does.
+///
+/// Like some Unix systems silently truncate `hostname` if it doesn't fit into
+/// `buffer`, and omit the trailing NUL byte in this case.
+#[cfg(all(not(windows), any(test, miri, target_os = "android")))]
+pub(crate) fn copy_hostname(buffer: &mut [u8], hostname: &[u8]) {
+    let len = hostname.len().min(buffer.len());
+    buffer[..len].copy_from_slice(&hostname[..len]);
+    if let Some(nul) = buffer.get_mut(len) {
+        *nul = 0;
+    }
+}
+
+/// Call `sysconf(_SC_HOST_NAME_MAX)`.
+#[cfg(all(not(windows), not(miri)))]
+#[inline]
+pub(crate) fn host_name_max() -> c_long {
+    unsafe { libc::sysconf(libc::_SC_HOST_NAME_MAX) }
+}
+
+/// Pretend that hostnames have at most 64 bytes, like on Linux.
+#[cfg(all(not(windows), miri))]
+pub(crate) fn host_name_max() -> c_long {
+    64
+}
+
+/// Call `GetComputerNameExW`.
+///
+/// # Safety
+///
+/// `buffer` must be NULL or point to at least `*size` wide characters.
+#[cfg(windows)]
+#[inline]
+pub(crate) unsafe fn get_computer_name_ex_w(
+    name_type: COMPUTER_NAME_FORMAT,
+    buffer: *mut wchar_t,
+    size: &mut c_ulong,
+) -> BOOL {
+    #[cfg(any(test, miri))]
+    if let Some(returncode) = fake::get_computer_name_ex_w(buffer, size) {
+        return returncode;
+    }
+    winapi::um::sysinfoapi::GetComputerNameExW(name_type, buffer, size)
+}
+
+/// Get the NetBIOS name of the computer with `GetComputerNameW`.
+///
+/// # Safety
+///
+/// `buffer` must point to at least `*size` wide characters.
+#[cfg(windows)]
+#[inline]
+pub(crate) unsafe fn get_computer_name_w(buffer: *mut wchar_t, size: &mut c_ulong) -> BOOL {
+    #[cfg(any(test, miri))]
+    if let Some(returncode) = fake::get_computer_name_ex_w(buffer, size) {
+        return returncode;
+    }
+    winapi::um::winbase::GetComputerNameW(buffer, size)
+}
+
+/// Get the error of the last failed system call of this module.
+#[inline]
+pub(crate) fn last_error() -> HostnameError {
+    #[cfg(any(test, miri))]
+    if let Some(errno) = fake::failure() {
+        return HostnameError::from_errno(errno);
+    }
+    HostnameError::last_os_error()
+}
+
+/// Get the Win32 error code of the last failed system call of this module.
+#[cfg(windows)]
+#[inline]
+pub(crate) fn last_error_code() -> u32 {
+    #[cfg(any(test, miri))]
+    if let Some(errno) = fake::failure() {
+        return errno as u32;
+    }
+    // GetLastError() always has a code for us
+    std::io::Error::last_os_error().raw_os_error().unwrap_or(0) as u32
+}
+
+#[cfg(test)]
+pub(crate) use self::fake::{with_failing_syscalls, with_fake_hostname};
+
+/// Emulated system calls.
+#[cfg(any(test, miri))]
+mod fake {
+    use std::cell::{Cell, RefCell};
+    use std::ffi::OsString;
+
+    thread_local! {
+        static HOSTNAME: RefCell<Option<OsString>> = const { RefCell::new(None) };
+        static FAILURE: Cell<Option<i32>> = const { Cell::new(None) };
+    }
+
+    /// Run `f` with system calls which return `hostname` on the current thread.
+    #[cfg(test)]
+    pub(crate) fn with_fake_hostname<R>(hostname: impl Into<OsString>, f: impl FnOnce() -> R) -> R {
+        let previous = HOSTNAME.with(|fake| fake.replace(Some(hostname.into())));
+        let result = f();
+        HOSTNAME.with(|fake| fake.replace(previous));
+        result
+    }
+
+    /// Run `f` with system calls which fail with `errno` on the current thread.
+    ///
+    /// `errno` is the value of `errno` on Unix, and the result of
+    /// `GetLastError()` on Windows.
+    #[cfg(test)]
+    pub(crate) fn with_failing_syscalls<R>(errno: i32, f: impl FnOnce() -> R) -> R {
+        let previous = FAILURE.with(|failure| failure.replace(Some(errno)));
+        let result = f();
+        FAILURE.with(|failure| failure.set(previous));
+        result
+    }
+
+    /// Get the error code system calls fail with on the current thread, if any.
+    pub(super) fn failure() -> Option<i32> {
+        FAILURE.with(Cell::get)
+    }
+
+    /// Get the fake hostname of the current thread.
+    ///
+    /// Miri can't make system calls, so it always gets a fake hostname.
+    fn hostname() -> Option<OsString> {
+        HOSTNAME
+            .with(|fake| fake.borrow().clone())
+            .or_else(|| cfg!(miri).then(|| OsString::from("miri")))
+    }
+
+    /// Emulate `gethostname`.
+    #[cfg(not(windows))]
+    pub(super) fn gethostname(buffer: &mut [u8]) -> Option<libc::c_int> {
+        use std::os::unix::ffi::OsStrExt;
+
+        if failure().is_some() {
+            return Some(-1);
+        }
+        super::copy_hostname(buffer, hostname()?.as_bytes());
+        Some(0)
+    }
+
+    /// Emulate `GetComputerNameExW` for any name type.
+    ///
+    /// If `buffer` is too small fail with `ERROR_MORE_DATA`, and set `size` to
+    /// the required size including the trailing NUL character.  Otherwise set
+    /// `size` to the length of the name, excluding the trailing NUL.
+    ///
+    /// # Safety
+    ///
+    /// `buffer` must be NULL or point to at least `*size` wide characters.
+    #[cfg(windows)]
+    pub(super) unsafe fn get_computer_name_ex_w(
+        buffer: *mut winapi::ctypes::wchar_t,
+        size: &mut winapi::ctypes::c_ulong,
+    ) -> Option<winapi::shared::minwindef::BOOL> {
+        use std::os::windows::ffi::OsStrExt;
+        use winapi::shared::winerror::ERROR_MORE_DATA;
+        use winapi::um::errhandlingapi::SetLastError;
+
+        if failure().is_some() {
+            return Some(0);
+        }
+        let hostname = hostname()?.encode_wide().collect::<Vec<_>>();
+        if buffer.is_null() || (*size as usize) <= hostname.len() {
+            *size = (hostname.len() + 1) as _;
+            SetLastError(ERROR_MORE_DATA);
+            return Some(0);
+        }
+        let buffer = std::slice::from_raw_parts_mut(buffer, *size as usize);
+        buffer[..hostname.len()].copy_from_slice(&hostname);
+        buffer[hostname.len()] = 0;
+        *size = hostname.len() as _;
+        Some(1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{with_failing_syscalls, with_fake_hostname};
+    use crate::{HostnameError, InvalidNameReason};
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    /// Hostnames of all lengths up to and a bit beyond the maximum.
+    fn hostnames() -> impl Iterator<Item = String> {
+        // Miri is slow, so only check interesting lengths there
+        let lengths: Vec<usize> = if cfg!(miri) {
+            vec![0, 1, 63, 64, 65, 66, 255, 256, 257]
+        } else {
+            (0..=crate::max_hostname_len() + 2)
+                .chain(255..=257)
+                .collect()
+        };
+        lengths.into_iter().map(|len| {
+            (0..len)
+                .map(|i| char::from(b'a' + (i % 26) as u8))
+                .collect()
+        })
+    }
+
+    /// Whether the `strict` feature rejects `hostname`.
+    fn strictly_empty(hostname: &str) -> bool {
+        cfg!(feature = "strict") && hostname.is_empty()
+    }
+
+    fn empty_hostname() -> HostnameError {
+        HostnameError::invalid_name(crate::InvalidNameReason::Empty, 0)
+    }
+
+    #[test]
+    fn try_gethostname_returns_hostname_up_to_buffer_size() {
+        // On Unix we allocate one extra byte for the NUL byte, so we get one
+        // more byte than the maximum if the hostname is too long.  On Windows
+        // we ask for the size of the buffer first.
+        let size = crate::max_hostname_len() + 1;
+        for hostname in hostnames() {
+            let expected = if cfg!(windows) {
+                &hostname[..]
+            } else {
+                &hostname[..hostname.len().min(size)]
+            };
+            let result = with_fake_hostname(hostname.as_str(), crate::try_gethostname);
+            if strictly_empty(&hostname) {
+                assert_eq!(result, Err(empty_hostname()));
+            } else {
+                assert_eq!(result.unwrap(), OsString::from(expected));
+            }
+        }
+    }
+
+    #[test]
+    fn try_gethostname_into_buffers_of_all_sizes() {
+        for hostname in hostnames().filter(|name| name.len() < 70) {
+            for size in 0..hostname.len() + 3 {
+                let mut buffer = vec![0xff; size];
+                let result = with_fake_hostname(hostname.as_str(), || {
+                    crate::try_gethostname_into(&mut buffer).map(str::to_owned)
+                });
+                // On Windows we convert from a buffer of wide characters, so
+                // we don't need room for a trailing NUL
+                let fits = if cfg!(windows) {
+                    hostname.len() <= size
+                } else {
+                    hostname.len() < size
+                };
+                if fits && strictly_empty(&hostname) {
+                    assert_eq!(result, Err(empty_hostname()));
+                } else if fits {
+                    assert_eq!(result.unwrap(), hostname);
+                } else {
+                    assert_eq!(result, Err(HostnameError::BufferTooSmall));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn gethostname_tls_with_changing_hostnames() {
+        let size = crate::max_hostname_len() + 1;
+        for hostname in hostnames() {
+            let result = with_fake_hostname(hostname.as_str(), || {
+                crate::gethostname_tls(|name| name.to_os_string())
+            });
+            if strictly_empty(&hostname) {
+                assert_eq!(result, Err(empty_hostname()));
+            } else if cfg!(windows) || hostname.len() < size {
+                assert_eq!(result.unwrap(), OsString::from(hostname));
+            } else {
+                assert_eq!(result, Err(HostnameError::BufferTooSmall));
+            }
+        }
+    }
+
+    #[test]
+    fn non_ascii_hostname() {
+        let hostname = "bücher.example";
+        assert_eq!(
+            with_fake_hostname(hostname, crate::try_gethostname).unwrap(),
+            OsString::from(hostname)
+        );
+        let mut buffer = [0; 256];
+        assert_eq!(
+            with_fake_hostname(hostname, || crate::try_gethostname_into(&mut buffer)),
+            Ok(hostname)
+        );
+        // Don't split multi-byte characters
+        let mut buffer = [0; 2];
+        assert_eq!(
+            with_fake_hostname("bü", || crate::try_gethostname_into(&mut buffer)),
+            Err(HostnameError::BufferTooSmall)
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn hostname_with_invalid_utf8() {
+        use std::os::unix::ffi::OsStringExt;
+
+        let hostname = OsString::from_vec(b"foo\xffbar".to_vec());
+        assert_eq!(
+            with_fake_hostname(hostname.clone(), crate::try_gethostname).unwrap(),
+            hostname
+        );
+        let mut buffer = [0; 256];
+        assert_eq!(
+            with_fake_hostname(hostname, || crate::try_gethostname_into(&mut buffer)),
+            Err(HostnameError::invalid_name(
+                InvalidNameReason::NotUnicode,
+                3
+            ))
+        );
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn hostname_with_unpaired_surrogate() {
+        use std::os::windows::ffi::OsStringExt;
+
+        let hostname = OsString::from_wide(&[0x66, 0x6f, 0x6f, 0xd800, 0x62]);
+        assert_eq!(
+            with_fake_hostname(hostname.clone(), crate::try_gethostname).unwrap(),
+            hostname
+        );
+        let mut buffer = [0; 256];
+        assert_eq!(
+            with_fake_hostname(hostname, || crate::try_gethostname_into(&mut buffer)),
+            Err(HostnameError::invalid_name(
+                InvalidNameReason::NotUnicode,
+                3
+            ))
+        );
+    }
+
+    #[test]
+    fn hostname_up_to_embedded_nul() {
+        assert_eq!(
+            with_fake_hostname("foo\0bar", crate::try_gethostname).unwrap(),
+            OsString::from("foo")
+        );
+    }
+
+    /// An arbitrary error code: `EACCES` on Unix, `ERROR_ACCESS_DENIED` on
+    /// Windows.
+    #[cfg(not(windows))]
+    const ACCESS_DENIED: i32 = libc::EACCES;
+    #[cfg(windows)]
+    const ACCESS_DENIED: i32 = 5;
+
+    /// The system call which gets the hostname.
+    #[cfg(not(windows))]
+    const CALL: &str = "gethostname";
+    #[cfg(windows)]
+    const CALL: &str = "GetComputerNameExW";
+
+    /// Get the OS error code and the failed call of `result`.
+    fn errno_and_call<T>(result: Result<T, HostnameError>) -> Option<(i32, Option<&'static str>)> {
+        match result {
+            Err(HostnameError::SyscallFailed { errno, call, .. }) => Some((errno, call)),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn failing_syscalls_return_errors() {
+        let error = Some((ACCESS_DENIED, Some(CALL)));
+        with_failing_syscalls(ACCESS_DENIED, || {
+            assert_eq!(errno_and_call(crate::try_gethostname()), error);
+            assert_eq!(
+                errno_and_call(crate::try_gethostname_into(&mut [0; 256])),
+                error
+            );
+            assert_eq!(errno_and_call(crate::gethostname_tls(|_| ())), error);
+            assert_eq!(errno_and_call(crate::Hostname::current()), error);
+        });
+        // Only fail within with_failing_syscalls
+        assert!(crate::try_gethostname().is_ok());
+    }
+
+    #[test]
+    fn failing_syscalls_report_buffer_size() {
+        let mut buffer = [0; 256];
+        assert_eq!(
+            with_failing_syscalls(ACCESS_DENIED, || crate::try_gethostname_into(&mut buffer)),
+            Err(HostnameError::from_errno(ACCESS_DENIED).in_call(CALL, Some(256)))
+        );
+    }
+
+    #[test]
+    fn getfqdn_fails_with_failing_syscalls() {
+        assert_eq!(
+            errno_and_call(with_failing_syscalls(ACCESS_DENIED, crate::getfqdn)),
+            Some((ACCESS_DENIED, Some(CALL)))
+        );
+    }
+
+    #[test]
+    fn truncated_hostname_is_buffer_too_small() {
+        // glibc fails with ENAMETOOLONG, and Windows with ERROR_MORE_DATA if
+        // the hostname doesn't fit into the buffer
+        #[cfg(not(windows))]
+        let errno = libc::ENAMETOOLONG;
+        #[cfg(windows)]
+        let errno = winapi::shared::winerror::ERROR_MORE_DATA as i32;
+        let mut buffer = [0; 256];
+        assert_eq!(
+            with_failing_syscalls(errno, || crate::try_gethostname_into(&mut buffer)),
+            Err(HostnameError::BufferTooSmall)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "failed to get hostname")]
+    fn gethostname_panics_if_syscalls_fail() {
+        with_failing_syscalls(ACCESS_DENIED, crate::gethostname);
+    }
+}
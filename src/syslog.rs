@@ -0,0 +1,88 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The `HOSTNAME` field of [RFC 5424] syslog messages.
+//!
+//! ```
+//! println!("<34>1 2003-10-11T22:14:15.003Z {} su - ID47 - hello", gethostname::syslog::hostname());
+//! ```
+//!
+//! [RFC 5424]: https://tools.ietf.org/html/rfc5424#section-6.2.4
+
+use std::ffi::OsStr;
+
+/// The syslog `NILVALUE`, used when the hostname is unknown.
+pub const NILVALUE: &str = "-";
+
+/// The maximum length of the `HOSTNAME` field.
+const MAX_LEN: usize = 255;
+
+/// Get the hostname of the current machine for the syslog `HOSTNAME` field.
+///
+/// See [`format_hostname()`] for how the hostname is rendered.  Return
+/// [`NILVALUE`] if the operating system fails to return a hostname.
+pub fn hostname() -> String {
+    crate::try_gethostname()
+        .map(|hostname| format_hostname(&hostname).to_string())
+        .unwrap_or_else(|_| NILVALUE.to_string())
+}
+
+/// Render `hostname` for the syslog `HOSTNAME` field.
+///
+/// RFC 5424 permits only printable US-ASCII characters without spaces in
+/// the `HOSTNAME` field, and at most 255 of them.  Return `hostname` unchanged
+/// if it satisfies these constraints, and truncate it if it's too long.  If
+/// `hostname` is empty or contains any other character return [`NILVALUE`]
+/// instead, because RFC 5424 requires `NILVALUE` if the syslog application
+/// does not know the hostname.
+pub fn format_hostname(hostname: &OsStr) -> &str {
+    match hostname.to_str() {
+        Some(s) if !s.is_empty() && s.bytes().all(|b| (33..=126).contains(&b)) => {
+            &s[..s.len().min(MAX_LEN)]
+        }
+        _ => NILVALUE,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{format_hostname, NILVALUE};
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsStr;
+
+    #[test]
+    fn printable_ascii_hostname() {
+        assert_eq!(
+            format_hostname(OsStr::new("mymachine.example.com")),
+            "mymachine.example.com"
+        );
+    }
+
+    #[test]
+    fn long_hostname_is_truncated() {
+        let hostname = "a".repeat(300);
+        assert_eq!(format_hostname(OsStr::new(&hostname)), "a".repeat(255));
+    }
+
+    #[test]
+    fn empty_hostname_is_nilvalue() {
+        assert_eq!(format_hostname(OsStr::new("")), NILVALUE);
+    }
+
+    #[test]
+    fn non_printable_hostname_is_nilvalue() {
+        assert_eq!(format_hostname(OsStr::new("my machine")), NILVALUE);
+        assert_eq!(format_hostname(OsStr::new("bücher")), NILVALUE);
+    }
+}
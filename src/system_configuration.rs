@@ -0,0 +1,136 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Bindings to the CoreFoundation and SystemConfiguration frameworks of macOS.
+
+use std::ffi::CStr;
+use std::os::raw::{c_char, c_int, c_void};
+
+use crate::error::HostnameError;
+
+pub(crate) type CFStringRef = *const c_void;
+pub(crate) type SCPreferencesRef = *const c_void;
+
+const K_CF_STRING_ENCODING_UTF8: u32 = 0x0800_0100;
+
+#[link(name = "CoreFoundation", kind = "framework")]
+extern "C" {
+    fn CFStringCreateWithBytes(
+        allocator: *const c_void,
+        bytes: *const u8,
+        length: isize,
+        encoding: u32,
+        is_external_representation: u8,
+    ) -> CFStringRef;
+    fn CFStringGetCString(
+        string: CFStringRef,
+        buffer: *mut c_char,
+        size: isize,
+        encoding: u32,
+    ) -> u8;
+    fn CFRelease(object: *const c_void);
+}
+
+#[link(name = "SystemConfiguration", kind = "framework")]
+extern "C" {
+    pub(crate) fn SCDynamicStoreCopyLocalHostName(store: *const c_void) -> CFStringRef;
+    pub(crate) fn SCPreferencesCreate(
+        allocator: *const c_void,
+        name: CFStringRef,
+        prefs_id: CFStringRef,
+    ) -> SCPreferencesRef;
+    pub(crate) fn SCPreferencesLock(prefs: SCPreferencesRef, wait: u8) -> u8;
+    pub(crate) fn SCPreferencesCommitChanges(prefs: SCPreferencesRef) -> u8;
+    pub(crate) fn SCPreferencesApplyChanges(prefs: SCPreferencesRef) -> u8;
+    pub(crate) fn SCPreferencesUnlock(prefs: SCPreferencesRef) -> u8;
+    pub(crate) fn SCPreferencesSetComputerName(
+        prefs: SCPreferencesRef,
+        name: CFStringRef,
+        encoding: u32,
+    ) -> u8;
+    pub(crate) fn SCPreferencesSetLocalHostName(prefs: SCPreferencesRef, name: CFStringRef) -> u8;
+    pub(crate) fn SCPreferencesSetHostName(prefs: SCPreferencesRef, name: CFStringRef) -> u8;
+    fn SCError() -> c_int;
+    fn SCErrorString(status: c_int) -> *const c_char;
+}
+
+/// An owned CoreFoundation object, released on drop.
+pub(crate) struct Owned(*const c_void);
+
+impl Owned {
+    /// Take ownership of `object`, or return `None` if it's NULL.
+    pub(crate) fn new(object: *const c_void) -> Option<Owned> {
+        if object.is_null() {
+            None
+        } else {
+            Some(Owned(object))
+        }
+    }
+
+    /// Create a CoreFoundation string from `s`.
+    pub(crate) fn string(s: &str) -> Option<Owned> {
+        Owned::new(unsafe {
+            CFStringCreateWithBytes(
+                std::ptr::null(),
+                s.as_ptr(),
+                s.len() as isize,
+                K_CF_STRING_ENCODING_UTF8,
+                0,
+            )
+        })
+    }
+
+    pub(crate) fn as_ptr(&self) -> *const c_void {
+        self.0
+    }
+
+    /// Convert this CoreFoundation string to a Rust string.
+    pub(crate) fn to_string_lossy(&self) -> Option<String> {
+        // Names in the system configuration are short, so don't bother with
+        // computing the required size.
+        let mut buffer = [0 as c_char; 1024];
+        let converted = unsafe {
+            CFStringGetCString(
+                self.0,
+                buffer.as_mut_ptr(),
+                buffer.len() as isize,
+                K_CF_STRING_ENCODING_UTF8,
+            )
+        };
+        if converted == 0 {
+            None
+        } else {
+            let s = unsafe { CStr::from_ptr(buffer.as_ptr()) };
+            Some(s.to_string_lossy().into_owned())
+        }
+    }
+}
+
+impl Drop for Owned {
+    fn drop(&mut self) {
+        unsafe { CFRelease(self.0) }
+    }
+}
+
+/// The last error of the SystemConfiguration framework.
+pub(crate) fn last_error() -> HostnameError {
+    let message = unsafe { CStr::from_ptr(SCErrorString(SCError())) };
+    HostnameError::ServiceFailed {
+        service: "SystemConfiguration",
+        message: message.to_string_lossy().into_owned(),
+    }
+}
+
+/// The encoding for `SCPreferencesSetComputerName`.
+pub(crate) const COMPUTER_NAME_ENCODING: u32 = K_CF_STRING_ENCODING_UTF8;
@@ -0,0 +1,201 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Format templates for the names of the current machine.
+
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// Format the names of the current machine into `template`.
+///
+/// Replace the following placeholders, after the options of `hostname(1)`:
+///
+/// * `%H` with the hostname, as returned by [`try_gethostname()`](crate::try_gethostname),
+/// * `%s` with the short hostname, i.e. the hostname up to the first dot,
+/// * `%f` with the fully qualified domain name, as returned by
+///   [`getfqdn()`](crate::getfqdn),
+/// * `%d` with the domain, i.e. the FQDN after the first dot, which is empty
+///   if the current machine has no domain, and
+/// * `%%` with a single `%`.
+///
+/// Keep all other characters, including unknown placeholders, as they are.
+/// Only look up the names `template` uses, so `%f` and `%d` resolve the
+/// hostname, but `%H` and `%s` don't.  Replace invalid unicode in names with
+/// `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// Fail if a lookup fails.
+///
+/// ```
+/// let prompt = gethostname::format("[%s] $ ").unwrap();
+/// println!("{}", prompt);
+/// ```
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn format(template: &str) -> Result<String, HostnameError> {
+    render(
+        template,
+        || Ok(crate::try_gethostname()?.to_string_lossy().into_owned()),
+        || Ok(crate::getfqdn()?.to_string_lossy().into_owned()),
+    )
+}
+
+/// Format the names of the current machine into `template` within `timeout`.
+///
+/// Like [`format()`], but give up resolving the hostname for `%f` and `%d`
+/// after `timeout`, as with [`getfqdn_timeout()`](crate::getfqdn_timeout).
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn format_timeout(template: &str, timeout: Duration) -> Result<String, HostnameError> {
+    render(
+        template,
+        || Ok(crate::try_gethostname()?.to_string_lossy().into_owned()),
+        || {
+            Ok(crate::getfqdn_timeout(timeout)?
+                .to_string_lossy()
+                .into_owned())
+        },
+    )
+}
+
+/// Render `template` with lazily looked up `hostname` and `fqdn`.
+fn render<H, F>(template: &str, hostname: H, fqdn: F) -> Result<String, HostnameError>
+where
+    H: FnOnce() -> Result<String, HostnameError>,
+    F: FnOnce() -> Result<String, HostnameError>,
+{
+    let mut hostname = Lookup::new(hostname);
+    let mut fqdn = Lookup::new(fqdn);
+    let mut output = String::with_capacity(template.len());
+    let mut chars = template.chars();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('H') => output.push_str(hostname.get()?),
+            Some('s') => output.push_str(short(hostname.get()?)),
+            Some('f') => output.push_str(fqdn.get()?),
+            Some('d') => output.push_str(domain(fqdn.get()?)),
+            Some('%') => output.push('%'),
+            Some(other) => {
+                output.push('%');
+                output.push(other);
+            }
+            None => output.push('%'),
+        }
+    }
+    Ok(output)
+}
+
+/// A name which is looked up at most once.
+struct Lookup<F> {
+    lookup: Option<F>,
+    name: String,
+}
+
+impl<F: FnOnce() -> Result<String, HostnameError>> Lookup<F> {
+    fn new(lookup: F) -> Lookup<F> {
+        Lookup {
+            lookup: Some(lookup),
+            name: String::new(),
+        }
+    }
+
+    fn get(&mut self) -> Result<&str, HostnameError> {
+        if let Some(lookup) = self.lookup.take() {
+            self.name = lookup()?;
+        }
+        Ok(&self.name)
+    }
+}
+
+fn short(hostname: &str) -> &str {
+    hostname.split('.').next().unwrap_or(hostname)
+}
+
+fn domain(fqdn: &str) -> &str {
+    fqdn.trim_end_matches('.')
+        .split_once('.')
+        .map_or("", |(_, domain)| domain)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::render;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+
+    fn render_names(template: &str) -> String {
+        render(
+            template,
+            || Ok("web-01".to_string()),
+            || Ok("web-01.example.com.".to_string()),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn placeholders() {
+        assert_eq!(render_names("%H"), "web-01");
+        assert_eq!(render_names("%s"), "web-01");
+        assert_eq!(render_names("%f"), "web-01.example.com.");
+        assert_eq!(render_names("%d"), "example.com");
+        assert_eq!(
+            render_names("%s in %d (%H)"),
+            "web-01 in example.com (web-01)"
+        );
+    }
+
+    #[test]
+    fn literal_and_unknown_placeholders() {
+        assert_eq!(render_names(""), "");
+        assert_eq!(render_names("100%% %x %"), "100% %x %");
+        assert_eq!(render_names("bücher"), "bücher");
+    }
+
+    #[test]
+    fn looks_up_only_used_names() {
+        let output = render("%s", || Ok("foo.bar".to_string()), || panic!("no FQDN")).unwrap();
+        assert_eq!(output, "foo");
+        let error = render(
+            "%H %f",
+            || Ok("foo".to_string()),
+            || Err(HostnameError::Unsupported),
+        );
+        assert_eq!(error, Err(HostnameError::Unsupported));
+    }
+
+    #[test]
+    fn format_timeout_without_fqdn() {
+        let timeout = std::time::Duration::from_secs(30);
+        assert_eq!(
+            super::format_timeout("%H %%", timeout),
+            super::format("%H %%")
+        );
+    }
+
+    #[test]
+    fn domain_of_name_without_domain() {
+        let output = render("[%d]", || unreachable!(), || Ok("foo".to_string())).unwrap();
+        assert_eq!(output, "[]");
+    }
+
+    #[test]
+    fn format_hostname_of_current_machine() {
+        assert_eq!(
+            super::format("%H").unwrap(),
+            crate::gethostname().to_string_lossy()
+        );
+    }
+}
@@ -0,0 +1,127 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Get the hostname without allocating on every call.
+//!
+//! On Unix keep a buffer for the hostname per thread, and reuse it on every
+//! call.  On Windows the standard library offers no way to borrow an `OsStr`
+//! from a wide string buffer, so allocate as usual.
+
+use std::ffi::{OsStr, OsString};
+
+use crate::error::HostnameError;
+
+#[cfg(not(windows))]
+thread_local! {
+    static BUFFER: std::cell::RefCell<Vec<u8>> = const { std::cell::RefCell::new(Vec::new()) };
+}
+
+/// Call `f` with the hostname of the current machine.
+///
+/// Like [`try_gethostname()`](crate::try_gethostname), but lend the hostname
+/// to `f` from a buffer which each thread allocates once and then reuses, so
+/// that calling this function over and over again, e.g. for every log record,
+/// doesn't allocate.  Return the result of `f`.
+///
+/// ```
+/// let len = gethostname::gethostname_tls(|hostname| hostname.len()).unwrap();
+/// assert_eq!(len, gethostname::gethostname().len());
+/// ```
+pub fn gethostname_tls<F, R>(f: F) -> Result<R, HostnameError>
+where
+    F: FnOnce(&OsStr) -> R,
+{
+    gethostname_tls_impl(f)
+}
+
+/// Copy the hostname of the current machine into `target`.
+///
+/// Replace the contents of `target` with the hostname, and reuse the
+/// allocation of `target` if it has enough capacity.  Together with
+/// [`gethostname_tls()`] this gets the hostname without allocating once
+/// `target` is large enough.
+///
+/// ```
+/// let mut hostname = std::ffi::OsString::new();
+/// gethostname::gethostname_tls_into(&mut hostname).unwrap();
+/// assert_eq!(hostname, gethostname::gethostname());
+/// ```
+pub fn gethostname_tls_into(target: &mut OsString) -> Result<(), HostnameError> {
+    gethostname_tls(|hostname| {
+        target.clear();
+        target.push(hostname);
+    })
+}
+
+#[cfg(not(windows))]
+fn gethostname_tls_impl<F, R>(f: F) -> Result<R, HostnameError>
+where
+    F: FnOnce(&OsStr) -> R,
+{
+    use std::os::unix::ffi::OsStrExt;
+
+    BUFFER.with(|buffer| match buffer.try_borrow_mut() {
+        Ok(mut buffer) => {
+            // Account for the trailing NUL byte
+            let len = crate::max_hostname_len() + 1;
+            if buffer.len() < len {
+                buffer.resize(len, 0);
+            }
+            let hostname = crate::gethostname_bytes_into(&mut buffer)?;
+            Ok(f(OsStr::from_bytes(hostname)))
+        }
+        // f called us again, and still holds the buffer
+        Err(_) => crate::try_gethostname().map(|hostname| f(&hostname)),
+    })
+}
+
+#[cfg(windows)]
+fn gethostname_tls_impl<F, R>(f: F) -> Result<R, HostnameError>
+where
+    F: FnOnce(&OsStr) -> R,
+{
+    crate::try_gethostname().map(|hostname| f(&hostname))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{gethostname_tls, gethostname_tls_into};
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    #[test]
+    fn hostname_from_thread_local_buffer() {
+        for _ in 0..3 {
+            let hostname = gethostname_tls(|hostname| hostname.to_os_string()).unwrap();
+            assert_eq!(hostname, crate::gethostname());
+        }
+    }
+
+    #[test]
+    fn nested_calls() {
+        let (outer, inner) = gethostname_tls(|outer| {
+            let inner = gethostname_tls(|inner| inner.to_os_string()).unwrap();
+            (outer.to_os_string(), inner)
+        })
+        .unwrap();
+        assert_eq!(outer, inner);
+    }
+
+    #[test]
+    fn hostname_into_string() {
+        let mut hostname = OsString::from("previous contents");
+        gethostname_tls_into(&mut hostname).unwrap();
+        assert_eq!(hostname, crate::gethostname());
+    }
+}
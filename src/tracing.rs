@@ -0,0 +1,57 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Integration with [tracing].
+//!
+//! ```
+//! use gethostname::tracing::{hostname_field, record_hostname};
+//!
+//! let span = tracing::info_span!("request", host.name = tracing::field::Empty);
+//! record_hostname(&span);
+//!
+//! tracing::info!(host.name = hostname_field(), "hello");
+//! ```
+//!
+//! With this feature the fallible operations of this crate also emit spans at
+//! debug level, and record their errors.
+//!
+//! [tracing]: https://docs.rs/tracing
+
+use ::tracing::field::Value;
+use ::tracing::Span;
+
+/// The name of the hostname field, as per the OpenTelemetry semantic
+/// conventions.
+pub const FIELD: &str = "host.name";
+
+/// Get the hostname as a value for a tracing field.
+///
+/// Take the hostname from a process-wide cache, to avoid asking the operating
+/// system for every span or event.  Replace invalid unicode in the hostname
+/// with `U+FFFD REPLACEMENT CHARACTER`.  If the operating system fails to
+/// return a hostname the value is empty.
+pub fn hostname_field() -> impl Value {
+    crate::cached_hostname()
+        .map(|hostname| hostname.to_string_lossy().into_owned())
+        .unwrap_or_default()
+}
+
+/// Record the hostname in the [`FIELD`] field of `span`.
+///
+/// Like all fields, `span` must declare the field when it is created, e.g.
+/// with `host.name = tracing::field::Empty`, otherwise the hostname is not
+/// recorded.
+pub fn record_hostname(span: &Span) -> &Span {
+    span.record(FIELD, hostname_field())
+}
@@ -0,0 +1,101 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Conversions between [`Hostname`] and [`url::Host`].
+
+use std::convert::TryFrom;
+
+use ::url::{Host, ParseError};
+
+use crate::error::HostnameError;
+use crate::hostname::{Hostname, InvalidNameReason};
+
+impl Hostname {
+    /// Create a hostname from a host of the [url] crate.
+    ///
+    /// Fail with [`InvalidNameReason::IpAddress`] if `host` is an IP address,
+    /// or if the domain of `host` is not a valid hostname.  Note that the url
+    /// crate stores internationalized domains in their punycode form, so the
+    /// resulting hostname is ASCII-only.
+    ///
+    /// ```
+    /// use gethostname::Hostname;
+    ///
+    /// let url = url::Url::parse("https://bücher.example/").unwrap();
+    /// let hostname = Hostname::from_url_host(&url.host().unwrap()).unwrap();
+    /// assert_eq!(hostname.as_str(), "xn--bcher-kva.example");
+    /// ```
+    ///
+    /// [url]: https://docs.rs/url
+    pub fn from_url_host<S: AsRef<str>>(host: &Host<S>) -> Result<Hostname, HostnameError> {
+        match host {
+            Host::Domain(domain) => domain.as_ref().parse(),
+            Host::Ipv4(_) | Host::Ipv6(_) => {
+                Err(HostnameError::invalid_name(InvalidNameReason::IpAddress, 0))
+            }
+        }
+    }
+}
+
+/// Convert a hostname into a domain host.
+///
+/// Internationalized hostnames are converted to punycode, which fails if the
+/// hostname is not a valid internationalized domain name.
+impl TryFrom<Hostname> for Host {
+    type Error = ParseError;
+
+    fn try_from(hostname: Hostname) -> Result<Self, Self::Error> {
+        Host::parse(hostname.as_str())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Hostname;
+    use pretty_assertions::assert_eq;
+    use std::convert::TryFrom;
+    use std::net::Ipv4Addr;
+    use url::Host;
+
+    #[test]
+    fn hostname_to_url_host() {
+        let hostname: Hostname = "foo.example.com".parse().unwrap();
+        assert_eq!(
+            Host::try_from(hostname).unwrap(),
+            Host::Domain("foo.example.com".to_string())
+        );
+    }
+
+    #[test]
+    fn internationalized_hostname_to_url_host() {
+        let hostname: Hostname = "bücher.example".parse().unwrap();
+        assert_eq!(
+            Host::try_from(hostname).unwrap(),
+            Host::Domain("xn--bcher-kva.example".to_string())
+        );
+    }
+
+    #[test]
+    fn round_trip() {
+        let hostname: Hostname = "foo.example.com".parse().unwrap();
+        let host = Host::try_from(hostname.clone()).unwrap();
+        assert_eq!(Hostname::from_url_host(&host).unwrap(), hostname);
+    }
+
+    #[test]
+    fn ip_address_is_not_a_hostname() {
+        let host: Host<String> = Host::Ipv4(Ipv4Addr::LOCALHOST);
+        assert!(Hostname::from_url_host(&host).is_err());
+    }
+}
@@ -0,0 +1,139 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Validate hostnames against the limits of the current platform.
+
+use crate::error::HostnameError;
+use crate::hostname::{violations, Hostname, InvalidNameReason};
+use crate::options::Strictness;
+
+/// Check whether `name` can become the hostname of the current machine.
+///
+/// Check `name` against the rules of [`Hostname`], and against the limits of
+/// the current platform:
+///
+/// * On Unix the name must not exceed [`max_hostname_len()`](crate::max_hostname_len),
+///   e.g. 64 bytes on Linux.
+/// * On Windows the name must be a single label, as for
+///   `rename_computer()`, and not exceed the 15 characters of NetBIOS names.
+///
+/// Unlike parsing a [`Hostname`] don't stop at the first problem, but return
+/// all violations at once, each as [`HostnameError::InvalidName`], so that
+/// callers can report them all before they start to set the hostname.
+///
+/// ```
+/// use gethostname::{validate_for_current_platform, HostnameError, InvalidNameReason};
+///
+/// let errors = validate_for_current_platform("-foo.bar-").unwrap_err();
+/// assert_eq!(
+///     errors[..2],
+///     [
+///         HostnameError::InvalidName { reason: InvalidNameReason::LeadingHyphen, position: 0 },
+///         HostnameError::InvalidName { reason: InvalidNameReason::TrailingHyphen, position: 8 },
+///     ]
+/// );
+/// ```
+pub fn validate_for_current_platform(name: &str) -> Result<Hostname, Vec<HostnameError>> {
+    let violations = platform_violations(name, crate::max_hostname_len());
+    if violations.is_empty() {
+        Hostname::validated(name.to_string(), Strictness::default()).map_err(|error| vec![error])
+    } else {
+        Err(violations
+            .into_iter()
+            .map(|(reason, position)| HostnameError::invalid_name(reason, position))
+            .collect())
+    }
+}
+
+#[cfg(not(windows))]
+fn platform_violations(name: &str, max_len: usize) -> Vec<(InvalidNameReason, usize)> {
+    let mut violations = violations(name, Strictness::default());
+    if max_len < name.len() {
+        violations.push((InvalidNameReason::TooLongForPlatform, max_len));
+    }
+    violations
+}
+
+#[cfg(windows)]
+fn platform_violations(name: &str, _max_len: usize) -> Vec<(InvalidNameReason, usize)> {
+    let mut violations = violations(name, Strictness::default());
+    if let Some(position) = name.find('.') {
+        violations.push((InvalidNameReason::InvalidCharacter('.'), position));
+    }
+    if let Some((position, _)) = name.char_indices().nth(crate::hostname::MAX_NETBIOS_LEN) {
+        violations.push((InvalidNameReason::NetbiosTooLong, position));
+    }
+    violations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{platform_violations, validate_for_current_platform};
+    use crate::{HostnameError, InvalidNameReason};
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn valid_name() {
+        assert_eq!(
+            validate_for_current_platform("foo").unwrap().as_str(),
+            "foo"
+        );
+    }
+
+    #[test]
+    fn reports_all_violations() {
+        assert_eq!(
+            validate_for_current_platform("-foo..bar_"),
+            Err(vec![
+                HostnameError::invalid_name(InvalidNameReason::LeadingHyphen, 0),
+                HostnameError::invalid_name(InvalidNameReason::EmptyLabel, 5),
+                HostnameError::invalid_name(InvalidNameReason::InvalidCharacter('_'), 9),
+            ]
+            .into_iter()
+            .chain(if cfg!(windows) {
+                Some(HostnameError::invalid_name(
+                    InvalidNameReason::InvalidCharacter('.'),
+                    4,
+                ))
+            } else {
+                None
+            })
+            .collect())
+        );
+    }
+
+    #[cfg(not(windows))]
+    #[test]
+    fn platform_limit() {
+        let name = "a".repeat(40) + "." + &"b".repeat(40);
+        assert_eq!(
+            platform_violations(&name, 64),
+            vec![(InvalidNameReason::TooLongForPlatform, 64)]
+        );
+        assert_eq!(platform_violations(&name, 255), Vec::new());
+    }
+
+    #[cfg(windows)]
+    #[test]
+    fn netbios_limit() {
+        assert_eq!(
+            platform_violations("workstation-0001.example", 63),
+            vec![
+                (InvalidNameReason::InvalidCharacter('.'), 16),
+                (InvalidNameReason::NetbiosTooLong, 15),
+            ]
+        );
+        assert_eq!(platform_violations("workstation-001", 63), Vec::new());
+    }
+}
@@ -0,0 +1,183 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Watch the hostname for changes.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::Duration;
+
+use crate::error::HostnameError;
+
+/// The default interval between two checks of the hostname.
+const DEFAULT_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+type Source = Box<dyn FnMut() -> Result<OsString, HostnameError> + Send>;
+
+/// A blocking iterator over changes of the hostname.
+///
+/// Check the hostname periodically, and yield the new hostname whenever it
+/// differs from the hostname at the previous check.  Block in
+/// [`next()`](Iterator::next) until the hostname changes, and end iteration
+/// once a [`CloseHandle`] closes the iterator.  Skip checks which fail to get
+/// the hostname.
+///
+/// ```no_run
+/// for hostname in gethostname::HostnameChanges::new() {
+///     println!("Renamed to {:?}", hostname);
+/// }
+/// ```
+pub struct HostnameChanges {
+    source: Source,
+    last: Option<OsString>,
+    poll_interval: Duration,
+    closed: Arc<(Mutex<bool>, Condvar)>,
+}
+
+impl HostnameChanges {
+    /// Watch the hostname of the current machine.
+    ///
+    /// Remember the current hostname, and check every five seconds.
+    pub fn new() -> HostnameChanges {
+        HostnameChanges::with_source(Box::new(crate::try_gethostname))
+    }
+
+    fn with_source(mut source: Source) -> HostnameChanges {
+        let last = source()
+            .map_err(|error| warn!("Failed to get hostname: {}", error))
+            .ok();
+        HostnameChanges {
+            source,
+            last,
+            poll_interval: DEFAULT_POLL_INTERVAL,
+            closed: Arc::default(),
+        }
+    }
+
+    /// Check the hostname every `interval`.
+    pub fn poll_interval(mut self, interval: Duration) -> HostnameChanges {
+        self.poll_interval = interval;
+        self
+    }
+
+    /// Get a handle to close this iterator, e.g. from another thread.
+    pub fn close_handle(&self) -> CloseHandle {
+        CloseHandle(self.closed.clone())
+    }
+
+    /// Wait for the poll interval, and return whether we got closed.
+    fn wait(&self) -> bool {
+        let (closed, condvar) = &*self.closed;
+        let closed = closed.lock().unwrap_or_else(|error| error.into_inner());
+        let (closed, _) = condvar
+            .wait_timeout_while(closed, self.poll_interval, |closed| !*closed)
+            .unwrap_or_else(|error| error.into_inner());
+        *closed
+    }
+}
+
+impl Default for HostnameChanges {
+    fn default() -> Self {
+        HostnameChanges::new()
+    }
+}
+
+impl fmt::Debug for HostnameChanges {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("HostnameChanges")
+            .field("last", &self.last)
+            .field("poll_interval", &self.poll_interval)
+            .finish()
+    }
+}
+
+impl Iterator for HostnameChanges {
+    type Item = OsString;
+
+    fn next(&mut self) -> Option<OsString> {
+        loop {
+            if self.wait() {
+                return None;
+            }
+            match (self.source)() {
+                Ok(hostname) if self.last.as_ref() != Some(&hostname) => {
+                    self.last = Some(hostname.clone());
+                    return Some(hostname);
+                }
+                Ok(_) => {}
+                Err(error) => warn!("Failed to get hostname: {}", error),
+            }
+        }
+    }
+}
+
+/// A handle to close a [`HostnameChanges`] iterator.
+#[derive(Debug, Clone)]
+pub struct CloseHandle(Arc<(Mutex<bool>, Condvar)>);
+
+impl CloseHandle {
+    /// Close the iterator.
+    ///
+    /// Wake up the iterator if it's waiting for the next check, and make it
+    /// return `None` from now on.
+    pub fn close(&self) {
+        let (closed, condvar) = &*self.0;
+        *closed.lock().unwrap_or_else(|error| error.into_inner()) = true;
+        condvar.notify_all();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::HostnameChanges;
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn yield_changed_hostnames() {
+        let mut hostnames = vec![
+            Ok("a"),
+            Ok("a"),
+            Err(HostnameError::Unsupported),
+            Ok("b"),
+            Ok("b"),
+            Ok("a"),
+        ]
+        .into_iter();
+        let source = Box::new(move || hostnames.next().unwrap_or(Ok("a")).map(OsString::from));
+        let mut changes =
+            HostnameChanges::with_source(source).poll_interval(Duration::from_millis(1));
+        assert_eq!(changes.next().unwrap(), "b");
+        assert_eq!(changes.next().unwrap(), "a");
+        changes.close_handle().close();
+        assert_eq!(changes.next(), None);
+    }
+
+    #[test]
+    fn close_wakes_up_iterator() {
+        let mut changes = HostnameChanges::new().poll_interval(Duration::from_secs(3600));
+        let handle = changes.close_handle();
+        let start = Instant::now();
+        let closer = std::thread::spawn(move || {
+            std::thread::sleep(Duration::from_millis(10));
+            handle.close();
+        });
+        assert_eq!(changes.next(), None);
+        assert!(start.elapsed() < Duration::from_secs(60));
+        closer.join().unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Get the hostname on Windows with the exact Win32 error code.
+
+use std::ffi::OsString;
+use std::fmt;
+use std::io;
+
+use winapi::ctypes::{c_ulong, wchar_t};
+use winapi::shared::winerror::{ERROR_MORE_DATA, ERROR_NOT_ENOUGH_MEMORY};
+use winapi::um::sysinfoapi::{ComputerNamePhysicalDnsHostname, COMPUTER_NAME_FORMAT};
+
+use crate::error::HostnameError;
+use crate::sys;
+
+/// The step of getting the hostname which failed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Win32Phase {
+    /// Asking `GetComputerNameExW` for the size of the name.
+    SizeQuery,
+    /// Allocating the buffer for the name.
+    Allocate,
+    /// Fetching the name into the buffer with `GetComputerNameExW`.
+    Fetch,
+}
+
+impl fmt::Display for Win32Phase {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(match self {
+            Win32Phase::SizeQuery => "size query",
+            Win32Phase::Allocate => "allocation",
+            Win32Phase::Fetch => "fetch",
+        })
+    }
+}
+
+/// A failure of a Win32 API call, with its exact error code.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct Win32Error {
+    code: u32,
+    phase: Win32Phase,
+    buffer_size: usize,
+}
+
+impl Win32Error {
+    /// The result of `GetLastError()` after the failed call.
+    ///
+    /// Compare against the constants in `winapi::shared::winerror`, e.g.
+    /// `ERROR_ACCESS_DENIED`.
+    pub fn code(&self) -> u32 {
+        self.code
+    }
+
+    /// The step which failed.
+    pub fn phase(&self) -> Win32Phase {
+        self.phase
+    }
+
+    /// The size of the buffer in wide characters, or zero for
+    /// [`Win32Phase::SizeQuery`].
+    pub fn buffer_size(&self) -> usize {
+        self.buffer_size
+    }
+}
+
+impl fmt::Display for Win32Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} failed: {}",
+            self.phase,
+            io::Error::from_raw_os_error(self.code as i32)
+        )
+    }
+}
+
+impl std::error::Error for Win32Error {}
+
+impl From<Win32Error> for HostnameError {
+    fn from(error: Win32Error) -> Self {
+        let buffer_size = match error.phase {
+            Win32Phase::Fetch => Some(error.buffer_size),
+            _ => None,
+        };
+        HostnameError::from_errno(error.code as i32).in_call("GetComputerNameExW", buffer_size)
+    }
+}
+
+/// Get the DNS hostname of the current machine, or the exact Win32 error.
+///
+/// Like [`try_gethostname()`](crate::try_gethostname) on Windows, but
+/// return the exact code of `GetLastError()` together with the step which
+/// failed, so that callers can map known codes to guidance for users, e.g.
+/// `ERROR_ACCESS_DENIED` in restricted AppContainers.  Always ask the system,
+/// even with the `fixed-hostname` or `configure` features.
+///
+/// ```
+/// # #[cfg(windows)]
+/// match gethostname::try_gethostname_win32() {
+///     Ok(hostname) => println!("Hostname: {:?}", hostname),
+///     Err(error) => eprintln!("Error {} in {}", error.code(), error.phase()),
+/// }
+/// ```
+pub fn try_gethostname_win32() -> Result<OsString, Win32Error> {
+    computer_name_ex(ComputerNamePhysicalDnsHostname)
+}
+
+/// Get the computer name of the given `name_type` with `GetComputerNameExW`.
+pub(crate) fn computer_name_ex(name_type: COMPUTER_NAME_FORMAT) -> Result<OsString, Win32Error> {
+    use std::os::windows::ffi::OsStringExt;
+
+    let mut buffer_size: c_ulong = 0;
+    let returncode =
+        unsafe { sys::get_computer_name_ex_w(name_type, std::ptr::null_mut(), &mut buffer_size) };
+    // With a NULL buffer the call fails with ERROR_MORE_DATA, and tells us the
+    // size we need; any other error is a real failure.
+    if returncode == 0 {
+        let code = sys::last_error_code();
+        if code != ERROR_MORE_DATA {
+            return Err(Win32Error {
+                code,
+                phase: Win32Phase::SizeQuery,
+                buffer_size: 0,
+            });
+        }
+    }
+
+    let mut buffer =
+        crate::zeroed_buffer::<wchar_t>(buffer_size as usize).map_err(|_| Win32Error {
+            code: ERROR_NOT_ENOUGH_MEMORY,
+            phase: Win32Phase::Allocate,
+            buffer_size: buffer_size as usize,
+        })?;
+    let returncode = unsafe {
+        sys::get_computer_name_ex_w(
+            name_type,
+            buffer.as_mut_ptr() as *mut wchar_t,
+            &mut buffer_size,
+        )
+    };
+    // GetComputerNameExW returns a non-zero value on success!
+    if returncode == 0 {
+        return Err(Win32Error {
+            code: sys::last_error_code(),
+            phase: Win32Phase::Fetch,
+            buffer_size: buffer.len(),
+        });
+    }
+
+    let end = buffer.iter().position(|&b| b == 0).unwrap_or(buffer.len());
+    let name = OsString::from_wide(&buffer[..end]);
+    crate::zeroize::wipe_wide(buffer);
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Win32Error, Win32Phase};
+    use crate::sys::{with_failing_syscalls, with_fake_hostname};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    const ERROR_ACCESS_DENIED: u32 = 5;
+
+    #[test]
+    fn hostname() {
+        let hostname = OsString::from("foo");
+        assert_eq!(
+            with_fake_hostname(hostname.clone(), super::try_gethostname_win32),
+            Ok(hostname)
+        );
+    }
+
+    #[test]
+    fn failing_size_query() {
+        let error = with_failing_syscalls(ERROR_ACCESS_DENIED as i32, || {
+            super::try_gethostname_win32().unwrap_err()
+        });
+        assert_eq!(error.code(), ERROR_ACCESS_DENIED);
+        assert_eq!(error.phase(), Win32Phase::SizeQuery);
+        assert_eq!(
+            HostnameError::from(error),
+            HostnameError::from_errno(5).in_call("GetComputerNameExW", None)
+        );
+    }
+
+    #[test]
+    fn display() {
+        let error = Win32Error {
+            code: ERROR_ACCESS_DENIED,
+            phase: Win32Phase::Fetch,
+            buffer_size: 16,
+        };
+        assert!(error.to_string().starts_with("fetch failed: "));
+    }
+}
@@ -0,0 +1,140 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! The DNS and NetBIOS names of Windows computers.
+
+use std::ffi::{OsStr, OsString};
+
+use crate::error::HostnameError;
+
+/// The DNS and NetBIOS names of a Windows computer.
+///
+/// See [`windows_names()`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WindowsNames {
+    dns: OsString,
+    netbios: OsString,
+}
+
+impl WindowsNames {
+    /// The DNS host name, in the case the administrator gave it.
+    ///
+    /// This is the same name [`gethostname()`](crate::gethostname) returns.
+    pub fn dns(&self) -> &OsStr {
+        &self.dns
+    }
+
+    /// The NetBIOS name, in uppercase and at most 15 characters long.
+    pub fn netbios(&self) -> &OsStr {
+        &self.netbios
+    }
+}
+
+/// Get the DNS and NetBIOS names of the current computer.
+///
+/// Return the names of [GetComputerNameExW] with
+/// `ComputerNamePhysicalDnsHostname` and `ComputerNamePhysicalNetBIOS` as
+/// `NameType`.  Windows preserves the case of the DNS host name, whereas
+/// NetBIOS names are uppercase by convention.  Services which register
+/// service principal names need both names in exactly these forms, e.g.
+/// `HTTP/web-01` and `HTTP/WEB-01`.
+///
+/// ```
+/// let names = gethostname::windows_names().unwrap();
+/// println!("DNS: {:?}, NetBIOS: {:?}", names.dns(), names.netbios());
+/// ```
+///
+/// [GetComputerNameExW]: https://docs.microsoft.com/en-us/windows/desktop/api/sysinfoapi/nf-sysinfoapi-getcomputernameexw
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn windows_names() -> Result<WindowsNames, HostnameError> {
+    use winapi::um::sysinfoapi::{ComputerNamePhysicalDnsHostname, ComputerNamePhysicalNetBIOS};
+
+    Ok(WindowsNames {
+        dns: crate::get_computer_name_ex(ComputerNamePhysicalDnsHostname)?,
+        netbios: crate::get_computer_name_ex(ComputerNamePhysicalNetBIOS)?,
+    })
+}
+
+/// Get the NetBIOS name of the current computer with the legacy
+/// [GetComputerNameW].
+///
+/// Unlike [`windows_names()`] return the `ComputerNameNetBIOS` name, which is
+/// the name of the virtual server on cluster nodes rather than the physical
+/// name, and which Windows only updates at reboot after a rename.  Prefer
+/// [`windows_names()`] or [`gethostname()`](crate::gethostname), unless an
+/// integration needs exactly the name of `GetComputerNameW`, e.g. old license
+/// servers or SPNs registered by legacy software.
+///
+/// ```
+/// println!("NetBIOS: {:?}", gethostname::get_computer_name_legacy().unwrap());
+/// ```
+///
+/// [GetComputerNameW]: https://docs.microsoft.com/en-us/windows/win32/api/winbase/nf-winbase-getcomputernamew
+#[cfg_attr(feature = "tracing", tracing::instrument(level = "debug", err))]
+pub fn get_computer_name_legacy() -> Result<OsString, HostnameError> {
+    use std::os::windows::ffi::OsStringExt;
+    use winapi::shared::winerror::{ERROR_BUFFER_OVERFLOW, ERROR_MORE_DATA};
+
+    let mut buffer = vec![0; crate::hostname::MAX_NETBIOS_LEN + 1];
+    loop {
+        let mut size = buffer.len() as u32;
+        // GetComputerNameW returns a non-zero value on success!
+        if unsafe { crate::sys::get_computer_name_w(buffer.as_mut_ptr(), &mut size) } != 0 {
+            buffer.truncate(size as usize);
+            return Ok(OsString::from_wide(&buffer));
+        }
+        let code = crate::sys::last_error_code();
+        // On overflow size holds the required size, including the NUL byte
+        if (code == ERROR_BUFFER_OVERFLOW || code == ERROR_MORE_DATA)
+            && buffer.len() < size as usize
+        {
+            buffer.resize(size as usize, 0);
+        } else {
+            return Err(HostnameError::from_errno(code as i32)
+                .in_call("GetComputerNameW", Some(buffer.len())));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::sys::{with_failing_syscalls, with_fake_hostname};
+    use crate::HostnameError;
+    use pretty_assertions::assert_eq;
+    use std::ffi::OsString;
+
+    #[test]
+    fn windows_names_of_current_computer() {
+        let names = super::windows_names().unwrap();
+        assert_eq!(names.dns(), crate::gethostname());
+        let netbios = names.netbios().to_str().unwrap();
+        assert!(!netbios.is_empty() && netbios.len() <= 15);
+        assert_eq!(netbios, netbios.to_uppercase());
+    }
+
+    #[test]
+    fn legacy_computer_name() {
+        let name = super::get_computer_name_legacy().unwrap();
+        assert!(!name.is_empty());
+        let long_name = OsString::from("a".repeat(40));
+        assert_eq!(
+            with_fake_hostname(long_name.clone(), super::get_computer_name_legacy),
+            Ok(long_name)
+        );
+        assert_eq!(
+            with_failing_syscalls(5, super::get_computer_name_legacy),
+            Err(HostnameError::from_errno(5).in_call("GetComputerNameW", Some(16)))
+        );
+    }
+}
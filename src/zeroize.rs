@@ -0,0 +1,152 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Wipe hostnames from memory.
+//!
+//! With the `zeroize` feature [`Hostname`] overwrites its contents when
+//! dropped, and this crate wipes the intermediate copies it makes while getting
+//! and validating hostnames, so that hostnames don't linger in freed memory,
+//! e.g. in memory dumps.  Without the feature the helpers in this module just
+//! drop their arguments.
+//!
+//! Some copies are out of our control, e.g. in the standard library on
+//! Windows, or when converting internationalized hostnames to punycode.
+
+#[cfg(feature = "zeroize")]
+use std::borrow::Cow;
+#[cfg(feature = "zeroize")]
+use std::ffi::OsStr;
+use std::ffi::OsString;
+
+#[cfg(feature = "zeroize")]
+use ::zeroize::{Zeroize, ZeroizeOnDrop};
+
+#[cfg(feature = "zeroize")]
+use crate::error::HostnameError;
+#[cfg(feature = "zeroize")]
+use crate::hostname::{Hostname, UNKNOWN_NAME};
+
+/// Wipe and drop `s`.
+pub(crate) fn wipe(s: String) {
+    #[cfg(feature = "zeroize")]
+    {
+        let mut s = s;
+        s.zeroize();
+    }
+    #[cfg(not(feature = "zeroize"))]
+    drop(s);
+}
+
+/// Wipe and drop `s`.
+///
+/// On Windows the standard library offers no way to get at the buffer of an
+/// `OsString`, so this just drops `s`.
+pub(crate) fn wipe_os_string(s: OsString) {
+    #[cfg(all(unix, feature = "zeroize"))]
+    {
+        use std::os::unix::ffi::OsStringExt;
+        s.into_vec().zeroize();
+    }
+    #[cfg(not(all(unix, feature = "zeroize")))]
+    drop(s);
+}
+
+/// Wipe and drop a wide string `buffer`.
+#[cfg(windows)]
+pub(crate) fn wipe_wide(buffer: Vec<u16>) {
+    #[cfg(feature = "zeroize")]
+    {
+        let mut buffer = buffer;
+        buffer.zeroize();
+    }
+    #[cfg(not(feature = "zeroize"))]
+    drop(buffer);
+}
+
+/// Call `f` with the hostname of the current machine, and wipe it afterwards.
+///
+/// Like [`try_gethostname()`](crate::try_gethostname), but lend the hostname
+/// to `f` instead of returning it, so that it doesn't outlive `f` in memory.
+/// Return the result of `f`.
+///
+/// ```
+/// let len = gethostname::try_gethostname_with(|hostname| hostname.len()).unwrap();
+/// assert!(0 < len);
+/// ```
+#[cfg(feature = "zeroize")]
+pub fn try_gethostname_with<F, R>(f: F) -> Result<R, HostnameError>
+where
+    F: FnOnce(&OsStr) -> R,
+{
+    let hostname = crate::try_gethostname()?;
+    let result = f(&hostname);
+    wipe_os_string(hostname);
+    Ok(result)
+}
+
+/// Overwrite the hostname with zeros.
+///
+/// This leaves [`Hostname::UNKNOWN`] behind, so that the hostname stays
+/// valid.
+#[cfg(feature = "zeroize")]
+impl Zeroize for Hostname {
+    fn zeroize(&mut self) {
+        let name = self.as_mut_cow();
+        // Static names are no secret, and we can't overwrite them anyway
+        if let Cow::Owned(name) = name {
+            name.zeroize();
+        }
+        *name = Cow::Borrowed(UNKNOWN_NAME);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Hostname {
+    fn drop(&mut self) {
+        self.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl ZeroizeOnDrop for Hostname {}
+
+#[cfg(all(test, feature = "zeroize"))]
+mod tests {
+    use crate::Hostname;
+    use ::zeroize::Zeroize;
+    use pretty_assertions::assert_eq;
+
+    #[test]
+    fn zeroize_hostname() {
+        let mut hostname: Hostname = "foo.example".parse().unwrap();
+        hostname.zeroize();
+        assert_eq!(hostname, Hostname::UNKNOWN);
+        assert_eq!(hostname.as_str().parse::<Hostname>().unwrap(), hostname);
+        let mut hostname = Hostname::from_static("foo.example");
+        hostname.zeroize();
+        assert_eq!(hostname, Hostname::UNKNOWN);
+    }
+
+    #[test]
+    fn into_string_keeps_contents() {
+        let hostname: Hostname = "foo.example".parse().unwrap();
+        assert_eq!(hostname.into_string(), "foo.example");
+    }
+
+    #[test]
+    fn hostname_with() {
+        let hostname = super::try_gethostname_with(|hostname| hostname.to_os_string()).unwrap();
+        assert_eq!(hostname, crate::gethostname());
+    }
+}
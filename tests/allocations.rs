@@ -0,0 +1,91 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Check how often getting the hostname allocates.
+//!
+//! Count allocations per thread with a global allocator, so that tests running
+//! in parallel don't disturb each other.
+
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+struct CountingAllocator;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAllocator {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        // Ignore allocations while the thread shuts down
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+
+    unsafe fn realloc(&self, ptr: *mut u8, layout: Layout, new_size: usize) -> *mut u8 {
+        let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+        System.realloc(ptr, layout, new_size)
+    }
+}
+
+#[global_allocator]
+static ALLOCATOR: CountingAllocator = CountingAllocator;
+
+/// Count the allocations of `f` on the current thread.
+fn allocations<R>(f: impl FnOnce() -> R) -> usize {
+    let before = ALLOCATIONS.with(Cell::get);
+    let result = f();
+    let after = ALLOCATIONS.with(Cell::get);
+    drop(result);
+    after - before
+}
+
+#[test]
+// On Windows the name is first read into a wide buffer, and then converted
+#[cfg(unix)]
+fn try_gethostname_allocates_once() {
+    // Warm up caches, e.g. of sysconf
+    gethostname::try_gethostname().unwrap();
+    assert_eq!(allocations(gethostname::try_gethostname), 1);
+}
+
+#[test]
+fn try_gethostname_into_does_not_allocate() {
+    let mut buffer = [0; 256];
+    gethostname::try_gethostname_into(&mut buffer).unwrap();
+    assert_eq!(
+        allocations(|| gethostname::try_gethostname_into(&mut buffer).is_ok()),
+        0
+    );
+}
+
+#[test]
+#[cfg(unix)]
+fn gethostname_tls_does_not_allocate_after_first_call() {
+    gethostname::gethostname_tls(|_| ()).unwrap();
+    assert_eq!(
+        allocations(|| gethostname::gethostname_tls(|hostname| hostname.len())),
+        0
+    );
+    let mut hostname = std::ffi::OsString::new();
+    gethostname::gethostname_tls_into(&mut hostname).unwrap();
+    assert_eq!(
+        allocations(|| gethostname::gethostname_tls_into(&mut hostname)),
+        0
+    );
+}
@@ -0,0 +1,47 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Configure the hostname process-wide.
+//!
+//! This test lives in its own process, because the configuration applies to
+//! the whole process.
+
+#![cfg(feature = "configure")]
+
+use gethostname::config::{configure, Config, HostnameSource};
+
+#[test]
+fn configuration_applies_to_all_hostname_functions() {
+    std::env::set_var("GETHOSTNAME_TEST_CONFIGURE", "Configured.Example.");
+    let config = Config::new()
+        .source_chain([HostnameSource::Env("GETHOSTNAME_TEST_CONFIGURE".into())])
+        .normalize(true);
+    configure(config.clone()).unwrap();
+    assert_eq!(configure(config.clone()), Err(config));
+
+    assert_eq!(gethostname::gethostname(), "configured.example");
+    assert_eq!(
+        gethostname::try_gethostname().unwrap(),
+        "configured.example"
+    );
+    let mut buffer = [0; 256];
+    assert_eq!(
+        gethostname::try_gethostname_into(&mut buffer).unwrap(),
+        "configured.example"
+    );
+    assert_eq!(
+        gethostname::gethostname_tls(|hostname| hostname.to_owned()).unwrap(),
+        "configured.example"
+    );
+}
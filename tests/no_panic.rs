@@ -0,0 +1,39 @@
+// Copyright 2019  Sebastian Wiesner <sebastian@swsnr.de>
+
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at
+
+// 	http://www.apache.org/licenses/LICENSE-2.0
+
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Prove that `try_gethostname()` cannot panic.
+//!
+//! With the `no-panic` feature `try_gethostname()` fails to link if the
+//! optimizer cannot remove all panic paths from it, so merely building this
+//! test is the proof.  Run with
+//!
+//! ```console
+//! $ cargo test --release --features no-panic --test no_panic
+//! ```
+//!
+//! The proof requires optimizations, hence `--release`, and doesn't hold with
+//! the `log` feature, because loggers may panic, nor with the `configure`
+//! feature, because configured sources may panic.
+
+#![cfg(all(
+    feature = "no-panic",
+    not(feature = "log"),
+    not(feature = "configure"),
+    not(debug_assertions)
+))]
+
+#[test]
+fn try_gethostname_cannot_panic() {
+    assert!(!gethostname::try_gethostname().unwrap().is_empty());
+}